@@ -6,8 +6,10 @@ use ink::storage::traits::StorageLayout;
 use pink_extension::chain_extension::{signing, SigType};
 use pink_extension::ResultExt;
 use scale::{Decode, Encode};
+use serde::{Deserialize, Serialize};
 
-#[derive(Clone)]
+#[derive(Clone, Debug, PartialEq, Eq, Encode, Decode, Serialize, Deserialize)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
 pub enum AccountType {
     Account20,
     Account32,
@@ -36,7 +38,7 @@ impl AccountInfo {
             ChainType::Sub => self.account32.into(),
         };
         chain
-            .get_balance(asset, account)
+            .get_balance(asset, account, context.rpc_health)
             .map_err(|_| "FetchBalanceFailed")
     }
 
@@ -50,7 +52,7 @@ impl AccountInfo {
             ChainType::Sub => self.account32.into(),
         };
         chain
-            .get_nonce(account.clone())
+            .get_nonce(account.clone(), context.rpc_health)
             .log_err(&format!(
                 "Fetch nonce failed, chain: {:?}, account: {:?}",
                 &chain_name,
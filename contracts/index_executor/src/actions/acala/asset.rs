@@ -4,6 +4,7 @@ use alloc::{collections::BTreeMap, vec::Vec};
 
 use scale::Decode;
 use scale::Encode;
+use serde::{Deserialize, Serialize};
 use xcm::v3::{prelude::*, MultiLocation};
 
 // Copy from https://github.com/AcalaNetwork/Acala/blob/master/primitives/src/currency.rs ,
@@ -17,7 +18,9 @@ use xcm::v3::{prelude::*, MultiLocation};
 // 128 - 147: Karura & Kusama native tokens
 // 148 - 167: External tokens (e.g. bridged)
 // 168 - 255: Kusama parachain tokens
-#[derive(Debug, Encode, Decode, Eq, PartialEq, Copy, Clone, PartialOrd, Ord)]
+#[derive(
+    Debug, Encode, Decode, Eq, PartialEq, Copy, Clone, PartialOrd, Ord, Serialize, Deserialize,
+)]
 #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
 #[repr(u8)]
 #[allow(clippy::upper_case_acronyms)]
@@ -122,12 +125,39 @@ impl AcalaAssets {
         let lc_pha: MultiLocation = MultiLocation::new(1, X1(Parachain(2004)));
         let lc_aca: MultiLocation =
             MultiLocation::new(1, X2(Parachain(2000), slice_to_generalkey(&[0x00, 0x00])));
+        let lc_ausd: MultiLocation =
+            MultiLocation::new(1, X2(Parachain(2000), slice_to_generalkey(&[0x00, 0x01])));
         let lc_dot: MultiLocation =
             MultiLocation::new(1, X2(Parachain(2000), slice_to_generalkey(&[0x00, 0x02])));
+        let lc_ldot: MultiLocation =
+            MultiLocation::new(1, X2(Parachain(2000), slice_to_generalkey(&[0x00, 0x03])));
+        let lc_kusd: MultiLocation =
+            MultiLocation::new(1, X2(Parachain(2000), slice_to_generalkey(&[0x00, 0x81])));
+        let lc_ksm: MultiLocation =
+            MultiLocation::new(1, X2(Parachain(2000), slice_to_generalkey(&[0x00, 0x82])));
+        let lc_lksm: MultiLocation =
+            MultiLocation::new(1, X2(Parachain(2000), slice_to_generalkey(&[0x00, 0x83])));
         BTreeMap::from([
             (lc_aca, (lc_aca, TokenSymbol::ACA, TokenType::Utility, None)),
+            (
+                lc_ausd,
+                (lc_ausd, TokenSymbol::AUSD, TokenType::Native, None),
+            ),
             (lc_dot, (lc_dot, TokenSymbol::DOT, TokenType::Native, None)),
+            (
+                lc_ldot,
+                (lc_ldot, TokenSymbol::LDOT, TokenType::Native, None),
+            ),
             (lc_kar, (lc_kar, TokenSymbol::KAR, TokenType::Native, None)),
+            (
+                lc_kusd,
+                (lc_kusd, TokenSymbol::KUSD, TokenType::Native, None),
+            ),
+            (lc_ksm, (lc_ksm, TokenSymbol::KSM, TokenType::Native, None)),
+            (
+                lc_lksm,
+                (lc_lksm, TokenSymbol::LKSM, TokenType::Native, None),
+            ),
             (
                 lc_pha,
                 (lc_pha, TokenSymbol::PHA, TokenType::Foreign, Some(FA_PHA)),
@@ -147,11 +177,21 @@ impl AcalaAssets {
         None
     }
 
+    pub fn get_location_by_symbol(symbol: TokenSymbol) -> Option<MultiLocation> {
+        AcalaAssets::get_map()
+            .into_values()
+            .find(|token| token.1 == symbol)
+            .map(|token| token.0)
+    }
+
+    /// Currency id of the asset on chain, foreign tokens are identified by their foreign asset id
     pub fn get_currency_id(location: &MultiLocation) -> Option<CurrencyId> {
-        if let Some(attrs) = AcalaAssets::get_asset_attrs(location) {
-            return Some(CurrencyId::Token(attrs.0));
+        match AcalaAssets::get_asset_attrs(location)? {
+            (_, TokenType::Foreign, Some(foreign_asset_id)) => {
+                Some(CurrencyId::ForeignAsset(foreign_asset_id))
+            }
+            (symbol, _, _) => Some(CurrencyId::Token(symbol)),
         }
-        None
     }
 }
 
@@ -159,8 +199,15 @@ impl AssetRegistry<CurrencyId> for AcalaAssets {
     fn get_assetid(&self, _chain: &str, location: &MultiLocation) -> Option<CurrencyId> {
         Self::get_currency_id(location)
     }
-    fn get_location(&self, _chain: &str, _asset_id: CurrencyId) -> Option<MultiLocation> {
-        // TODO.implement
-        None
+    fn get_location(&self, _chain: &str, asset_id: CurrencyId) -> Option<MultiLocation> {
+        match asset_id {
+            CurrencyId::Token(symbol) => Self::get_location_by_symbol(symbol),
+            CurrencyId::ForeignAsset(id) => Self::get_map()
+                .into_values()
+                .find(|token| token.3 == Some(id))
+                .map(|token| token.0),
+            // Other assets are resolved from the asset registry on chain by `AssetResolver`
+            _ => None,
+        }
     }
 }
@@ -1,29 +1,210 @@
 use super::asset::{AcalaAssets, AggregatedSwapPath, CurrencyId, TokenSymbol};
-use alloc::{format, vec};
+use alloc::{format, string::String, vec, vec::Vec};
 use pink_extension::ResultExt;
+use pink_subrpc::{
+    get_storage,
+    hasher::Twox64Concat,
+    storage::{storage_map_prefix, storage_prefix},
+};
+use primitive_types::U256;
 use scale::{Compact, Decode, Encode};
+use serde::{Deserialize, Serialize};
+use sp_runtime::Permill;
 use xcm::v3::prelude::*;
 
 use crate::call::{Call, CallBuilder, CallParams, SubCall, SubExtrinsic};
+use crate::rpc::{self, RpcEndpoint, RpcHealth};
 use crate::step::Step;
 
+/// Trading fee of Acala dex, 0.3%
+const DEX_FEE_PERTHOUSAND: u32 = 3;
+
+/// Tolerated slippage between the quoted and the received amount of dex swaps
+const MAX_SLIPPAGE_PERCENT: u32 = 1;
+
+/// Reserves of the pool of two tokens, in the order of the tokens
+type Reserves = (u128, u128);
+
+/// Taiga stable pool of a staking token and its liquid token, e.g. pool 0 of DOT/LDOT on Acala
+/// and pool 0 of KSM/LKSM on Karura
+#[derive(Clone, Copy, Debug, Decode, Encode, Eq, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+pub struct TaigaPool {
+    pub pool_id: u32,
+    // Asset at index 0 of the pool
+    pub token: TokenSymbol,
+    // Asset at index 1 of the pool
+    pub liquid_token: TokenSymbol,
+}
+
+/// Tokens the swaps are routed through on the chain
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Routes {
+    // Token paired with most tokens in the dex, e.g. AUSD on Acala and KUSD on Karura
+    hub: CurrencyId,
+    stable_pool: Option<TaigaPool>,
+}
+
+/// Swap any pair of tokens of `AcalaAssets` through the aggregated dex of Acala or Karura.
+///
+/// Tokens are swapped in the pool of the pair if there is one, otherwise through their pools
+/// with the hub token. The staking token is swapped into its liquid token with the Taiga stable
+/// pool if there is no dex route for it.
 #[derive(Clone)]
-pub struct AcalaSwap;
+pub struct AcalaSwap {
+    endpoints: Vec<RpcEndpoint>,
+    health: RpcHealth,
+    routes: Routes,
+}
 
 #[allow(dead_code)]
 impl AcalaSwap {
-    pub fn new() -> Self
+    pub fn new(
+        endpoints: Vec<RpcEndpoint>,
+        health: &RpcHealth,
+        hub_token: TokenSymbol,
+        stable_pool: Option<TaigaPool>,
+    ) -> Self
     where
         Self: Sized,
     {
-        Self {}
+        Self {
+            endpoints,
+            health: health.clone(),
+            routes: Routes {
+                hub: CurrencyId::Token(hub_token),
+                stable_pool,
+            },
+        }
     }
+
+    /// Reserves of the dex pool of the two tokens, `None` if the pool has no liquidity
+    fn liquidity_pool(
+        &self,
+        token0: CurrencyId,
+        token1: CurrencyId,
+    ) -> Result<Option<Reserves>, &'static str> {
+        // Pools are keyed by trading pair with tokens sorted
+        let (pair, reversed) = if token0 < token1 {
+            ((token0, token1), false)
+        } else {
+            ((token1, token0), true)
+        };
+        let raw_storage = rpc::with_failover(&self.endpoints, &self.health, |endpoint| {
+            get_storage(
+                endpoint,
+                &storage_map_prefix::<Twox64Concat>(
+                    &storage_prefix("Dex", "LiquidityPool")[..],
+                    &pair.encode(),
+                ),
+                None,
+            )
+            .log_err("AcalaSwap: read storage [dex liquidity pool] failed")
+            .or(Err("FetchDataFailed"))
+        })?;
+        let reserves = match raw_storage {
+            Some(raw_storage) => <Reserves>::decode(&mut raw_storage.as_slice())
+                .log_err("AcalaSwap: decode storage [dex liquidity pool] failed")
+                .or(Err("DecodeStorageFailed"))?,
+            None => return Ok(None),
+        };
+        if reserves.0 == 0 || reserves.1 == 0 {
+            return Ok(None);
+        }
+        Ok(Some(if reversed {
+            (reserves.1, reserves.0)
+        } else {
+            reserves
+        }))
+    }
+}
+
+/// Find the dex path from `token0` to `token1`, either the pool of the pair or through `hub`
+fn dex_path(
+    token0: CurrencyId,
+    token1: CurrencyId,
+    hub: CurrencyId,
+    pool: &impl Fn(CurrencyId, CurrencyId) -> Result<Option<Reserves>, &'static str>,
+) -> Result<Option<Vec<CurrencyId>>, &'static str> {
+    if pool(token0, token1)?.is_some() {
+        return Ok(Some(vec![token0, token1]));
+    }
+    if token0 != hub
+        && token1 != hub
+        && pool(token0, hub)?.is_some()
+        && pool(hub, token1)?.is_some()
+    {
+        return Ok(Some(vec![token0, hub, token1]));
+    }
+    Ok(None)
+}
+
+/// Amount received by swapping `amount_in` along the dex path
+fn quote(
+    path: &[CurrencyId],
+    amount_in: u128,
+    pool: &impl Fn(CurrencyId, CurrencyId) -> Result<Option<Reserves>, &'static str>,
+) -> Result<u128, &'static str> {
+    let mut amount = amount_in;
+    for pair in path.windows(2) {
+        let (reserve_in, reserve_out) = pool(pair[0], pair[1])?.ok_or("NoLiquidity")?;
+        let amount_in_with_fee = U256::from(amount) * U256::from(1000 - DEX_FEE_PERTHOUSAND);
+        let numerator = amount_in_with_fee * U256::from(reserve_out);
+        let denominator = U256::from(reserve_in) * U256::from(1000) + amount_in_with_fee;
+        amount = (numerator / denominator).try_into().or(Err("Overflow"))?;
+    }
+    Ok(amount)
+}
+
+/// Build the swap paths from `token0` to `token1` and the minimum amount to receive.
+///
+/// Routes through Taiga are not quoted, the minimum amount is left to 1 for them
+fn swap_paths(
+    token0: CurrencyId,
+    token1: CurrencyId,
+    amount_in: u128,
+    routes: &Routes,
+    pool: &impl Fn(CurrencyId, CurrencyId) -> Result<Option<Reserves>, &'static str>,
+) -> Result<(Vec<AggregatedSwapPath>, u128), &'static str> {
+    if token0 == token1 {
+        return Err("InvalidTradingPair");
+    }
+    let hub = routes.hub;
+    if let Some(path) = dex_path(token0, token1, hub, pool)? {
+        let amount_out = quote(&path, amount_in, pool)?;
+        let min_amount_out = amount_out - Permill::from_percent(MAX_SLIPPAGE_PERCENT) * amount_out;
+        return Ok((vec![AggregatedSwapPath::Dex(path)], min_amount_out.max(1)));
+    }
+    let stable_pool = routes.stable_pool.ok_or("NoSwapPath")?;
+    let (id, token, liquid) = (
+        stable_pool.pool_id,
+        CurrencyId::Token(stable_pool.token),
+        CurrencyId::Token(stable_pool.liquid_token),
+    );
+    let paths = if (token0, token1) == (token, liquid) {
+        vec![AggregatedSwapPath::Taiga(id, 0, 1)]
+    } else if (token0, token1) == (liquid, token) {
+        vec![AggregatedSwapPath::Taiga(id, 1, 0)]
+    } else if token0 == token {
+        // Dex path starts with the liquid token, which is what Taiga outputs for the spent token
+        vec![
+            AggregatedSwapPath::Taiga(id, 0, 1),
+            AggregatedSwapPath::Dex(dex_path(liquid, token1, hub, pool)?.ok_or("NoSwapPath")?),
+        ]
+    } else if token1 == token {
+        vec![
+            AggregatedSwapPath::Dex(dex_path(token0, liquid, hub, pool)?.ok_or("NoSwapPath")?),
+            AggregatedSwapPath::Taiga(id, 1, 0),
+        ]
+    } else {
+        return Err("NoSwapPath");
+    };
+    Ok((paths, 1))
 }
 
 impl CallBuilder for AcalaSwap {
     fn build_call(&self, step: Step) -> Result<Call, &'static str> {
-        let amount_out = Compact(1_u8);
-        let amount_in = Compact(step.spend_amount.ok_or("MissingSpendAmount")?);
+        let spend_amount = step.spend_amount.ok_or("MissingSpendAmount")?;
 
         let asset0_location: MultiLocation = Decode::decode(&mut step.spend_asset.as_slice())
             .log_err(&format!(
@@ -41,22 +222,17 @@ impl CallBuilder for AcalaSwap {
         let token0 = AcalaAssets::get_currency_id(&asset0_location).ok_or("AssetNotRecognized")?;
         let token1 = AcalaAssets::get_currency_id(&asset1_location).ok_or("AssetNotRecognized")?;
 
-        // FIXME: hardcode for demo
-        if token0 != CurrencyId::Token(TokenSymbol::DOT)
-            || token1 != CurrencyId::Token(TokenSymbol::ACA)
-        {
-            pink_extension::debug!("AcalaDexExecutor: Unsupported trading pair",);
-            return Err("Unimplemented");
-        }
-
-        let taiga_path = AggregatedSwapPath::Taiga(0, 0, 1);
-        // FIXME: Looks like first node is LDOT, represents dex will spend DOT
-        let dex_path = AggregatedSwapPath::Dex(vec![
-            CurrencyId::Token(TokenSymbol::LDOT),
-            CurrencyId::Token(TokenSymbol::AUSD),
-            token1,
-        ]);
-        let path = vec![taiga_path, dex_path];
+        let (path, min_amount_out) =
+            swap_paths(token0, token1, spend_amount, &self.routes, &|a, b| {
+                self.liquidity_pool(a, b)
+            })?;
+        pink_extension::debug!(
+            "AcalaSwap: swap path {:?}, min amount out {:?}",
+            &path,
+            min_amount_out
+        );
+        let amount_in = Compact(spend_amount);
+        let amount_out = Compact(min_amount_out);
 
         Ok(Call {
             params: CallParams::Sub(SubCall {
@@ -75,3 +251,134 @@ impl CallBuilder for AcalaSwap {
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ACA: CurrencyId = CurrencyId::Token(TokenSymbol::ACA);
+    const AUSD: CurrencyId = CurrencyId::Token(TokenSymbol::AUSD);
+    const DOT: CurrencyId = CurrencyId::Token(TokenSymbol::DOT);
+    const LDOT: CurrencyId = CurrencyId::Token(TokenSymbol::LDOT);
+    const PHA: CurrencyId = CurrencyId::ForeignAsset(9);
+    const KAR: CurrencyId = CurrencyId::Token(TokenSymbol::KAR);
+    const KUSD: CurrencyId = CurrencyId::Token(TokenSymbol::KUSD);
+    const KSM: CurrencyId = CurrencyId::Token(TokenSymbol::KSM);
+    const LKSM: CurrencyId = CurrencyId::Token(TokenSymbol::LKSM);
+
+    const ACALA: Routes = Routes {
+        hub: AUSD,
+        stable_pool: Some(TaigaPool {
+            pool_id: 0,
+            token: TokenSymbol::DOT,
+            liquid_token: TokenSymbol::LDOT,
+        }),
+    };
+    const KARURA: Routes = Routes {
+        hub: KUSD,
+        stable_pool: Some(TaigaPool {
+            pool_id: 0,
+            token: TokenSymbol::KSM,
+            liquid_token: TokenSymbol::LKSM,
+        }),
+    };
+
+    // Pools of ACA/AUSD, PHA/AUSD and LDOT/AUSD on Acala, KAR/KUSD and LKSM/KUSD on Karura
+    fn mock_pool(a: CurrencyId, b: CurrencyId) -> Result<Option<Reserves>, &'static str> {
+        let reserves = [
+            (ACA, AUSD, (1_000_000, 50_000)),
+            (PHA, AUSD, (1_000_000, 100_000)),
+            (LDOT, AUSD, (1_000, 5_000)),
+            (KAR, KUSD, (1_000_000, 50_000)),
+            (LKSM, KUSD, (1_000, 20_000)),
+        ];
+        Ok(reserves.iter().find_map(|(t0, t1, r)| {
+            if (*t0, *t1) == (a, b) {
+                Some(*r)
+            } else if (*t1, *t0) == (a, b) {
+                Some((r.1, r.0))
+            } else {
+                None
+            }
+        }))
+    }
+
+    #[test]
+    fn test_swap_paths() {
+        // Direct pool, quoted with dex fee and slippage
+        let (paths, min_amount_out) = swap_paths(ACA, AUSD, 1_000, &ACALA, &mock_pool).unwrap();
+        assert_eq!(paths, vec![AggregatedSwapPath::Dex(vec![ACA, AUSD])]);
+        assert_eq!(min_amount_out, 49 - Permill::from_percent(1) * 49);
+
+        // Through AUSD
+        let (paths, _) = swap_paths(PHA, ACA, 1_000, &ACALA, &mock_pool).unwrap();
+        assert_eq!(paths, vec![AggregatedSwapPath::Dex(vec![PHA, AUSD, ACA])]);
+
+        // DOT through Taiga
+        let (paths, min_amount_out) = swap_paths(DOT, ACA, 1_000, &ACALA, &mock_pool).unwrap();
+        assert_eq!(
+            paths,
+            vec![
+                AggregatedSwapPath::Taiga(0, 0, 1),
+                AggregatedSwapPath::Dex(vec![LDOT, AUSD, ACA]),
+            ]
+        );
+        assert_eq!(min_amount_out, 1);
+        let (paths, _) = swap_paths(PHA, DOT, 1_000, &ACALA, &mock_pool).unwrap();
+        assert_eq!(
+            paths,
+            vec![
+                AggregatedSwapPath::Dex(vec![PHA, AUSD, LDOT]),
+                AggregatedSwapPath::Taiga(0, 1, 0),
+            ]
+        );
+
+        assert_eq!(
+            swap_paths(ACA, ACA, 1_000, &ACALA, &mock_pool),
+            Err("InvalidTradingPair")
+        );
+        assert_eq!(
+            swap_paths(
+                ACA,
+                CurrencyId::Token(TokenSymbol::KAR),
+                1_000,
+                &ACALA,
+                &mock_pool
+            ),
+            Err("NoSwapPath")
+        );
+    }
+
+    #[test]
+    fn test_karura_swap_paths() {
+        // Through KUSD
+        let (paths, _) = swap_paths(KAR, LKSM, 1_000, &KARURA, &mock_pool).unwrap();
+        assert_eq!(paths, vec![AggregatedSwapPath::Dex(vec![KAR, KUSD, LKSM])]);
+
+        // KSM through Taiga pool of KSM/LKSM
+        let (paths, _) = swap_paths(KSM, KAR, 1_000, &KARURA, &mock_pool).unwrap();
+        assert_eq!(
+            paths,
+            vec![
+                AggregatedSwapPath::Taiga(0, 0, 1),
+                AggregatedSwapPath::Dex(vec![LKSM, KUSD, KAR]),
+            ]
+        );
+        let (paths, _) = swap_paths(LKSM, KSM, 1_000, &KARURA, &mock_pool).unwrap();
+        assert_eq!(paths, vec![AggregatedSwapPath::Taiga(0, 1, 0)]);
+
+        // Karura tokens are not routed with the tokens of Acala
+        assert_eq!(
+            swap_paths(KSM, KAR, 1_000, &ACALA, &mock_pool),
+            Err("NoSwapPath")
+        );
+        let no_stable_pool = Routes {
+            hub: KUSD,
+            stable_pool: None,
+        };
+        assert_eq!(
+            swap_paths(KSM, KAR, 1_000, &no_stable_pool, &mock_pool),
+            Err("NoSwapPath")
+        );
+    }
+}
@@ -0,0 +1,106 @@
+use crate::account::AccountType;
+use crate::actions::base::xcm::{
+    account_junction, XcmConfig, XcmDestWeights, XcmPallet, XcmTransfer,
+};
+use crate::call::{Call, CallBuilder};
+use crate::step::Step;
+use scale::Decode;
+use xcm::v3::prelude::*;
+
+/// Pallet index of polkadotXcm on AssetHub
+const POLKADOT_XCM_PALLET_ID: u8 = 0x1f;
+
+/// Reserve transfer assets of AssetHub, e.g. USDT and USDC, to parachains with
+/// `polkadotXcm.limited_reserve_transfer_assets`
+#[derive(Clone)]
+pub struct AssetHubXcm {
+    dest_chain_id: u32,
+    account_type: AccountType,
+    config: XcmConfig,
+    dest_weights: XcmDestWeights,
+}
+
+impl AssetHubXcm {
+    pub fn new(dest_chain_id: u32, account_type: AccountType) -> Self
+    where
+        Self: Sized,
+    {
+        Self {
+            dest_chain_id,
+            account_type,
+            config: XcmConfig::default(),
+            dest_weights: XcmDestWeights::default(),
+        }
+    }
+
+    /// Fee asset of the transfer
+    pub fn with_xcm_config(mut self, config: XcmConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Weight limits of the destination chains, the limit of the step's destination is applied
+    pub fn with_dest_weights(mut self, dest_weights: XcmDestWeights) -> Self {
+        self.dest_weights = dest_weights;
+        self
+    }
+}
+
+impl CallBuilder for AssetHubXcm {
+    fn build_call(&self, step: Step) -> Result<Call, &'static str> {
+        let asset_location: MultiLocation =
+            Decode::decode(&mut step.spend_asset.as_slice()).map_err(|_| "InvalidMultilocation")?;
+        let multi_asset = MultiAsset {
+            id: AssetId::Concrete(asset_location),
+            fun: Fungibility::Fungible(step.spend_amount.ok_or("MissingSpendAmount")?),
+        };
+
+        XcmTransfer::new(
+            MultiLocation::new(1, X1(Parachain(self.dest_chain_id))),
+            account_junction(&step.recipient, &self.account_type)?,
+            multi_asset,
+        )
+        .with_weight_limit(self.dest_weights.weight_limit(&step.dest_chain))
+        .with_config(&self.config)?
+        .build(XcmPallet::PolkadotXcm {
+            pallet_id: POLKADOT_XCM_PALLET_ID,
+            xcm_version: 3,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::call::CallParams;
+    use crate::constants::MOONBEAM_PARACHAIN_ID;
+    use scale::Encode;
+
+    #[test]
+    fn test_usdt_to_moonbeam() {
+        let xcm = AssetHubXcm::new(MOONBEAM_PARACHAIN_ID, AccountType::Account20);
+        let usdt = MultiLocation::new(0, X2(PalletInstance(50), GeneralIndex(1984)));
+        let call = xcm
+            .build_call(Step {
+                exe: String::from(""),
+                source_chain: String::from("AssetHub"),
+                dest_chain: String::from("Moonbeam"),
+                spend_asset: usdt.encode(),
+                receive_asset: hex::decode("FFFFFFFFea09fb06d082fd1275cd48b191cbcd1d").unwrap(),
+                sender: None,
+                recipient: hex::decode("bEA1C40ecf9c4603ec25264860B9b6623Ff733F5").unwrap(),
+                // 1 USDT
+                spend_amount: Some(1_000_000 as u128),
+                origin_balance: None,
+                nonce: None,
+            })
+            .unwrap();
+
+        match &call.params {
+            CallParams::Sub(sub_call) => {
+                assert_eq!(&sub_call.calldata[..2], &[0x1f, 0x08]);
+            }
+            _ => assert!(false),
+        }
+    }
+}
@@ -1,11 +1,12 @@
 use pink_extension::AccountId;
 use xcm::v3::prelude::*;
 
-use super::asset::AstarAssets;
+use crate::assets::AssetResolver;
 use crate::call::{Call, CallBuilder, CallParams, SubCall, SubExtrinsic};
+use crate::rpc::{RpcEndpoint, RpcHealth};
 use crate::step::Step;
 use crate::utils::{h160_to_sr25519_pub, ToArray};
-use alloc::{string::String, vec::Vec};
+use alloc::vec::Vec;
 use scale::{Compact, Decode, Encode};
 
 type MultiAddress = sp_runtime::MultiAddress<AccountId, u32>;
@@ -16,12 +17,12 @@ pub struct AstarSubToEvmTransactor {
 }
 
 impl AstarSubToEvmTransactor {
-    pub fn new(native: Vec<u8>) -> Self
+    pub fn new(endpoints: Vec<RpcEndpoint>, health: &RpcHealth, native: Vec<u8>) -> Self
     where
         Self: Sized,
     {
         Self {
-            transactor: AstarTransactor::new(native),
+            transactor: AstarTransactor::new(endpoints, health, native),
         }
     }
 }
@@ -37,15 +38,19 @@ impl CallBuilder for AstarSubToEvmTransactor {
 
 #[derive(Clone)]
 pub struct AstarTransactor {
+    assets: AssetResolver,
     native: Vec<u8>,
 }
 
 impl AstarTransactor {
-    pub fn new(native: Vec<u8>) -> Self
+    pub fn new(endpoints: Vec<RpcEndpoint>, health: &RpcHealth, native: Vec<u8>) -> Self
     where
         Self: Sized,
     {
-        Self { native }
+        Self {
+            assets: AssetResolver::with_endpoints(endpoints, health),
+            native,
+        }
     }
 }
 
@@ -72,8 +77,10 @@ impl CallBuilder for AstarTransactor {
                 call_index: None,
             })
         } else {
-            let asset_id = AstarAssets::new()
-                .get_assetid(&String::from("Astar"), &asset_location)
+            let asset_id: u128 = self
+                .assets
+                .lookup_assetid(&step.source_chain, &asset_location)?
+                .and_then(|id| Decode::decode(&mut id.as_slice()).ok())
                 .ok_or("AssetNotFound")?;
             Ok(Call {
                 params: CallParams::Sub(SubCall {
@@ -105,11 +112,15 @@ mod tests {
         pink_extension_runtime::mock_ext::mock_all_ext();
 
         let astr_location = MultiLocation::new(1, X1(Parachain(2006)));
-        let transactor = AstarTransactor::new(astr_location.encode());
+        let endpoint = "https://astar.public.blastapi.io";
+        let transactor = AstarTransactor::new(
+            vec![RpcEndpoint::new(endpoint, 0)],
+            &RpcHealth::default(),
+            astr_location.encode(),
+        );
         let recipient =
             hex::decode("b63a28ab657209e5894e9021fb680180e2ef9c66ae80a7f6db41f2ed3c9e8707")
                 .unwrap();
-        let endpoint = "https://astar.public.blastapi.io";
 
         let secret_key = std::env::vars().find(|x| x.0 == "SECRET_KEY");
         let secret_key = secret_key.unwrap().1;
@@ -163,11 +174,15 @@ mod tests {
 
         let astr_location = MultiLocation::new(1, X1(Parachain(2006)));
         let pha_location = MultiLocation::new(1, X1(Parachain(2035)));
-        let transactor = AstarTransactor::new(astr_location.encode());
+        let endpoint = "https://astar.public.blastapi.io";
+        let transactor = AstarTransactor::new(
+            vec![RpcEndpoint::new(endpoint, 0)],
+            &RpcHealth::default(),
+            astr_location.encode(),
+        );
         let recipient =
             hex::decode("b63a28ab657209e5894e9021fb680180e2ef9c66ae80a7f6db41f2ed3c9e8707")
                 .unwrap();
-        let endpoint = "https://astar.public.blastapi.io";
 
         let secret_key = std::env::vars().find(|x| x.0 == "SECRET_KEY");
         let secret_key = secret_key.unwrap().1;
@@ -221,9 +236,13 @@ mod tests {
 
         let astr_location = MultiLocation::new(1, X1(Parachain(2006)));
         let pha_location = MultiLocation::new(1, X1(Parachain(2035)));
-        let transactor = AstarSubToEvmTransactor::new(astr_location.encode());
-        let h160_recipient = hex::decode("e887376a93bDa91ed66D814528D7aeEfe59990a5").unwrap();
         let endpoint = "https://astar.public.blastapi.io";
+        let transactor = AstarSubToEvmTransactor::new(
+            vec![RpcEndpoint::new(endpoint, 0)],
+            &RpcHealth::default(),
+            astr_location.encode(),
+        );
+        let h160_recipient = hex::decode("e887376a93bDa91ed66D814528D7aeEfe59990a5").unwrap();
 
         let secret_key = std::env::vars().find(|x| x.0 == "SECRET_KEY");
         let secret_key = secret_key.unwrap().1;
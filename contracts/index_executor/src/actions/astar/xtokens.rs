@@ -1,12 +1,22 @@
-use crate::call::{Call, CallBuilder, CallParams, SubCall, SubExtrinsic};
+use crate::account::AccountType;
+use crate::actions::base::xcm::{
+    account_junction, XcmConfig, XcmDestWeights, XcmPallet, XcmTransfer,
+};
+use crate::call::{Call, CallBuilder};
 use crate::step::Step;
-use crate::utils::ToArray;
-use scale::{Decode, Encode};
-use xcm::{v3::prelude::*, VersionedMultiAsset, VersionedMultiLocation};
+use scale::Decode;
+use xcm::v3::prelude::*;
+
+/// Pallet index of xtokens on Astar and Shiden
+const XTOKENS_PALLET_ID: u8 = 0x37;
 
 #[derive(Clone)]
 pub struct AstarXtokens {
-    dest_chain_id: u32,
+    pallet_id: u8,
+    // Transfer to the relay chain if `None`
+    dest_chain_id: Option<u32>,
+    config: XcmConfig,
+    dest_weights: XcmDestWeights,
 }
 
 impl AstarXtokens {
@@ -14,7 +24,32 @@ impl AstarXtokens {
     where
         Self: Sized,
     {
-        Self { dest_chain_id }
+        Self::with_pallet_id(XTOKENS_PALLET_ID, Some(dest_chain_id))
+    }
+
+    /// Chains other than Astar with pallet xtokens at a different index, e.g. Karura
+    pub fn with_pallet_id(pallet_id: u8, dest_chain_id: Option<u32>) -> Self
+    where
+        Self: Sized,
+    {
+        Self {
+            pallet_id,
+            dest_chain_id,
+            config: XcmConfig::default(),
+            dest_weights: XcmDestWeights::default(),
+        }
+    }
+
+    /// Fee asset of the transfer
+    pub fn with_xcm_config(mut self, config: XcmConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Weight limits of the destination chains, the limit of the step's destination is applied
+    pub fn with_dest_weights(mut self, dest_weights: XcmDestWeights) -> Self {
+        self.dest_weights = dest_weights;
+        self
     }
 }
 
@@ -22,40 +57,31 @@ impl CallBuilder for AstarXtokens {
     fn build_call(&self, step: Step) -> Result<Call, &'static str> {
         let asset_location: MultiLocation =
             Decode::decode(&mut step.spend_asset.as_slice()).map_err(|_| "InvalidMultilocation")?;
-        let multi_asset = VersionedMultiAsset::V3(MultiAsset {
+        let multi_asset = MultiAsset {
             id: AssetId::Concrete(asset_location),
             fun: Fungibility::Fungible(step.spend_amount.ok_or("MissingSpendAmount")?),
-        });
-        let dest = VersionedMultiLocation::V3(MultiLocation::new(
+        };
+        let account_type = match step.recipient.len() {
+            20 => AccountType::Account20,
+            _ => AccountType::Account32,
+        };
+        let dest = MultiLocation::new(
             1,
-            Junctions::X2(
-                Parachain(self.dest_chain_id),
-                match step.recipient.len() {
-                    20 => AccountKey20 {
-                        network: None,
-                        key: step.recipient.to_array(),
-                    },
-                    32 => AccountId32 {
-                        network: None,
-                        id: step.recipient.to_array(),
-                    },
-                    _ => return Err("InvalidRecipient"),
-                },
-            ),
-        ));
-        let dest_weight: WeightLimit = WeightLimit::Unlimited;
+            match self.dest_chain_id {
+                Some(dest_chain_id) => X1(Parachain(dest_chain_id)),
+                None => Here,
+            },
+        );
 
-        Ok(Call {
-            params: CallParams::Sub(SubCall {
-                calldata: SubExtrinsic {
-                    pallet_id: 0x37u8,
-                    call_id: 0x01u8,
-                    call: (multi_asset, dest, dest_weight),
-                }
-                .encode(),
-            }),
-            input_call: None,
-            call_index: None,
+        XcmTransfer::new(
+            dest,
+            account_junction(&step.recipient, &account_type)?,
+            multi_asset,
+        )
+        .with_weight_limit(self.dest_weights.weight_limit(&step.dest_chain))
+        .with_config(&self.config)?
+        .build(XcmPallet::Xtokens {
+            pallet_id: self.pallet_id,
         })
     }
 }
@@ -63,13 +89,12 @@ impl CallBuilder for AstarXtokens {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::call::CallParams;
     use crate::constants::PHALA_PARACHAIN_ID;
 
     #[test]
     fn test_bridge_to_phala() {
-        let xtokens = AstarXtokens {
-            dest_chain_id: PHALA_PARACHAIN_ID,
-        };
+        let xtokens = AstarXtokens::new(PHALA_PARACHAIN_ID);
         let call = xtokens
             .build_call(Step {
                 exe: String::from(""),
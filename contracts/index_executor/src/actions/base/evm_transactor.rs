@@ -0,0 +1,229 @@
+use alloc::{vec, vec::Vec};
+use pink_web3::{
+    api::{Eth, Namespace},
+    contract::{tokens::Tokenize, Contract},
+    ethabi::Address,
+    transports::PinkHttp,
+    types::U256,
+};
+
+use crate::call::{Call, CallBuilder, CallParams, EvmCall};
+use crate::step::Step;
+
+/// Transfer an ERC20 token or the native asset from the handler to the recipient on EVM chains
+#[derive(Clone)]
+pub struct EvmTransactor {
+    pub eth: Eth<PinkHttp>,
+    // Native asset of the chain, transferred as value
+    pub native: Address,
+}
+
+impl EvmTransactor {
+    pub fn new(rpc: &str, native: Address) -> Self {
+        let eth = Eth::new(PinkHttp::new(rpc));
+        Self { eth, native }
+    }
+
+    /// Forward the output of `call`, which is received by `handler`, to the recipient of the
+    /// step if the recipient is another account.
+    ///
+    /// The transfer spends `output_asset` and its amount is updated with the output of `call`.
+    /// Like other transfers it doesn't need settlement: it receives nothing, the output is
+    /// recorded when `call` is settled
+    pub fn forward(
+        &self,
+        call: Call,
+        handler: Address,
+        output_asset: Address,
+        step: Step,
+    ) -> Result<Vec<Call>, &'static str> {
+        if step.recipient == handler.as_bytes() {
+            return Ok(vec![call]);
+        }
+        let forward = self.build_call(Step {
+            spend_asset: output_asset.as_bytes().to_vec(),
+            receive_asset: output_asset.as_bytes().to_vec(),
+            ..step
+        })?;
+        Ok(vec![call, forward])
+    }
+}
+
+impl CallBuilder for EvmTransactor {
+    fn build_call(&self, step: Step) -> Result<Call, &'static str> {
+        if step.recipient.len() != 20 {
+            return Err("InvalidRecipient");
+        }
+        let spend_asset = Address::from_slice(&step.spend_asset);
+        let recipient = Address::from_slice(&step.recipient);
+        let spend_amount = U256::from(step.spend_amount.ok_or("MissingSpendAmount")?);
+
+        let (target, calldata, value, update_offset, update_len) = if spend_asset == self.native {
+            // Amount is sent as value
+            (recipient, vec![], spend_amount, 0, 0)
+        } else {
+            let token = Contract::from_json(
+                self.eth.clone(),
+                spend_asset,
+                include_bytes!("../../abi/erc20.json"),
+            )
+            .map_err(|_| "BadAbi")?;
+            let transfer_func = token
+                .abi()
+                .function("transfer")
+                .map_err(|_| "NoFunctionFound")?;
+            let transfer_calldata = transfer_func
+                .encode_input(&(recipient, spend_amount).into_tokens())
+                .map_err(|_| "EncodeParamError")?;
+            (spend_asset, transfer_calldata, U256::from(0), 36, 32)
+        };
+
+        Ok(Call {
+            params: CallParams::Evm(EvmCall {
+                target,
+                calldata,
+                value,
+
+                // Transfer do not need do settlement, because it must be the last step
+                need_settle: false,
+                update_offset: U256::from(update_offset),
+                update_len: U256::from(update_len),
+                // No spender
+                spender: Address::from(&[0; 20]),
+                spend_asset,
+                spend_amount,
+                receive_asset: spend_asset,
+            }),
+            input_call: None,
+            call_index: None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_transfer() {
+        let transactor = EvmTransactor::new("https://eth.llamarpc.com", [0; 20].into());
+        let recipient = hex::decode("A29D4E0F035cb50C0d78c8CeBb56Ca292616Ab20").unwrap();
+        let step = Step {
+            exe: String::from("ethereum_transactor"),
+            source_chain: String::from("Ethereum"),
+            dest_chain: String::from("Ethereum"),
+            // PHA
+            spend_asset: hex::decode("6c5bA91642F10282b576d91922Ae6448C9d52f4E").unwrap(),
+            receive_asset: hex::decode("6c5bA91642F10282b576d91922Ae6448C9d52f4E").unwrap(),
+            sender: None,
+            recipient: recipient.clone(),
+            spend_amount: Some(1_000_000_000_000),
+            origin_balance: None,
+            nonce: None,
+        };
+
+        let call = transactor.build_call(step.clone()).unwrap();
+        match &call.params {
+            CallParams::Evm(evm_call) => {
+                assert_eq!(evm_call.target, Address::from_slice(&step.spend_asset));
+                assert_eq!(evm_call.value, U256::from(0));
+                // transfer(address,uint256)
+                assert_eq!(evm_call.calldata[..4], hex_literal::hex!("a9059cbb"));
+                assert_eq!(evm_call.calldata[16..36], recipient[..]);
+                let offset = evm_call.update_offset.as_usize();
+                assert_eq!(
+                    U256::from_big_endian(&evm_call.calldata[offset..offset + 32]),
+                    U256::from(1_000_000_000_000u128)
+                );
+            }
+            _ => assert!(false),
+        }
+
+        // Native asset
+        let call = transactor
+            .build_call(Step {
+                spend_asset: [0; 20].to_vec(),
+                receive_asset: [0; 20].to_vec(),
+                ..step
+            })
+            .unwrap();
+        match &call.params {
+            CallParams::Evm(evm_call) => {
+                assert_eq!(evm_call.target, Address::from_slice(&recipient));
+                assert!(evm_call.calldata.is_empty());
+                assert_eq!(evm_call.value, U256::from(1_000_000_000_000u128));
+            }
+            _ => assert!(false),
+        }
+    }
+
+    #[test]
+    fn test_forward() {
+        let transactor = EvmTransactor::new("https://eth.llamarpc.com", [0; 20].into());
+        let dai: Address = hex_literal::hex!("6B175474E89094C44Da98b954EedeAC495271d0F").into();
+        let usdc: Address = hex_literal::hex!("A0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48").into();
+        let handler: Address = [2; 20].into();
+        let recipient: Address = [1; 20].into();
+        let swap = Call {
+            params: CallParams::Sub(crate::call::SubCall { calldata: vec![] }),
+            input_call: None,
+            call_index: None,
+        };
+        let step = Step {
+            exe: String::from("ethereum_curve"),
+            source_chain: String::from("Ethereum"),
+            dest_chain: String::from("Ethereum"),
+            spend_asset: dai.as_bytes().to_vec(),
+            receive_asset: usdc.as_bytes().to_vec(),
+            sender: None,
+            recipient: handler.as_bytes().to_vec(),
+            spend_amount: Some(100),
+            origin_balance: None,
+            nonce: None,
+        };
+        // Handler keeps the output
+        assert_eq!(
+            transactor.forward(swap.clone(), handler, usdc, step.clone()),
+            Ok(vec![swap.clone()])
+        );
+
+        let step = Step {
+            recipient: recipient.as_bytes().to_vec(),
+            ..step
+        };
+        let calls = transactor
+            .forward(swap.clone(), handler, usdc, step.clone())
+            .unwrap();
+        assert_eq!(calls.len(), 2);
+        match &calls[1].params {
+            CallParams::Evm(evm_call) => {
+                assert_eq!(evm_call.target, usdc);
+                // transfer(address,uint256)
+                assert_eq!(evm_call.calldata[..4], hex_literal::hex!("a9059cbb"));
+                assert_eq!(evm_call.calldata[16..36], recipient.0);
+                assert_eq!(
+                    U256::from_big_endian(&evm_call.calldata[36..68]),
+                    U256::from(100)
+                );
+                assert_eq!(evm_call.update_offset, U256::from(36));
+                assert_eq!(evm_call.spend_asset, usdc);
+                assert!(!evm_call.need_settle);
+            }
+            _ => assert!(false),
+        }
+
+        // Native asset is sent as value
+        let calls = transactor
+            .forward(swap, handler, [0; 20].into(), step)
+            .unwrap();
+        match &calls[1].params {
+            CallParams::Evm(evm_call) => {
+                assert_eq!(evm_call.target, recipient);
+                assert!(evm_call.calldata.is_empty());
+                assert_eq!(evm_call.value, U256::from(100));
+                assert!(!evm_call.need_settle);
+            }
+            _ => assert!(false),
+        }
+    }
+}
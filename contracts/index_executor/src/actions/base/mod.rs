@@ -1,5 +1,8 @@
 pub mod account;
+pub mod algebrav3;
+pub mod evm_transactor;
 pub mod native_wrapper;
 pub mod sub_transactor;
 pub mod uniswapv2;
 pub mod uniswapv3;
+pub mod xcm;
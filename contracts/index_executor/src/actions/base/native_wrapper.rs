@@ -1,3 +1,4 @@
+use alloc::vec::Vec;
 use pink_web3::{
     api::{Eth, Namespace},
     contract::{tokens::Tokenize, Contract},
@@ -6,23 +7,52 @@ use pink_web3::{
     types::U256,
 };
 
+use crate::actions::base::evm_transactor::EvmTransactor;
 use crate::call::{Call, CallBuilder, CallParams, EvmCall};
 use crate::step::Step;
 
+/// Direction of the step, decided by its spend and receive assets
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum WrapDirection {
+    // Native asset to WETH with `deposit`
+    Wrap,
+    // WETH to native asset with `withdraw`
+    Unwrap,
+}
+
 #[derive(Clone)]
 pub struct NativeWrapper {
     pub eth: Eth<PinkHttp>,
     pub weth9: Contract<PinkHttp>,
     pub native: Address,
+    // Handler contract of the chain, which holds the wrapped and unwrapped assets
+    pub handler: Address,
 }
 
 impl NativeWrapper {
-    pub fn new(rpc: &str, weth9: Address, native: Address) -> Self {
+    pub fn new(rpc: &str, weth9: Address, native: Address, handler: Address) -> Self {
         let eth = Eth::new(PinkHttp::new(rpc));
         let weth9 = Contract::from_json(eth.clone(), weth9, include_bytes!("../../abi/WETH9.json"))
             .expect("Bad abi data");
 
-        Self { eth, weth9, native }
+        Self {
+            eth,
+            weth9,
+            native,
+            handler,
+        }
+    }
+
+    fn direction(&self, step: &Step) -> Result<WrapDirection, &'static str> {
+        let spend_asset = Address::from_slice(&step.spend_asset);
+        let receive_asset = Address::from_slice(&step.receive_asset);
+        if spend_asset == self.native && receive_asset == self.weth9.address() {
+            Ok(WrapDirection::Wrap)
+        } else if spend_asset == self.weth9.address() && receive_asset == self.native {
+            Ok(WrapDirection::Unwrap)
+        } else {
+            Err("UnrecognizedArguments")
+        }
     }
 }
 
@@ -32,66 +62,82 @@ impl CallBuilder for NativeWrapper {
         let receive_asset = Address::from_slice(&step.receive_asset);
         let spend_amount = U256::from(step.spend_amount.ok_or("MissingSpendAmount")?);
 
-        if spend_asset == self.native && receive_asset == self.weth9.address() {
-            // Deposit
-            let deposit_func = self
-                .weth9
-                .abi()
-                .function("deposit")
-                .map_err(|_| "NoFunctionFound")?;
-            let deposit_calldata = deposit_func
-                .encode_input(&[])
-                .map_err(|_| "EncodeParamError")?;
-            Ok(Call {
-                params: CallParams::Evm(EvmCall {
-                    target: self.weth9.address(),
-                    calldata: deposit_calldata,
-                    value: spend_amount,
-
-                    need_settle: true,
-                    update_offset: U256::from(0),
-                    update_len: U256::from(0),
-                    // No spender
-                    spender: Address::from(&[0; 20]),
-                    spend_asset,
-                    spend_amount,
-                    receive_asset,
-                }),
-                input_call: None,
-                call_index: None,
-            })
-        } else if spend_asset == self.weth9.address() && receive_asset == self.native {
-            // Withdraw
-            let withdraw_func = self
-                .weth9
-                .abi()
-                .function("withdraw")
-                .map_err(|_| "NoFunctionFound")?;
-            let withdraw_calldata = withdraw_func
-                .encode_input(&spend_amount.into_tokens())
-                .map_err(|_| "EncodeParamError")?;
-            Ok(Call {
-                params: CallParams::Evm(EvmCall {
-                    target: self.weth9.address(),
-                    calldata: withdraw_calldata,
-                    value: U256::from(0),
-
-                    need_settle: true,
-                    update_offset: U256::from(4),
-                    update_len: U256::from(32),
-                    // No spender
-                    spender: Address::from(&[0; 20]),
-                    spend_asset,
-                    spend_amount,
-                    receive_asset,
-                }),
-                input_call: None,
-                call_index: None,
-            })
-        } else {
-            Err("UnrecognizedArguments")
+        match self.direction(&step)? {
+            WrapDirection::Wrap => {
+                let deposit_func = self
+                    .weth9
+                    .abi()
+                    .function("deposit")
+                    .map_err(|_| "NoFunctionFound")?;
+                let deposit_calldata = deposit_func
+                    .encode_input(&[])
+                    .map_err(|_| "EncodeParamError")?;
+                Ok(Call {
+                    params: CallParams::Evm(EvmCall {
+                        target: self.weth9.address(),
+                        calldata: deposit_calldata,
+                        value: spend_amount,
+
+                        need_settle: true,
+                        update_offset: U256::from(0),
+                        update_len: U256::from(0),
+                        // No spender
+                        spender: Address::from(&[0; 20]),
+                        spend_asset,
+                        spend_amount,
+                        receive_asset,
+                    }),
+                    input_call: None,
+                    call_index: None,
+                })
+            }
+            WrapDirection::Unwrap => {
+                let withdraw_func = self
+                    .weth9
+                    .abi()
+                    .function("withdraw")
+                    .map_err(|_| "NoFunctionFound")?;
+                let withdraw_calldata = withdraw_func
+                    .encode_input(&spend_amount.into_tokens())
+                    .map_err(|_| "EncodeParamError")?;
+                Ok(Call {
+                    params: CallParams::Evm(EvmCall {
+                        target: self.weth9.address(),
+                        calldata: withdraw_calldata,
+                        value: U256::from(0),
+
+                        need_settle: true,
+                        update_offset: U256::from(4),
+                        update_len: U256::from(32),
+                        // No spender
+                        spender: Address::from(&[0; 20]),
+                        spend_asset,
+                        spend_amount,
+                        receive_asset,
+                    }),
+                    input_call: None,
+                    call_index: None,
+                })
+            }
         }
     }
+
+    fn build_calls(&self, step: Step) -> Result<Vec<Call>, &'static str> {
+        let output_asset = match self.direction(&step)? {
+            WrapDirection::Wrap => self.weth9.address(),
+            WrapDirection::Unwrap => self.native,
+        };
+        let transactor = EvmTransactor {
+            eth: self.eth.clone(),
+            native: self.native,
+        };
+        transactor.forward(
+            self.build_call(step.clone())?,
+            self.handler,
+            output_asset,
+            step,
+        )
+    }
 }
 
 #[cfg(test)]
@@ -141,6 +187,7 @@ mod tests {
             "https://rpc.api.moonbeam.network",
             wglmr.into(),
             glmr.into(),
+            handler_address,
         );
         let mut deposit_call = native_wrapper
             .build_call(Step {
@@ -221,4 +268,49 @@ mod tests {
         .unwrap();
         println!("native warpper test tx: {:?}", tx_id);
     }
+
+    #[test]
+    fn test_unwrap_to_recipient() {
+        let weth: [u8; 20] = hex_literal::hex!("C02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2");
+        let handler: [u8; 20] = hex_literal::hex!("d693bDC5cb0cF2a31F08744A0Ec135a68C26FE1c");
+        let recipient: [u8; 20] = hex_literal::hex!("A29D4E0F035cb50C0d78c8CeBb56Ca292616Ab20");
+        let native_wrapper = NativeWrapper::new(
+            "https://eth.llamarpc.com",
+            weth.into(),
+            [0; 20].into(),
+            handler.into(),
+        );
+        let step = Step {
+            exe: String::from("ethereum_nativewrapper"),
+            source_chain: String::from("Ethereum"),
+            dest_chain: String::from("Ethereum"),
+            spend_asset: weth.to_vec(),
+            receive_asset: [0; 20].to_vec(),
+            sender: None,
+            recipient: handler.to_vec(),
+            spend_amount: Some(1_000_000),
+            origin_balance: None,
+            nonce: None,
+        };
+        // Handler keeps the output
+        assert_eq!(native_wrapper.build_calls(step.clone()).unwrap().len(), 1);
+
+        let calls = native_wrapper
+            .build_calls(Step {
+                recipient: recipient.to_vec(),
+                ..step
+            })
+            .unwrap();
+        assert_eq!(calls.len(), 2);
+        match &calls[1].params {
+            CallParams::Evm(evm_call) => {
+                assert_eq!(evm_call.target, recipient.into());
+                assert!(evm_call.calldata.is_empty());
+                assert_eq!(evm_call.value, U256::from(1_000_000));
+                assert_eq!(evm_call.spend_asset, Address::from([0; 20]));
+                assert!(!evm_call.need_settle);
+            }
+            _ => assert!(false),
+        }
+    }
 }
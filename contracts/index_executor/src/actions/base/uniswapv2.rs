@@ -1,23 +1,34 @@
-use alloc::vec;
+use alloc::{vec, vec::Vec};
 use pink_web3::{
     api::{Eth, Namespace},
-    contract::{tokens::Tokenize, Contract},
+    contract::{tokens::Tokenize, Contract, Options},
     ethabi::Address,
-    transports::PinkHttp,
+    transports::{resolve_ready, PinkHttp},
     types::U256,
 };
 
 use crate::call::{Call, CallBuilder, CallParams, EvmCall};
+use crate::rpc::{self, RpcEndpoint, RpcHealth};
 use crate::step::Step;
 
 #[derive(Clone)]
 pub struct UniswapV2 {
     pub eth: Eth<PinkHttp>,
     pub router: Contract<PinkHttp>,
+    // Native asset of the chain, swapped with the ETH variants of the router
+    pub native: Address,
+    // Tokens that can be used as the middle of the path if they give more output
+    pub intermediates: Vec<Address>,
+    // Tokens that charge fee on transfer, swaps with one of them on the path use the
+    // `SupportingFeeOnTransferTokens` variants of the router
+    pub fee_on_transfer_tokens: Vec<Address>,
+    // Endpoints the router is queried from, failover happens between them
+    pub endpoints: Vec<RpcEndpoint>,
+    pub health: RpcHealth,
 }
 
 impl UniswapV2 {
-    pub fn new(rpc: &str, router: Address) -> Self {
+    pub fn new(rpc: &str, router: Address, native: Address) -> Self {
         let eth = Eth::new(PinkHttp::new(rpc));
         let router = Contract::from_json(
             eth.clone(),
@@ -26,8 +37,104 @@ impl UniswapV2 {
         )
         .expect("Bad abi data");
 
-        Self { eth, router }
+        Self {
+            eth,
+            router,
+            native,
+            intermediates: vec![],
+            fee_on_transfer_tokens: vec![],
+            endpoints: vec![RpcEndpoint::new(rpc, 0)],
+            health: RpcHealth::default(),
+        }
+    }
+
+    /// Fail over between `endpoints` when querying the router, requests are recorded to `health`
+    pub fn with_endpoints(mut self, endpoints: Vec<RpcEndpoint>, health: &RpcHealth) -> Self {
+        self.endpoints = endpoints;
+        self.health = health.clone();
+        self
+    }
+
+    fn on_endpoint(&self, endpoint: &str) -> Self {
+        Self {
+            eth: Eth::new(PinkHttp::new(endpoint)),
+            router: rpc::contract_on(&self.router, endpoint),
+            ..self.clone()
+        }
+    }
+
+    /// Route swaps through one of `intermediates` if there is no pool of the pair or the pool
+    /// gives less output
+    pub fn with_intermediates(mut self, intermediates: Vec<Address>) -> Self {
+        self.intermediates = intermediates;
+        self
     }
+
+    /// Swap with the `SupportingFeeOnTransferTokens` variants when any of `tokens` is on the path
+    pub fn with_fee_on_transfer_tokens(mut self, tokens: Vec<Address>) -> Self {
+        self.fee_on_transfer_tokens = tokens;
+        self
+    }
+
+    fn weth(&self) -> Result<Address, &'static str> {
+        resolve_ready(
+            self.router
+                .query("WETH", (), None, Options::default(), None),
+        )
+        .map_err(|_| "FailedToGetWETH")
+    }
+
+    /// Output of swapping along the path, `None` if any pair of the path has no pool
+    fn get_amount_out(&self, amount_in: U256, path: &[Address]) -> Option<U256> {
+        let amounts: Result<Vec<U256>, _> = resolve_ready(self.router.query(
+            "getAmountsOut",
+            (amount_in, path.to_vec()),
+            None,
+            Options::default(),
+            None,
+        ));
+        amounts.ok().and_then(|amounts| amounts.last().cloned())
+    }
+
+    /// Path with the best output, swap directly if no intermediates are configured
+    fn find_path(
+        &self,
+        token0: Address,
+        token1: Address,
+        amount_in: U256,
+    ) -> Result<Vec<Address>, &'static str> {
+        let candidates = candidate_paths(token0, token1, &self.intermediates);
+        if candidates.len() == 1 {
+            return Ok(candidates[0].clone());
+        }
+        candidates
+            .into_iter()
+            .filter_map(|path| self.get_amount_out(amount_in, &path).map(|out| (path, out)))
+            .max_by_key(|(_, out)| *out)
+            .map(|(path, _)| path)
+            .ok_or("NoPoolFound")
+    }
+}
+
+/// The direct path of the pair and the paths through each of the intermediates
+fn candidate_paths(
+    token0: Address,
+    token1: Address,
+    intermediates: &[Address],
+) -> Vec<Vec<Address>> {
+    let mut paths = vec![vec![token0, token1]];
+    for intermediate in intermediates {
+        if *intermediate != token0 && *intermediate != token1 {
+            paths.push(vec![token0, *intermediate, token1]);
+        }
+    }
+    paths
+}
+
+/// Whether any token of the path charges fee on transfer
+fn charges_fee_on_transfer(path: &[Address], fee_on_transfer_tokens: &[Address]) -> bool {
+    path.iter()
+        .any(|token| fee_on_transfer_tokens.contains(token))
 }
 
 impl CallBuilder for UniswapV2 {
@@ -35,32 +142,77 @@ impl CallBuilder for UniswapV2 {
         let asset0 = Address::from_slice(&step.spend_asset);
         let asset1 = Address::from_slice(&step.receive_asset);
         let to = Address::from_slice(&step.recipient);
-        let path = vec![asset0, asset1];
         let amount_out = U256::from(1);
         let amount_in = U256::from(step.spend_amount.ok_or("MissingSpendAmount")?);
         let time = pink_extension::ext().untrusted_millis_since_unix_epoch() / 1000;
         // 1 month
         let deadline = U256::from(time + 60 * 60 * 24 * 30);
-        let swap_params = (amount_in, amount_out, path, to, deadline);
+
+        // Native asset is wrapped and unwrapped by the router, the path goes through WETH
+        let native_in = asset0 == self.native;
+        let native_out = asset1 == self.native;
+        if native_in && native_out {
+            return Err("UnrecognizedArguments");
+        }
+        let path = rpc::with_failover(&self.endpoints, &self.health, |endpoint| {
+            let uniswap = self.on_endpoint(endpoint);
+            let (token0, token1) = match (native_in, native_out) {
+                (true, _) => (uniswap.weth()?, asset1),
+                (_, true) => (asset0, uniswap.weth()?),
+                _ => (asset0, asset1),
+            };
+            uniswap.find_path(token0, token1, amount_in)
+        })?;
+        pink_extension::debug!("UniswapV2: swap path {:?}", &path);
+
+        let suffix = if charges_fee_on_transfer(&path, &self.fee_on_transfer_tokens) {
+            "SupportingFeeOnTransferTokens"
+        } else {
+            ""
+        };
+        let (func, swap_tokens, value, update_offset, update_len, spender) = if native_in {
+            (
+                ["swapExactETHForTokens", suffix].concat(),
+                (amount_out, path, to, deadline).into_tokens(),
+                amount_in,
+                // Amount is sent as value
+                0,
+                0,
+                Address::from(&[0; 20]),
+            )
+        } else {
+            (
+                if native_out {
+                    ["swapExactTokensForETH", suffix].concat()
+                } else {
+                    ["swapExactTokensForTokens", suffix].concat()
+                },
+                (amount_in, amount_out, path, to, deadline).into_tokens(),
+                U256::from(0),
+                4,
+                32,
+                self.router.address(),
+            )
+        };
         let swap_func = self
             .router
             .abi()
-            .function("swapExactTokensForTokens")
+            .function(&func)
             .map_err(|_| "NoFunctionFound")?;
         let swap_calldata = swap_func
-            .encode_input(&swap_params.into_tokens())
+            .encode_input(&swap_tokens)
             .map_err(|_| "EncodeParamError")?;
 
         Ok(Call {
             params: CallParams::Evm(EvmCall {
                 target: self.router.address(),
                 calldata: swap_calldata,
-                value: U256::from(0),
+                value,
 
                 need_settle: true,
-                update_offset: U256::from(4),
-                update_len: U256::from(32),
-                spender: self.router.address(),
+                update_offset: U256::from(update_offset),
+                update_len: U256::from(update_len),
+                spender,
                 spend_asset: asset0,
                 spend_amount: amount_in,
                 receive_asset: asset1,
@@ -70,3 +222,44 @@ impl CallBuilder for UniswapV2 {
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_candidate_paths() {
+        let [a, b, weth, usdc]: [Address; 4] = [
+            [1; 20].into(),
+            [2; 20].into(),
+            [3; 20].into(),
+            [4; 20].into(),
+        ];
+        assert_eq!(candidate_paths(a, b, &[]), vec![vec![a, b]]);
+        assert_eq!(
+            candidate_paths(a, b, &[weth, usdc]),
+            vec![vec![a, b], vec![a, weth, b], vec![a, usdc, b]]
+        );
+        // Intermediates that are one side of the pair are skipped
+        assert_eq!(
+            candidate_paths(weth, b, &[weth, usdc]),
+            vec![vec![weth, b], vec![weth, usdc, b]]
+        );
+    }
+
+    #[test]
+    fn test_charges_fee_on_transfer() {
+        let [a, b, weth, fot]: [Address; 4] = [
+            [1; 20].into(),
+            [2; 20].into(),
+            [3; 20].into(),
+            [4; 20].into(),
+        ];
+        assert!(!charges_fee_on_transfer(&[a, b], &[]));
+        assert!(!charges_fee_on_transfer(&[a, weth, b], &[fot]));
+        // Decided per swap, by the tokens on the chosen path
+        assert!(charges_fee_on_transfer(&[fot, b], &[fot]));
+        assert!(charges_fee_on_transfer(&[a, fot, b], &[fot]));
+        assert!(charges_fee_on_transfer(&[a, weth, fot], &[fot]));
+    }
+}
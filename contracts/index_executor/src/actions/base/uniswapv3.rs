@@ -1,53 +1,227 @@
+use alloc::vec::Vec;
 use pink_web3::{
     api::{Eth, Namespace},
-    contract::{tokens::Tokenize, Contract},
+    contract::{tokens::Tokenize, Contract, Options},
     ethabi::{Address, Token},
-    transports::PinkHttp,
+    transports::{resolve_ready, PinkHttp},
     types::U256,
 };
 
 use crate::call::{Call, CallBuilder, CallParams, EvmCall};
+use crate::rpc::{self, RpcEndpoint, RpcHealth};
 use crate::step::Step;
 
+/// Fee tiers of Uniswap V3 pools, in hundredths of a bip
+const FEE_TIERS: [u32; 4] = [100, 500, 3000, 10000];
+
+/// Pools the swap goes through
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Route {
+    /// Pool of the pair with the fee tier, swapped with `exactInputSingle`
+    Single(u32),
+    /// Packed path of tokens and fee tiers, swapped with `exactInput`
+    Multi(Vec<u8>),
+}
+
+/// Swap through the canonical Uniswap V3 SwapRouter.
+///
+/// Pools of all fee tiers are quoted with the quoter, the swap goes through the pool with the
+/// best output, or through two pools with `weth` in the middle if that gives more
 #[derive(Clone)]
 pub struct UniswapV3 {
     pub eth: Eth<PinkHttp>,
     pub router: Contract<PinkHttp>,
+    pub factory: Contract<PinkHttp>,
+    pub quoter: Contract<PinkHttp>,
+    pub weth: Address,
+    // Endpoints the factory and the quoter are queried from, failover happens between them
+    pub endpoints: Vec<RpcEndpoint>,
+    pub health: RpcHealth,
 }
 
 impl UniswapV3 {
-    pub fn new(rpc: &str, router: Address) -> Self {
+    pub fn new(
+        rpc: &str,
+        router: Address,
+        factory: Address,
+        quoter: Address,
+        weth: Address,
+    ) -> Self {
         let eth = Eth::new(PinkHttp::new(rpc));
         let router = Contract::from_json(
             eth.clone(),
             router,
-            include_bytes!("../../abi/uniswapV3Router.json"),
+            include_bytes!("../../abi/UniswapV3SwapRouter.json"),
+        )
+        .expect("Bad abi data");
+        let factory = Contract::from_json(
+            eth.clone(),
+            factory,
+            include_bytes!("../../abi/UniswapV3Factory.json"),
+        )
+        .expect("Bad abi data");
+        let quoter = Contract::from_json(
+            eth.clone(),
+            quoter,
+            include_bytes!("../../abi/UniswapV3Quoter.json"),
         )
         .expect("Bad abi data");
 
-        Self { eth, router }
+        Self {
+            eth,
+            router,
+            factory,
+            quoter,
+            weth,
+            endpoints: vec![RpcEndpoint::new(rpc, 0)],
+            health: RpcHealth::default(),
+        }
     }
-}
 
-impl CallBuilder for UniswapV3 {
-    fn build_call(&self, step: Step) -> Result<Call, &'static str> {
+    /// Fail over between `endpoints` when quoting, requests are recorded to `health`
+    pub fn with_endpoints(mut self, endpoints: Vec<RpcEndpoint>, health: &RpcHealth) -> Self {
+        self.endpoints = endpoints;
+        self.health = health.clone();
+        self
+    }
+
+    fn on_endpoint(&self, endpoint: &str) -> Self {
+        Self {
+            eth: Eth::new(PinkHttp::new(endpoint)),
+            factory: rpc::contract_on(&self.factory, endpoint),
+            quoter: rpc::contract_on(&self.quoter, endpoint),
+            ..self.clone()
+        }
+    }
+
+    /// Fee tiers that have a pool of the pair
+    fn pool_fees(&self, token0: Address, token1: Address) -> Vec<u32> {
+        FEE_TIERS
+            .into_iter()
+            .filter(|fee| {
+                let pool: Result<Address, _> = resolve_ready(self.factory.query(
+                    "getPool",
+                    (token0, token1, U256::from(*fee)),
+                    None,
+                    Options::default(),
+                    None,
+                ));
+                matches!(pool, Ok(pool) if !pool.is_zero())
+            })
+            .collect()
+    }
+
+    /// Fee tier of the pool with the best output and the output amount
+    fn quote_single(
+        &self,
+        token_in: Address,
+        token_out: Address,
+        amount_in: U256,
+    ) -> Option<(u32, U256)> {
+        self.pool_fees(token_in, token_out)
+            .into_iter()
+            .filter_map(|fee| {
+                let amount_out: Result<U256, _> = resolve_ready(self.quoter.query(
+                    "quoteExactInputSingle",
+                    (
+                        token_in,
+                        token_out,
+                        U256::from(fee),
+                        amount_in,
+                        U256::zero(),
+                    ),
+                    None,
+                    Options::default(),
+                    None,
+                ));
+                amount_out.ok().map(|amount_out| (fee, amount_out))
+            })
+            .max_by_key(|(_, amount_out)| *amount_out)
+    }
+
+    /// Route with the best output, the pool of the pair or two pools through `weth`
+    fn find_route(
+        &self,
+        token_in: Address,
+        token_out: Address,
+        amount_in: U256,
+    ) -> Result<Route, &'static str> {
+        let single = self.quote_single(token_in, token_out, amount_in);
+        let multi = if token_in != self.weth && token_out != self.weth {
+            self.quote_single(token_in, self.weth, amount_in)
+                .and_then(|(fee0, amount)| {
+                    self.quote_single(self.weth, token_out, amount)
+                        .map(|(fee1, amount_out)| (fee0, fee1, amount_out))
+                })
+        } else {
+            None
+        };
+        pink_extension::debug!(
+            "UniswapV3: quoted single pool {:?}, through weth {:?}",
+            &single,
+            &multi
+        );
+        match (single, multi) {
+            (Some((_, single_out)), Some((fee0, fee1, multi_out))) if multi_out > single_out => {
+                Ok(Route::Multi(encode_path(
+                    &[token_in, self.weth, token_out],
+                    &[fee0, fee1],
+                )))
+            }
+            (Some((fee, _)), _) => Ok(Route::Single(fee)),
+            (None, Some((fee0, fee1, _))) => Ok(Route::Multi(encode_path(
+                &[token_in, self.weth, token_out],
+                &[fee0, fee1],
+            ))),
+            (None, None) => Err("NoPoolFound"),
+        }
+    }
+
+    fn build_swap(&self, step: &Step, route: Route) -> Result<Call, &'static str> {
         let asset0 = Address::from_slice(&step.spend_asset);
         let asset1 = Address::from_slice(&step.receive_asset);
         let to = Address::from_slice(&step.recipient);
+        // Amount in is updated with the actual balance during settlement, so the quoted output
+        // can not be used as the minimum
         let amount_out = U256::from(1);
         let amount_in = U256::from(step.spend_amount.ok_or("MissingSpendAmount")?);
         let time = pink_extension::ext().untrusted_millis_since_unix_epoch() / 1000;
         // 1 month
         let deadline = U256::from(time + 60 * 60 * 24 * 30);
-        let swap_params = (asset0, asset1, to, deadline, amount_in, amount_out, 0_u128);
-        // https://github.com/Uniswap/v3-periphery/blob/6cce88e63e176af1ddb6cc56e029110289622317/contracts/SwapRouter.sol#L115
+
+        // https://github.com/Uniswap/v3-periphery/blob/main/contracts/SwapRouter.sol
+        let (func, params, update_offset) = match route {
+            Route::Single(fee) => (
+                "exactInputSingle",
+                (
+                    asset0,
+                    asset1,
+                    U256::from(fee),
+                    to,
+                    deadline,
+                    amount_in,
+                    amount_out,
+                    U256::zero(),
+                )
+                    .into_tokens(),
+                // amountIn is the 6th field of the static params tuple
+                164,
+            ),
+            Route::Multi(path) => (
+                "exactInput",
+                (path, to, deadline, amount_in, amount_out).into_tokens(),
+                // params tuple is dynamic, its head starts after the offset of the tuple,
+                // amountIn is the 4th field of the head
+                132,
+            ),
+        };
         let swap_func = self
             .router
             .abi()
-            .function("exactInputSingle")
+            .function(func)
             .map_err(|_| "NoFunctionFound")?;
         let swap_calldata = swap_func
-            .encode_input(&[Token::Tuple(swap_params.into_tokens())])
+            .encode_input(&[Token::Tuple(params)])
             .map_err(|_| "EncodeParamError")?;
 
         Ok(Call {
@@ -57,7 +231,7 @@ impl CallBuilder for UniswapV3 {
                 value: U256::from(0),
 
                 need_settle: true,
-                update_offset: U256::from(132),
+                update_offset: U256::from(update_offset),
                 update_len: U256::from(32),
                 spender: self.router.address(),
                 spend_asset: asset0,
@@ -70,98 +244,115 @@ impl CallBuilder for UniswapV3 {
     }
 }
 
+/// Packed path of `exactInput`, tokens interleaved with 3 bytes fee tiers of the pools
+fn encode_path(tokens: &[Address], fees: &[u32]) -> Vec<u8> {
+    let mut path = tokens[0].as_bytes().to_vec();
+    for (token, fee) in tokens[1..].iter().zip(fees) {
+        path.extend_from_slice(&fee.to_be_bytes()[1..]);
+        path.extend_from_slice(token.as_bytes());
+    }
+    path
+}
+
+impl CallBuilder for UniswapV3 {
+    fn build_call(&self, step: Step) -> Result<Call, &'static str> {
+        let amount_in = U256::from(step.spend_amount.ok_or("MissingSpendAmount")?);
+        let route = rpc::with_failover(&self.endpoints, &self.health, |endpoint| {
+            self.on_endpoint(endpoint).find_route(
+                Address::from_slice(&step.spend_asset),
+                Address::from_slice(&step.receive_asset),
+                amount_in,
+            )
+        })?;
+        self.build_swap(&step, route)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::utils::ToArray;
-    use dotenv::dotenv;
-    use primitive_types::H160;
 
-    use pink_web3::{
-        api::{Eth, Namespace},
-        contract::{Contract, Options},
-        keys::pink::KeyPair,
-        transports::{resolve_ready, PinkHttp},
-    };
+    fn uniswap() -> UniswapV3 {
+        UniswapV3::new(
+            "https://eth.llamarpc.com",
+            hex_literal::hex!("E592427A0AEce92De3Edee1F18E0157C05861564").into(),
+            hex_literal::hex!("1F98431c8aD98523631AE4a59f267346ea31F984").into(),
+            hex_literal::hex!("b27308f9F90D607463bb33eA1BeBb41C27CE5AB6").into(),
+            hex_literal::hex!("C02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2").into(),
+        )
+    }
+
+    fn swap_step() -> Step {
+        Step {
+            exe: String::from(""),
+            source_chain: String::from("Ethereum"),
+            dest_chain: String::from("Ethereum"),
+            // PHA
+            spend_asset: hex::decode("6c5bA91642F10282b576d91922Ae6448C9d52f4E").unwrap(),
+            // USDC
+            receive_asset: hex::decode("A0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48").unwrap(),
+            sender: None,
+            recipient: hex::decode("A29D4E0F035cb50C0d78c8CeBb56Ca292616Ab20").unwrap(),
+            // 100 PHA
+            spend_amount: Some(100_000_000_000_000_000_000 as u128),
+            origin_balance: None,
+            nonce: None,
+        }
+    }
 
     #[test]
-    #[ignore]
-    fn should_work() {
-        dotenv().ok();
+    fn test_encode_path() {
+        let tokens: [Address; 3] = [[1; 20].into(), [2; 20].into(), [3; 20].into()];
+        let path = encode_path(&tokens, &[3000, 500]);
+        assert_eq!(path.len(), 20 * 3 + 3 * 2);
+        assert_eq!(path[20..23], [0x00, 0x0b, 0xb8]);
+        assert_eq!(path[43..46], [0x00, 0x01, 0xf4]);
+        assert_eq!(path[46..], [3; 20]);
+    }
+
+    #[test]
+    fn test_update_offset() {
         pink_extension_runtime::mock_ext::mock_all_ext();
 
-        use pink_web3::types::Address;
-
-        let secret_key = std::env::vars().find(|x| x.0 == "SECRET_KEY");
-        let secret_key = secret_key.unwrap().1;
-        let secret_bytes = hex::decode(secret_key).unwrap();
-        let signer: [u8; 32] = secret_bytes.to_array();
-
-        // Handler on Moonbeam
-        let handler_address: H160 =
-            H160::from_slice(&hex::decode("635eA86804200F80C16ea8EdDc3c749a54a9C37D").unwrap());
-        let transport = Eth::new(PinkHttp::new("https://rpc.api.moonbeam.network"));
-        let handler = Contract::from_json(
-            transport,
-            handler_address,
-            include_bytes!("../../abi/handler.json"),
-        )
-        .unwrap();
-        let stellaswap_routerv3: [u8; 20] = hex::decode("e6d0ED3759709b743707DcfeCAe39BC180C981fe")
-            .unwrap()
-            .to_array();
-        let stellaswap_v3 = UniswapV3::new(
-            "https://rpc.api.moonbeam.network",
-            stellaswap_routerv3.into(),
+        let uniswap = uniswap();
+        let step = swap_step();
+        let mut amount_in = [0u8; 32];
+        U256::from(step.spend_amount.unwrap()).to_big_endian(&mut amount_in);
+        let path = encode_path(
+            &[
+                Address::from_slice(&step.spend_asset),
+                uniswap.weth,
+                Address::from_slice(&step.receive_asset),
+            ],
+            &[10000, 500],
         );
-        let mut call = stellaswap_v3
-            .build_call(Step {
-                exe: String::from(""),
-                source_chain: String::from("Moonbeam"),
-                dest_chain: String::from("Moonbeam"),
-                // xcDOT
-                spend_asset: hex::decode("FfFFfFff1FcaCBd218EDc0EbA20Fc2308C778080").unwrap(),
-                // xcPHA
-                receive_asset: hex::decode("FFFfFfFf63d24eCc8eB8a7b5D0803e900F7b6cED").unwrap(),
-                sender: None,
-                recipient: hex::decode("A29D4E0F035cb50C0d78c8CeBb56Ca292616Ab20").unwrap(),
-                // 0.002 xcDOT
-                spend_amount: Some(2_0_000_000 as u128),
-                origin_balance: None,
-                nonce: None,
-            })
-            .unwrap();
+        for route in [Route::Single(3000), Route::Multi(path)] {
+            let call = uniswap.build_swap(&step, route).unwrap();
+            match &call.params {
+                CallParams::Evm(evm_call) => {
+                    let offset = evm_call.update_offset.as_usize();
+                    assert_eq!(evm_call.calldata[offset..offset + 32], amount_in);
+                }
+                _ => assert!(false),
+            }
+        }
+    }
 
-        // Apply index mannually
-        call.input_call = Some(0);
-        call.call_index = Some(0);
-
-        // Estiamte gas before submission
-        let gas = resolve_ready(handler.estimate_gas(
-            "batchCall",
-            vec![call.clone()],
-            Address::from_slice(&hex::decode("bf526928373748b00763875448ee905367d97f96").unwrap()),
-            Options::default(),
-        ))
-        .map_err(|e| {
-            println!("Failed to estimated step gas cost with error: {:?}", e);
-            "FailedToEstimateGas"
-        })
-        .unwrap();
-
-        // Uncomment if wanna send it to blockchain
-        let _tx_id = resolve_ready(handler.signed_call(
-            "batchCall",
-            vec![call],
-            Options::with(|opt| {
-                opt.gas = Some(gas);
-            }),
-            KeyPair::from(signer),
-        ))
-        .map_err(|e| {
-            println!("Failed to submit step execution tx with error: {:?}", e);
-            "FailedToSubmitTransaction"
-        })
-        .unwrap();
+    #[test]
+    #[ignore]
+    fn test_find_route() {
+        pink_extension_runtime::mock_ext::mock_all_ext();
+
+        let uniswap = uniswap();
+        let step = swap_step();
+        let route = uniswap
+            .find_route(
+                Address::from_slice(&step.spend_asset),
+                Address::from_slice(&step.receive_asset),
+                U256::from(step.spend_amount.unwrap()),
+            )
+            .unwrap();
+        println!("route: {:?}", route);
+        assert!(uniswap.build_call(step).is_ok());
     }
 }
@@ -0,0 +1,461 @@
+use crate::account::AccountType;
+use crate::call::{Call, CallParams, SubCall, SubExtrinsic};
+use crate::chain::Chain;
+use crate::constants::XCM_DEFAULT_DEST_WEIGHT;
+use crate::utils::{serde_amount, serde_hex_bytes, ToArray};
+use alloc::{collections::BTreeMap, string::String, vec, vec::Vec};
+use scale::{Decode, Encode};
+use serde::{Deserialize, Serialize};
+use xcm::{
+    v2, v3::prelude::*, v3::Weight, VersionedMultiAsset, VersionedMultiAssets,
+    VersionedMultiLocation,
+};
+
+/// Pallet the transfer is sent with on the source chain
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum XcmPallet {
+    /// `limited_reserve_transfer_assets` of pallet xcm, e.g. polkadotXcm of AssetHub or
+    /// xcmPallet of relay chains. Versioned data is encoded in `xcm_version`, 2 or 3
+    PolkadotXcm { pallet_id: u8, xcm_version: u32 },
+    /// `transfer` of pallet xtransfer of Phala and Khala
+    XTransfer { pallet_id: u8 },
+    /// Pallet xtokens, with `transfer_multiasset`, `transfer_multiasset_with_fee` if the fee
+    /// is paid in the transferred asset, or `transfer_multiassets` if it is paid in another one
+    Xtokens { pallet_id: u8 },
+}
+
+/// Asset paid for the fee on the destination chain along with the transferred asset
+#[derive(Clone, Debug, Decode, Encode, Eq, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+pub struct XcmFee {
+    // Asset on the source chain in the form of step assets, i.e. the encoded `MultiLocation` on
+    // Substrate chains and the ERC20 address on EVM chains
+    #[serde(with = "serde_hex_bytes")]
+    pub asset: Vec<u8>,
+    #[serde(with = "serde_amount")]
+    pub amount: u128,
+}
+
+/// XCM parameters of a transfer action, configured in the registry per action
+#[derive(Clone, Debug, Default, Decode, Encode, Eq, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+pub struct XcmConfig {
+    // Fee on the destination is paid in the transferred asset if `None`
+    #[serde(default)]
+    pub fee: Option<XcmFee>,
+}
+
+/// Weight limits of XCM executed on the destination chains, keyed by chain name.
+///
+/// Limits are configured per chain with `xcm_dest_weight` of the chain entry, transfers to
+/// chains without one are not limited
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct XcmDestWeights(BTreeMap<String, (u64, u64)>);
+
+impl XcmDestWeights {
+    pub fn new(chains: &[Chain]) -> Self {
+        Self(
+            chains
+                .iter()
+                .filter_map(|chain| {
+                    chain
+                        .xcm_dest_weight
+                        .map(|weight| (chain.name.clone(), weight))
+                })
+                .collect(),
+        )
+    }
+
+    pub fn weight_limit(&self, dest_chain: &str) -> WeightLimit {
+        self.0
+            .get(dest_chain)
+            .map_or(WeightLimit::Unlimited, |(ref_time, proof_size)| {
+                WeightLimit::Limited(Weight::from_parts(*ref_time, *proof_size))
+            })
+    }
+}
+
+/// Transfer of an asset to a beneficiary on another chain with XCM.
+///
+/// Fee on the destination is paid in the transferred asset unless `fee` is given
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct XcmTransfer {
+    // Destination chain, as seen from the source chain
+    pub dest: MultiLocation,
+    // Account on the destination chain
+    pub beneficiary: Junction,
+    pub asset: MultiAsset,
+    pub fee: Option<MultiAsset>,
+    pub weight_limit: WeightLimit,
+}
+
+impl XcmTransfer {
+    pub fn new(dest: MultiLocation, beneficiary: Junction, asset: MultiAsset) -> Self {
+        Self {
+            dest,
+            beneficiary,
+            asset,
+            fee: None,
+            weight_limit: WeightLimit::Unlimited,
+        }
+    }
+
+    pub fn with_fee(mut self, fee: MultiAsset) -> Self {
+        self.fee = Some(fee);
+        self
+    }
+
+    pub fn with_weight_limit(mut self, weight_limit: WeightLimit) -> Self {
+        self.weight_limit = weight_limit;
+        self
+    }
+
+    /// Apply the fee asset configured for the action, the fee asset is an encoded `MultiLocation`
+    pub fn with_config(self, config: &XcmConfig) -> Result<Self, &'static str> {
+        match &config.fee {
+            Some(fee) => {
+                let location = MultiLocation::decode(&mut fee.asset.as_slice())
+                    .map_err(|_| "InvalidFeeAsset")?;
+                Ok(self.with_fee((location, fee.amount).into()))
+            }
+            None => Ok(self),
+        }
+    }
+
+    /// Location of the beneficiary as seen from the source chain
+    pub fn beneficiary_location(&self) -> Result<MultiLocation, &'static str> {
+        self.dest
+            .pushed_with_interior(self.beneficiary)
+            .map_err(|_| "InvalidDestination")
+    }
+
+    /// Weight to send to pallets that don't accept unlimited weight
+    pub fn dest_weight(&self) -> Weight {
+        match self.weight_limit {
+            WeightLimit::Limited(weight) => weight,
+            WeightLimit::Unlimited => XCM_DEFAULT_DEST_WEIGHT,
+        }
+    }
+
+    /// Fee paid in an asset other than the transferred one
+    fn separate_fee(&self) -> Option<&MultiAsset> {
+        self.fee.as_ref().filter(|fee| fee.id != self.asset.id)
+    }
+
+    /// Assets to transfer and the index of the fee asset among them
+    fn assets(&self) -> (MultiAssets, u32) {
+        match self.separate_fee() {
+            Some(fee) => {
+                // Assets are sorted by `MultiAssets`
+                let assets = MultiAssets::from(vec![self.asset.clone(), fee.clone()]);
+                let fee_item = assets
+                    .inner()
+                    .iter()
+                    .position(|asset| asset.id == fee.id)
+                    .unwrap_or_default() as u32;
+                (assets, fee_item)
+            }
+            None => (MultiAssets::from(vec![self.asset.clone()]), 0),
+        }
+    }
+
+    /// Build the extrinsic of the transfer with `pallet`
+    pub fn build(&self, pallet: XcmPallet) -> Result<Call, &'static str> {
+        let calldata = match pallet {
+            XcmPallet::PolkadotXcm {
+                pallet_id,
+                xcm_version,
+            } => {
+                let beneficiary = MultiLocation::new(0, X1(self.beneficiary));
+                let (assets, fee_item) = self.assets();
+                let call = match xcm_version {
+                    2 => (
+                        VersionedMultiLocation::V2(
+                            v2::MultiLocation::try_from(self.dest)
+                                .map_err(|_| "InvalidDestination")?,
+                        ),
+                        VersionedMultiLocation::V2(
+                            v2::MultiLocation::try_from(beneficiary)
+                                .map_err(|_| "InvalidBeneficiary")?,
+                        ),
+                        VersionedMultiAssets::V2(
+                            v2::MultiAssets::try_from(assets).map_err(|_| "InvalidMultiAsset")?,
+                        ),
+                        fee_item,
+                        match self.weight_limit {
+                            WeightLimit::Limited(weight) => {
+                                v2::WeightLimit::Limited(weight.ref_time())
+                            }
+                            WeightLimit::Unlimited => v2::WeightLimit::Unlimited,
+                        },
+                    )
+                        .encode(),
+                    3 => (
+                        VersionedMultiLocation::V3(self.dest),
+                        VersionedMultiLocation::V3(beneficiary),
+                        VersionedMultiAssets::V3(assets),
+                        fee_item,
+                        self.weight_limit,
+                    )
+                        .encode(),
+                    _ => return Err("UnsupportedXcmVersion"),
+                };
+                SubExtrinsic {
+                    pallet_id,
+                    call_id: 0x08u8,
+                    call,
+                }
+                .encode()
+            }
+            XcmPallet::XTransfer { pallet_id } => {
+                if self.separate_fee().is_some() {
+                    return Err("UnsupportedFeeAsset");
+                }
+                SubExtrinsic {
+                    pallet_id,
+                    call_id: 0x0u8,
+                    call: (
+                        self.asset.clone(),
+                        self.beneficiary_location()?,
+                        Some(self.dest_weight()),
+                    ),
+                }
+                .encode()
+            }
+            XcmPallet::Xtokens { pallet_id } => {
+                let dest = VersionedMultiLocation::V3(self.beneficiary_location()?);
+                match (&self.fee, self.separate_fee()) {
+                    (None, _) => SubExtrinsic {
+                        pallet_id,
+                        call_id: 0x01u8,
+                        call: (
+                            VersionedMultiAsset::V3(self.asset.clone()),
+                            dest,
+                            self.weight_limit,
+                        ),
+                    }
+                    .encode(),
+                    (Some(fee), None) => SubExtrinsic {
+                        pallet_id,
+                        call_id: 0x03u8,
+                        call: (
+                            VersionedMultiAsset::V3(self.asset.clone()),
+                            VersionedMultiAsset::V3(fee.clone()),
+                            dest,
+                            self.weight_limit,
+                        ),
+                    }
+                    .encode(),
+                    (Some(_), Some(_)) => {
+                        let (assets, fee_item) = self.assets();
+                        SubExtrinsic {
+                            pallet_id,
+                            call_id: 0x05u8,
+                            call: (
+                                VersionedMultiAssets::V3(assets),
+                                fee_item,
+                                dest,
+                                self.weight_limit,
+                            ),
+                        }
+                        .encode()
+                    }
+                }
+            }
+        };
+
+        Ok(Call {
+            params: CallParams::Sub(SubCall { calldata }),
+            input_call: None,
+            call_index: None,
+        })
+    }
+}
+
+/// Account junction of the recipient, `AccountKey20` or `AccountId32` by `account_type`
+pub fn account_junction(
+    recipient: &[u8],
+    account_type: &AccountType,
+) -> Result<Junction, &'static str> {
+    match (account_type, recipient.len()) {
+        (AccountType::Account20, 20) => Ok(AccountKey20 {
+            network: None,
+            key: recipient.to_array(),
+        }),
+        (AccountType::Account32, 32) => Ok(AccountId32 {
+            network: None,
+            id: recipient.to_array(),
+        }),
+        _ => Err("InvalidRecipient"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RECIPIENT: [u8; 32] = [1; 32];
+
+    fn dot_transfer(dest: MultiLocation) -> XcmTransfer {
+        XcmTransfer::new(
+            dest,
+            account_junction(&RECIPIENT, &AccountType::Account32).unwrap(),
+            (MultiLocation::parent(), 10_000_000_000u128).into(),
+        )
+    }
+
+    fn calldata(call: &Call) -> &[u8] {
+        match &call.params {
+            CallParams::Sub(sub_call) => &sub_call.calldata,
+            _ => panic!("Not a Substrate call"),
+        }
+    }
+
+    #[test]
+    fn test_polkadot_xcm() {
+        let transfer = dot_transfer(MultiLocation::new(1, X1(Parachain(2035))))
+            .with_weight_limit(WeightLimit::Limited(XCM_DEFAULT_DEST_WEIGHT));
+        let call = transfer
+            .build(XcmPallet::PolkadotXcm {
+                pallet_id: 0x1f,
+                xcm_version: 3,
+            })
+            .unwrap();
+        let data = calldata(&call);
+        assert_eq!(&data[..2], &[0x1f, 0x08]);
+        let (dest, beneficiary, assets, fee_item, weight_limit): (
+            VersionedMultiLocation,
+            VersionedMultiLocation,
+            VersionedMultiAssets,
+            u32,
+            WeightLimit,
+        ) = Decode::decode(&mut &data[2..]).unwrap();
+        assert_eq!(dest, VersionedMultiLocation::V3(transfer.dest));
+        assert_eq!(
+            beneficiary,
+            VersionedMultiLocation::V3(MultiLocation::new(0, X1(transfer.beneficiary)))
+        );
+        assert_eq!(
+            assets,
+            VersionedMultiAssets::V3(MultiAssets::from(vec![transfer.asset.clone()]))
+        );
+        assert_eq!(fee_item, 0);
+        assert_eq!(weight_limit, transfer.weight_limit);
+
+        // Encoded in XCM v2
+        let call = transfer
+            .build(XcmPallet::PolkadotXcm {
+                pallet_id: 0x63,
+                xcm_version: 2,
+            })
+            .unwrap();
+        let (dest, _, _, _, _): (
+            VersionedMultiLocation,
+            VersionedMultiLocation,
+            VersionedMultiAssets,
+            u32,
+            v2::WeightLimit,
+        ) = Decode::decode(&mut &calldata(&call)[2..]).unwrap();
+        assert!(matches!(dest, VersionedMultiLocation::V2(_)));
+    }
+
+    #[test]
+    fn test_xtokens_fee() {
+        let transfer = dot_transfer(MultiLocation::new(1, X1(Parachain(2035))));
+        let dest = transfer.beneficiary_location().unwrap();
+        assert_eq!(
+            dest,
+            MultiLocation::new(1, X2(Parachain(2035), transfer.beneficiary))
+        );
+        let pallet = XcmPallet::Xtokens { pallet_id: 0x36 };
+
+        let call = transfer.build(pallet).unwrap();
+        assert_eq!(&calldata(&call)[..2], &[0x36, 0x01]);
+
+        // Fee in the transferred asset
+        let fee: MultiAsset = (MultiLocation::parent(), 100_000_000u128).into();
+        let call = transfer.clone().with_fee(fee).build(pallet).unwrap();
+        assert_eq!(&calldata(&call)[..2], &[0x36, 0x03]);
+
+        // Fee in another asset
+        let fee: MultiAsset = (MultiLocation::here(), 100_000_000u128).into();
+        let call = transfer
+            .clone()
+            .with_fee(fee.clone())
+            .build(pallet)
+            .unwrap();
+        let data = calldata(&call);
+        assert_eq!(&data[..2], &[0x36, 0x05]);
+        let (assets, fee_item): (VersionedMultiAssets, u32) =
+            Decode::decode(&mut &data[2..]).unwrap();
+        match assets {
+            VersionedMultiAssets::V3(assets) => {
+                assert_eq!(assets.inner()[fee_item as usize], fee)
+            }
+            _ => assert!(false),
+        }
+        // Pallet xtransfer only takes a single asset
+        assert_eq!(
+            transfer
+                .with_fee(fee)
+                .build(XcmPallet::XTransfer { pallet_id: 0x52 }),
+            Err("UnsupportedFeeAsset")
+        );
+    }
+
+    #[test]
+    fn test_account_junction() {
+        assert!(account_junction(&RECIPIENT, &AccountType::Account32).is_ok());
+        assert_eq!(
+            account_junction(&RECIPIENT, &AccountType::Account20),
+            Err("InvalidRecipient")
+        );
+    }
+
+    #[test]
+    fn test_xcm_config() {
+        let transfer = dot_transfer(MultiLocation::new(1, X1(Parachain(2035))));
+        let configured = transfer.clone().with_config(&XcmConfig::default()).unwrap();
+        assert_eq!(configured, transfer);
+
+        let config = XcmConfig {
+            fee: Some(XcmFee {
+                asset: MultiLocation::here().encode(),
+                amount: 100_000_000,
+            }),
+        };
+        let configured = transfer.clone().with_config(&config).unwrap();
+        assert_eq!(
+            configured.fee,
+            Some((MultiLocation::here(), 100_000_000u128).into())
+        );
+        assert_eq!(configured.weight_limit, WeightLimit::Unlimited);
+
+        let config = XcmConfig {
+            fee: Some(XcmFee {
+                asset: vec![0xff],
+                amount: 1,
+            }),
+        };
+        assert_eq!(transfer.with_config(&config), Err("InvalidFeeAsset"));
+    }
+
+    #[test]
+    fn test_dest_weights() {
+        use crate::constants::PHALA_XCM_DEST_WEIGHT;
+
+        let registry = crate::registry::Registry::new();
+        let weights = XcmDestWeights::new(&registry.chains);
+        for chain in ["Phala", "Khala"] {
+            assert_eq!(
+                weights.weight_limit(chain),
+                WeightLimit::Limited(Weight::from_parts(
+                    PHALA_XCM_DEST_WEIGHT.0,
+                    PHALA_XCM_DEST_WEIGHT.1
+                ))
+            );
+        }
+        assert_eq!(weights.weight_limit("Moonbeam"), WeightLimit::Unlimited);
+        assert_eq!(weights.weight_limit("Unknown"), WeightLimit::Unlimited);
+    }
+}
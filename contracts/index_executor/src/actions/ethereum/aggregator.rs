@@ -0,0 +1,435 @@
+use alloc::{format, string::String, vec, vec::Vec};
+use pink_extension::http_req;
+use pink_web3::{ethabi::Address, types::U256};
+use scale::{Decode, Encode};
+use serde::{Deserialize, Serialize};
+
+use crate::call::{Call, CallBuilder, CallParams, EvmCall};
+use crate::step::Step;
+
+/// Address aggregator APIs use for the native asset of the chain
+const NATIVE_PLACEHOLDER: [u8; 20] = hex_literal::hex!("EeeeeEeeeEeEeeEeEeEeeEEEeeeeEeeeeeeeEEeE");
+/// Slippage in percent requested from the aggregator, the returned calldata carries the
+/// corresponding minimum return
+const SLIPPAGE_PERCENT: u32 = 1;
+
+/// Selector of `swap(address,(address,address,address,address,uint256,uint256,uint256),bytes,bytes)`
+/// of 1inch AggregationRouterV5
+const ONEINCH_SWAP_SELECTOR: [u8; 4] = hex_literal::hex!("12aa3caf");
+/// Selector of `transformERC20(address,address,uint256,uint256,(uint32,bytes)[])` of 0x ExchangeProxy
+const ZEROEX_TRANSFORM_SELECTOR: [u8; 4] = hex_literal::hex!("415565b0");
+
+/// API of the aggregator, determines how the quote is requested and the calldata is decoded
+#[derive(Clone, Debug, Decode, Encode, Eq, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+pub enum AggregatorApi {
+    /// 1inch swap API v5.2 of the developer portal, only `swap` of the router is accepted
+    /// since it is the only one with a custom recipient
+    OneInch,
+    /// 0x swap API v1, only `transformERC20` is accepted, output goes to the caller
+    ZeroEx,
+}
+
+impl AggregatorApi {
+    /// Header the API key is sent in
+    fn auth_header(&self, api_key: &str) -> (String, String) {
+        match self {
+            AggregatorApi::OneInch => ("Authorization".into(), format!("Bearer {}", api_key)),
+            AggregatorApi::ZeroEx => ("0x-api-key".into(), api_key.into()),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct OneInchTx {
+    to: String,
+    data: String,
+    value: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct OneInchResponse {
+    to_amount: String,
+    tx: OneInchTx,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ZeroExResponse {
+    to: String,
+    data: String,
+    value: String,
+    buy_amount: String,
+}
+
+/// Transaction returned by the aggregator API
+#[derive(Debug, PartialEq, Eq)]
+struct Quote {
+    target: Address,
+    calldata: Vec<u8>,
+    value: U256,
+    amount_out: U256,
+}
+
+/// Fields of the swap decoded from the calldata
+#[derive(Debug, PartialEq, Eq)]
+struct DecodedSwap {
+    src_token: Address,
+    dst_token: Address,
+    // `None` if the output goes to the caller
+    recipient: Option<Address>,
+    amount: U256,
+    min_return: U256,
+    // Offset of `amount` in calldata, updated by the handler during settlement
+    amount_offset: usize,
+}
+
+/// Swap with the calldata returned by an aggregator API.
+///
+/// The calldata is requested when the call is built, its target must be one of `allowlist`,
+/// and tokens, amount, recipient and minimum return decoded from it are checked against the step
+#[derive(Clone)]
+pub struct AggregatorSwap {
+    api: AggregatorApi,
+    api_url: String,
+    allowlist: Vec<Address>,
+    native: Address,
+    handler: Address,
+    api_key: Option<String>,
+}
+
+impl AggregatorSwap {
+    pub fn new(
+        api: AggregatorApi,
+        api_url: &str,
+        allowlist: Vec<Address>,
+        native: Address,
+        handler: Address,
+    ) -> Self
+    where
+        Self: Sized,
+    {
+        Self {
+            api,
+            api_url: api_url.trim_end_matches('/').into(),
+            allowlist,
+            native,
+            handler,
+            api_key: None,
+        }
+    }
+
+    /// Key sent with the quote requests, APIs reject requests without it
+    pub fn with_api_key(mut self, api_key: Option<String>) -> Self {
+        self.api_key = api_key;
+        self
+    }
+
+    fn headers(&self) -> Vec<(String, String)> {
+        let mut headers: Vec<(String, String)> = vec![("Accept".into(), "application/json".into())];
+        if let Some(api_key) = &self.api_key {
+            headers.push(self.api.auth_header(api_key));
+        }
+        headers
+    }
+
+    fn api_token(&self, token: Address) -> String {
+        let token = if token == self.native {
+            NATIVE_PLACEHOLDER.into()
+        } else {
+            token
+        };
+        format!("{:?}", token)
+    }
+
+    fn request_quote(&self, step: &Step, amount: u128) -> Result<Quote, &'static str> {
+        let src = self.api_token(Address::from_slice(&step.spend_asset));
+        let dst = self.api_token(Address::from_slice(&step.receive_asset));
+        let url = match self.api {
+            AggregatorApi::OneInch => format!(
+                "{}/swap?src={}&dst={}&amount={}&from={:?}&receiver={:?}&slippage={}&disableEstimate=true",
+                self.api_url,
+                src,
+                dst,
+                amount,
+                self.handler,
+                Address::from_slice(&step.recipient),
+                SLIPPAGE_PERCENT
+            ),
+            AggregatorApi::ZeroEx => format!(
+                "{}/swap/v1/quote?sellToken={}&buyToken={}&sellAmount={}&takerAddress={:?}&slippagePercentage=0.{:02}&skipValidation=true",
+                self.api_url, src, dst, amount, self.handler, SLIPPAGE_PERCENT
+            ),
+        };
+        pink_extension::debug!("AggregatorSwap: request quote from {}", &url);
+        let response = http_req!("GET", url, vec![], self.headers());
+        if response.status_code != 200 {
+            pink_extension::error!(
+                "AggregatorSwap: request quote failed with status {}",
+                response.status_code
+            );
+            return Err("CallAggregatorFailed");
+        }
+        self.parse_quote(&response.body)
+    }
+
+    fn parse_quote(&self, body: &[u8]) -> Result<Quote, &'static str> {
+        let (to, data, value, amount_out) = match self.api {
+            AggregatorApi::OneInch => {
+                let response: OneInchResponse =
+                    pink_json::from_slice(body).or(Err("InvalidAggregatorResponse"))?;
+                (
+                    response.tx.to,
+                    response.tx.data,
+                    response.tx.value,
+                    response.to_amount,
+                )
+            }
+            AggregatorApi::ZeroEx => {
+                let response: ZeroExResponse =
+                    pink_json::from_slice(body).or(Err("InvalidAggregatorResponse"))?;
+                (
+                    response.to,
+                    response.data,
+                    response.value,
+                    response.buy_amount,
+                )
+            }
+        };
+        let target: [u8; 20] = hex::decode(to.trim_start_matches("0x"))
+            .ok()
+            .and_then(|to| to.try_into().ok())
+            .ok_or("InvalidAggregatorResponse")?;
+        Ok(Quote {
+            target: target.into(),
+            calldata: hex::decode(data.trim_start_matches("0x"))
+                .or(Err("InvalidAggregatorResponse"))?,
+            value: U256::from_dec_str(&value).or(Err("InvalidAggregatorResponse"))?,
+            amount_out: U256::from_dec_str(&amount_out).or(Err("InvalidAggregatorResponse"))?,
+        })
+    }
+
+    fn decode_swap(&self, calldata: &[u8]) -> Result<DecodedSwap, &'static str> {
+        let selector = calldata.get(..4).ok_or("InvalidCalldata")?;
+        let word = |offset: usize| -> Result<U256, &'static str> {
+            calldata
+                .get(offset..offset + 32)
+                .map(U256::from_big_endian)
+                .ok_or("InvalidCalldata")
+        };
+        let address = |offset: usize| -> Result<Address, &'static str> {
+            calldata
+                .get(offset + 12..offset + 32)
+                .map(Address::from_slice)
+                .ok_or("InvalidCalldata")
+        };
+        let normalize = |token: Address| {
+            if token == NATIVE_PLACEHOLDER.into() {
+                self.native
+            } else {
+                token
+            }
+        };
+        match (&self.api, selector) {
+            // executor, then the static SwapDescription tuple: srcToken, dstToken, srcReceiver,
+            // dstReceiver, amount, minReturnAmount, flags
+            (AggregatorApi::OneInch, s) if s == ONEINCH_SWAP_SELECTOR => Ok(DecodedSwap {
+                src_token: normalize(address(36)?),
+                dst_token: normalize(address(68)?),
+                recipient: Some(address(132)?),
+                amount: word(164)?,
+                min_return: word(196)?,
+                amount_offset: 164,
+            }),
+            // inputToken, outputToken, inputTokenAmount, minOutputTokenAmount, transformations
+            (AggregatorApi::ZeroEx, s) if s == ZEROEX_TRANSFORM_SELECTOR => Ok(DecodedSwap {
+                src_token: normalize(address(4)?),
+                dst_token: normalize(address(36)?),
+                recipient: None,
+                amount: word(68)?,
+                min_return: word(100)?,
+                amount_offset: 68,
+            }),
+            _ => Err("UnsupportedAggregatorCall"),
+        }
+    }
+
+    /// Check the quote does what the step asks for
+    fn validate(&self, step: &Step, quote: &Quote, swap: &DecodedSwap) -> Result<(), &'static str> {
+        if !self.allowlist.contains(&quote.target) {
+            return Err("AggregatorTargetNotAllowed");
+        }
+        if swap.src_token != Address::from_slice(&step.spend_asset)
+            || swap.dst_token != Address::from_slice(&step.receive_asset)
+        {
+            return Err("AggregatorAssetMismatch");
+        }
+        let recipient = Address::from_slice(&step.recipient);
+        // Output goes to the handler if the call has no recipient
+        if swap.recipient.unwrap_or(self.handler) != recipient {
+            return Err("AggregatorRecipientMismatch");
+        }
+        if swap.amount != U256::from(step.spend_amount.ok_or("MissingSpendAmount")?) {
+            return Err("AggregatorAmountMismatch");
+        }
+        if swap.min_return.is_zero() || swap.min_return > quote.amount_out {
+            return Err("AggregatorMinReturnMismatch");
+        }
+        // Amount sent as value can not be updated during settlement
+        if !quote.value.is_zero() {
+            return Err("UnsupportedAsset");
+        }
+        Ok(())
+    }
+}
+
+impl CallBuilder for AggregatorSwap {
+    fn build_call(&self, step: Step) -> Result<Call, &'static str> {
+        let spend_amount = step.spend_amount.ok_or("MissingSpendAmount")?;
+        if Address::from_slice(&step.spend_asset) == self.native {
+            return Err("UnsupportedAsset");
+        }
+        let quote = self.request_quote(&step, spend_amount)?;
+        let swap = self.decode_swap(&quote.calldata)?;
+        self.validate(&step, &quote, &swap)?;
+
+        Ok(Call {
+            params: CallParams::Evm(EvmCall {
+                target: quote.target,
+                calldata: quote.calldata,
+                value: U256::from(0),
+
+                need_settle: true,
+                update_offset: U256::from(swap.amount_offset),
+                update_len: U256::from(32),
+                spender: quote.target,
+                spend_asset: swap.src_token,
+                spend_amount: swap.amount,
+                receive_asset: swap.dst_token,
+            }),
+            input_call: None,
+            call_index: None,
+        })
+    }
+
+    // Route and minimum return in the calldata are quoted for the spend amount
+    fn needs_final_amount(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ROUTER: [u8; 20] = hex_literal::hex!("1111111254EEB25477B68fb85Ed929f73A960582");
+    const HANDLER: [u8; 20] = hex_literal::hex!("d693bDC5cb0cF2a31F08744A0Ec135a68C26FE1c");
+
+    fn word(bytes: &[u8]) -> Vec<u8> {
+        let mut word = vec![0; 32 - bytes.len()];
+        word.extend_from_slice(bytes);
+        word
+    }
+
+    fn swap_step() -> Step {
+        Step {
+            exe: String::from("ethereum_1inch"),
+            source_chain: String::from("Ethereum"),
+            dest_chain: String::from("Ethereum"),
+            // PHA
+            spend_asset: hex::decode("6c5bA91642F10282b576d91922Ae6448C9d52f4E").unwrap(),
+            // ETH
+            receive_asset: [0; 20].to_vec(),
+            sender: None,
+            recipient: HANDLER.to_vec(),
+            spend_amount: Some(1_000_000),
+            origin_balance: None,
+            nonce: None,
+        }
+    }
+
+    fn oneinch_calldata(step: &Step, min_return: u128) -> Vec<u8> {
+        [
+            ONEINCH_SWAP_SELECTOR.to_vec(),
+            // executor
+            word(&[1; 20]),
+            word(&step.spend_asset),
+            word(&NATIVE_PLACEHOLDER),
+            // srcReceiver
+            word(&[1; 20]),
+            word(&step.recipient),
+            word(&step.spend_amount.unwrap().to_be_bytes()),
+            word(&min_return.to_be_bytes()),
+            // flags
+            word(&[0]),
+        ]
+        .concat()
+    }
+
+    #[test]
+    fn test_validate_oneinch_swap() {
+        let aggregator = AggregatorSwap::new(
+            AggregatorApi::OneInch,
+            "https://api.1inch.dev/swap/v5.2/1/",
+            vec![ROUTER.into()],
+            [0; 20].into(),
+            HANDLER.into(),
+        );
+        let step = swap_step();
+        let body = format!(
+            r#"{{"toAmount":"1000","tx":{{"from":"0x{}","to":"0x{}","data":"0x{}","value":"0","gas":0,"gasPrice":"0"}}}}"#,
+            hex::encode(HANDLER),
+            hex::encode(ROUTER),
+            hex::encode(oneinch_calldata(&step, 990))
+        );
+        let quote = aggregator.parse_quote(body.as_bytes()).unwrap();
+        let swap = aggregator.decode_swap(&quote.calldata).unwrap();
+        assert_eq!(swap.dst_token, Address::from([0; 20]));
+        assert_eq!(swap.amount_offset, 164);
+        assert_eq!(aggregator.validate(&step, &quote, &swap), Ok(()));
+
+        let mut other_step = swap_step();
+        other_step.recipient = [2; 20].to_vec();
+        assert_eq!(
+            aggregator.validate(&other_step, &quote, &swap),
+            Err("AggregatorRecipientMismatch")
+        );
+        let quote = Quote {
+            target: [2; 20].into(),
+            ..quote
+        };
+        assert_eq!(
+            aggregator.validate(&step, &quote, &swap),
+            Err("AggregatorTargetNotAllowed")
+        );
+        let quote = aggregator.parse_quote(body.as_bytes()).unwrap();
+        let swap = aggregator.decode_swap(&oneinch_calldata(&step, 0)).unwrap();
+        assert_eq!(
+            aggregator.validate(&step, &quote, &swap),
+            Err("AggregatorMinReturnMismatch")
+        );
+    }
+
+    #[test]
+    fn test_api_key_header() {
+        let aggregator = AggregatorSwap::new(
+            AggregatorApi::OneInch,
+            "https://api.1inch.dev/swap/v5.2/1",
+            vec![ROUTER.into()],
+            [0; 20].into(),
+            HANDLER.into(),
+        );
+        assert_eq!(aggregator.headers().len(), 1);
+        let aggregator = aggregator.with_api_key(Some("key".into()));
+        assert_eq!(
+            aggregator.headers()[1],
+            ("Authorization".into(), "Bearer key".into())
+        );
+        assert_eq!(
+            AggregatorApi::ZeroEx.auth_header("key"),
+            ("0x-api-key".into(), "key".into())
+        );
+    }
+}
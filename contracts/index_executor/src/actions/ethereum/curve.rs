@@ -0,0 +1,280 @@
+use alloc::{vec, vec::Vec};
+use pink_web3::{
+    api::{Eth, Namespace},
+    contract::{tokens::Tokenize, Contract, Options},
+    ethabi::{Address, Token},
+    transports::{resolve_ready, PinkHttp},
+    types::U256,
+};
+
+use crate::actions::base::evm_transactor::EvmTransactor;
+use crate::call::{ActionFee, Call, CallBuilder, CallParams, EvmCall};
+use crate::rpc::{self, RpcEndpoint, RpcHealth};
+use crate::step::Step;
+
+/// Max number of coins in a Curve pool
+const MAX_COINS: u64 = 8;
+/// Precision of the `fee` of a Curve pool
+const FEE_DENOMINATOR: u64 = 10_000_000_000;
+
+/// Swap stablecoins through a Curve StableSwap pool.
+///
+/// Coin indices are looked up from `coins(i)` of the pool, or from `underlying_coins(i)` if the
+/// pool swaps the underlying coins of its lending tokens with `exchange_underlying`.
+///
+/// The pool sends the output to the caller, which is the handler, so it is forwarded to the
+/// recipient in the same batch if the recipient is another account
+#[derive(Clone)]
+pub struct CurveStableSwap {
+    pub eth: Eth<PinkHttp>,
+    pub pool: Contract<PinkHttp>,
+    pub underlying: bool,
+    // Handler contract of the chain, which receives the output of the swap
+    pub handler: Address,
+    // Endpoints the pool is queried from, failover happens between them
+    pub endpoints: Vec<RpcEndpoint>,
+    pub health: RpcHealth,
+}
+
+impl CurveStableSwap {
+    pub fn new(rpc: &str, pool: Address, underlying: bool, handler: Address) -> Self {
+        let eth = Eth::new(PinkHttp::new(rpc));
+        let pool = Contract::from_json(
+            eth.clone(),
+            pool,
+            include_bytes!("../../abi/CurveStableSwap.json"),
+        )
+        .expect("Bad abi data");
+
+        Self {
+            eth,
+            pool,
+            underlying,
+            handler,
+            endpoints: vec![RpcEndpoint::new(rpc, 0)],
+            health: RpcHealth::default(),
+        }
+    }
+
+    /// Fail over between `endpoints` when querying the pool, requests are recorded to `health`
+    pub fn with_endpoints(mut self, endpoints: Vec<RpcEndpoint>, health: &RpcHealth) -> Self {
+        self.endpoints = endpoints;
+        self.health = health.clone();
+        self
+    }
+
+    fn on_endpoint(&self, endpoint: &str) -> Self {
+        Self {
+            eth: Eth::new(PinkHttp::new(endpoint)),
+            pool: rpc::contract_on(&self.pool, endpoint),
+            ..self.clone()
+        }
+    }
+
+    /// Coins of the pool, ordered by their index
+    fn coins(&self) -> Vec<Address> {
+        let func = if self.underlying {
+            "underlying_coins"
+        } else {
+            "coins"
+        };
+        let mut coins = Vec::new();
+        // Reading past the last coin reverts
+        for i in 0..MAX_COINS {
+            let coin: Result<Address, _> =
+                resolve_ready(
+                    self.pool
+                        .query(func, U256::from(i), None, Options::default(), None),
+                );
+            match coin {
+                Ok(coin) => coins.push(coin),
+                Err(_) => break,
+            }
+        }
+        coins
+    }
+
+    fn get_dy(&self, i: usize, j: usize, dx: U256) -> Result<U256, &'static str> {
+        let func = if self.underlying {
+            "get_dy_underlying"
+        } else {
+            "get_dy"
+        };
+        resolve_ready(self.pool.query(
+            func,
+            (coin_index(i), coin_index(j), dx),
+            None,
+            Options::default(),
+            None,
+        ))
+        .map_err(|_| "FailedToQuote")
+    }
+
+    /// Swap fee of the pool, with a precision of `FEE_DENOMINATOR`
+    fn fee(&self) -> Result<U256, &'static str> {
+        resolve_ready(self.pool.query("fee", (), None, Options::default(), None))
+            .map_err(|_| "FailedToQueryFee")
+    }
+
+    /// Coin indices of the step, the amount it spends and the quoted output
+    fn quote(&self, step: &Step) -> Result<(usize, usize, U256, U256), &'static str> {
+        let asset0 = Address::from_slice(&step.spend_asset);
+        let asset1 = Address::from_slice(&step.receive_asset);
+        let dx = U256::from(step.spend_amount.ok_or("MissingSpendAmount")?);
+        let (i, j) = coin_indices(&self.coins(), asset0, asset1)?;
+
+        let dy = self.get_dy(i, j, dx)?;
+        pink_extension::debug!("CurveStableSwap: quoted {:?} for {:?}", dy, dx);
+        if dy.is_zero() {
+            return Err("NoLiquidity");
+        }
+        Ok((i, j, dx, dy))
+    }
+}
+
+/// Fee charged on the output `dy`, which already has the fee deducted
+fn fee_of(dy: U256, fee: U256) -> Result<U256, &'static str> {
+    let denominator = U256::from(FEE_DENOMINATOR);
+    if fee >= denominator {
+        return Err("InvalidPoolFee");
+    }
+    Ok(dy * fee / (denominator - fee))
+}
+
+/// Coin indices are `int128` in StableSwap pools
+fn coin_index(i: usize) -> Token {
+    Token::Int(U256::from(i))
+}
+
+/// Indices of the two assets among the coins of the pool
+fn coin_indices(
+    coins: &[Address],
+    asset0: Address,
+    asset1: Address,
+) -> Result<(usize, usize), &'static str> {
+    let index = |asset: Address| {
+        coins
+            .iter()
+            .position(|coin| *coin == asset)
+            .ok_or("AssetNotInPool")
+    };
+    let (i, j) = (index(asset0)?, index(asset1)?);
+    if i == j {
+        return Err("InvalidTradingPair");
+    }
+    Ok((i, j))
+}
+
+impl CallBuilder for CurveStableSwap {
+    fn build_call(&self, step: Step) -> Result<Call, &'static str> {
+        let asset0 = Address::from_slice(&step.spend_asset);
+        let asset1 = Address::from_slice(&step.receive_asset);
+        let (i, j, dx, _) = rpc::with_failover(&self.endpoints, &self.health, |endpoint| {
+            self.on_endpoint(endpoint).quote(&step)
+        })?;
+        // Amount in is updated with the actual balance during settlement, so the quoted output
+        // can not be used as the minimum
+        let min_dy = U256::from(1);
+
+        let func = if self.underlying {
+            "exchange_underlying"
+        } else {
+            "exchange"
+        };
+        let swap_func = self
+            .pool
+            .abi()
+            .function(func)
+            .map_err(|_| "NoFunctionFound")?;
+        let swap_calldata = swap_func
+            .encode_input(&(coin_index(i), coin_index(j), dx, min_dy).into_tokens())
+            .map_err(|_| "EncodeParamError")?;
+
+        Ok(Call {
+            params: CallParams::Evm(EvmCall {
+                target: self.pool.address(),
+                calldata: swap_calldata,
+                value: U256::from(0),
+
+                need_settle: true,
+                // dx follows the two coin indices
+                update_offset: U256::from(68),
+                update_len: U256::from(32),
+                spender: self.pool.address(),
+                spend_asset: asset0,
+                spend_amount: dx,
+                receive_asset: asset1,
+            }),
+            input_call: None,
+            call_index: None,
+        })
+    }
+
+    fn build_calls(&self, step: Step) -> Result<Vec<Call>, &'static str> {
+        let receive_asset = Address::from_slice(&step.receive_asset);
+        // Coins of the pool are ERC20 tokens, none is forwarded as value
+        let transactor = EvmTransactor {
+            eth: self.eth.clone(),
+            native: Address::zero(),
+        };
+        transactor.forward(
+            self.build_call(step.clone())?,
+            self.handler,
+            receive_asset,
+            step,
+        )
+    }
+
+    /// Fee of the pool is deducted from the output, in the receive asset
+    fn quote_fee(&self, step: Step) -> Result<Option<ActionFee>, &'static str> {
+        let amount = rpc::with_failover(&self.endpoints, &self.health, |endpoint| {
+            let curve = self.on_endpoint(endpoint);
+            let (_, _, _, dy) = curve.quote(&step)?;
+            fee_of(dy, curve.fee()?)
+        })?;
+        Ok(Some(ActionFee {
+            asset: step.receive_asset,
+            amount: amount.try_into().map_err(|_| "FeeOverflow")?,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_coin_indices() {
+        // DAI, USDC, USDT of 3pool
+        let coins: Vec<Address> = [
+            hex_literal::hex!("6B175474E89094C44Da98b954EedeAC495271d0F"),
+            hex_literal::hex!("A0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48"),
+            hex_literal::hex!("dAC17F958D2ee523a2206206994597C13D831ec7"),
+        ]
+        .iter()
+        .map(|coin| (*coin).into())
+        .collect();
+        assert_eq!(coin_indices(&coins, coins[1], coins[2]), Ok((1, 2)));
+        assert_eq!(coin_indices(&coins, coins[2], coins[0]), Ok((2, 0)));
+        assert_eq!(
+            coin_indices(&coins, coins[0], coins[0]),
+            Err("InvalidTradingPair")
+        );
+        assert_eq!(
+            coin_indices(&coins, coins[0], [0; 20].into()),
+            Err("AssetNotInPool")
+        );
+    }
+
+    #[test]
+    fn test_fee_of() {
+        // 0.01% fee of 3pool
+        let fee = U256::from(1_000_000);
+        assert_eq!(fee_of(U256::from(999_900), fee), Ok(U256::from(100)));
+        assert_eq!(fee_of(U256::from(1_000), U256::zero()), Ok(U256::zero()));
+        assert_eq!(
+            fee_of(U256::from(1_000), U256::from(FEE_DENOMINATOR)),
+            Err("InvalidPoolFee")
+        );
+    }
+}
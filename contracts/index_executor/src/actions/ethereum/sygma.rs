@@ -1,6 +1,6 @@
-use alloc::{collections::BTreeMap, vec, vec::Vec};
-use core::str::FromStr;
+use alloc::{vec, vec::Vec};
 use hex_literal::hex;
+use pink_extension::ResultExt;
 use pink_web3::{
     api::{Eth, Namespace},
     contract::{tokens::Tokenize, Contract, Options},
@@ -8,7 +8,8 @@ use pink_web3::{
     transports::{resolve_ready, PinkHttp},
     types::U256,
 };
-use scale::Encode;
+use scale::{Decode, Encode};
+use serde::{Deserialize, Serialize};
 use xcm::v3::{
     Junction::{AccountId32, Parachain},
     Junctions::{X1, X2},
@@ -17,21 +18,62 @@ use xcm::v3::{
 
 use crate::step::Step;
 use crate::{
-    call::{Call, CallBuilder, CallParams, EvmCall},
-    utils::ToArray,
+    call::{ActionFee, Call, CallBuilder, CallParams, EvmCall},
+    rpc::{self, RpcEndpoint, RpcHealth},
+    utils::{serde_hex, ToArray},
 };
 
+/// Resource id Sygma registered for an ERC20 asset, the same on all domains of the resource
+#[derive(Clone, Debug, Decode, Encode, Eq, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+pub struct SygmaResource {
+    #[serde(with = "serde_hex")]
+    pub asset: [u8; 20],
+    #[serde(with = "serde_hex")]
+    pub resource_id: [u8; 32],
+}
+
+/// Fee of a deposit returned by `calculateFee` of the fee handler
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum SygmaFee {
+    /// Basic and dynamic fee handlers charge the native asset, paid as value of the deposit
+    Native(U256),
+    /// Percentage fee handlers charge the bridged token, collected by the fee handler with
+    /// `transferFrom` on top of the deposited amount, so the fee is deducted from the amount
+    Token(Address, U256),
+}
+
+impl SygmaFee {
+    fn decode(fee: U256, fee_token: Address) -> Self {
+        if fee_token.is_zero() {
+            SygmaFee::Native(fee)
+        } else {
+            SygmaFee::Token(fee_token, fee)
+        }
+    }
+}
+
+/// Deposit ERC20 tokens to the Sygma bridge, to the domain `to_domain_id`.
+///
+/// Tokens can be bridged if they are in `resources`, the fee is quoted from the fee handler
+/// configured on the bridge for the resource
 #[derive(Clone)]
 pub struct EvmSygmaBridge {
     eth: Eth<PinkHttp>,
     contract: Contract<PinkHttp>,
     erc20_handler_address: Address,
     fee_handler_address: Address,
-    fee_amount: u128,
+    // Handler contract of the chain, the depositor of the bridge
+    handler_address: Address,
+    // Native asset of the chain, fee of basic and dynamic fee handlers is paid in it
+    native: Address,
     from_domain_id: u8,
     to_domain_id: u8,
     maybe_parachain_id: Option<u32>,
-    resource_id_map: BTreeMap<Address, [u8; 32]>,
+    resources: Vec<SygmaResource>,
+    // Endpoints the fee handler is queried from, failover happens between them
+    endpoints: Vec<RpcEndpoint>,
+    health: RpcHealth,
 }
 
 #[allow(clippy::too_many_arguments)]
@@ -41,10 +83,12 @@ impl EvmSygmaBridge {
         contract_address: Address,
         erc20_handler_address: Address,
         fee_handler_address: Address,
-        fee_amount: u128,
+        handler_address: Address,
+        native: Address,
         from_domain_id: u8,
         to_domain_id: u8,
         maybe_parachain_id: Option<u32>,
+        resources: Vec<SygmaResource>,
     ) -> Self {
         let eth = Eth::new(PinkHttp::new(rpc));
         let contract = Contract::from_json(
@@ -54,42 +98,39 @@ impl EvmSygmaBridge {
         )
         .expect("Bad abi data");
 
-        let mut resource_id_map = BTreeMap::new();
-        // Goerli GPHA
-        resource_id_map.insert(
-            Address::from_str("B376b0Ee6d8202721838e76376e81eEc0e2FE864").unwrap(),
-            hex!("0000000000000000000000000000000000000000000000000000000000001000"),
-        );
-        // Ethereum PHA
-        resource_id_map.insert(
-            Address::from_str("6c5bA91642F10282b576d91922Ae6448C9d52f4E").unwrap(),
-            hex!("0000000000000000000000000000000000000000000000000000000000000001"),
-        );
-
         Self {
             eth,
             contract,
             erc20_handler_address,
             fee_handler_address,
-            fee_amount,
+            handler_address,
+            native,
             from_domain_id,
             to_domain_id,
             maybe_parachain_id,
-            resource_id_map,
+            resources,
+            endpoints: vec![RpcEndpoint::new(rpc, 0)],
+            health: RpcHealth::default(),
         }
     }
-}
 
-impl CallBuilder for EvmSygmaBridge {
-    fn build_call(&self, step: Step) -> Result<Call, &'static str> {
-        let sender = Address::from_slice(&step.sender.ok_or("MissingSender")?);
-        let spend_asset = Address::from_slice(&step.spend_asset);
-        let resource_id = *self
-            .resource_id_map
-            .get(&spend_asset)
-            .ok_or("NoResourceId")?;
-        let spend_amount = U256::from(step.spend_amount.ok_or("MissingSpendAmount")?);
-        let mut recipient = step.recipient;
+    /// Fail over between `endpoints` when quoting the fee, requests are recorded to `health`
+    pub fn with_endpoints(mut self, endpoints: Vec<RpcEndpoint>, health: &RpcHealth) -> Self {
+        self.endpoints = endpoints;
+        self.health = health.clone();
+        self
+    }
+
+    fn resource_id(&self, asset: Address) -> Result<[u8; 32], &'static str> {
+        self.resources
+            .iter()
+            .find(|resource| Address::from(resource.asset) == asset)
+            .map(|resource| resource.resource_id)
+            .ok_or("NoResourceId")
+    }
+
+    fn deposit_data(&self, recipient: Vec<u8>, amount: U256) -> Vec<u8> {
+        let mut recipient = recipient;
         if recipient.len() == 32 {
             let account_id = AccountId32 {
                 network: None,
@@ -104,7 +145,7 @@ impl CallBuilder for EvmSygmaBridge {
             recipient = rec.encode()
         }
         let mut deposit_data: Vec<u8> = vec![];
-        let token_stats: [u8; 32] = spend_amount.into();
+        let token_stats: [u8; 32] = amount.into();
         deposit_data.extend(token_stats);
         deposit_data.extend_from_slice(&{
             let mut res = Vec::new();
@@ -115,32 +156,132 @@ impl CallBuilder for EvmSygmaBridge {
             res
         });
         deposit_data.extend(recipient);
+        deposit_data
+    }
 
+    /// Quote the fee of the deposit, percentage fee handlers read the amount from `deposit_data`
+    fn calculate_fee(
+        &self,
+        resource_id: [u8; 32],
+        deposit_data: Vec<u8>,
+    ) -> Result<SygmaFee, &'static str> {
         let fee_handler = Contract::from_json(
             self.eth.clone(),
             self.fee_handler_address,
             include_bytes!("../../abi/SygmaBasicFeeHandler.json"),
         )
-        .expect("Bad abi data");
+        .map_err(|_| "BadAbi")?;
+
+        let (fee, fee_token): (Uint, Address) =
+            rpc::with_failover(&self.endpoints, &self.health, |endpoint| {
+                resolve_ready(rpc::contract_on(&fee_handler, endpoint).query(
+                    "calculateFee",
+                    (
+                        self.handler_address,
+                        self.from_domain_id,
+                        self.to_domain_id,
+                        resource_id,
+                        deposit_data.clone(),
+                        hex!("").to_vec(),
+                    ),
+                    None,
+                    Options::default(),
+                    None,
+                ))
+                .log_err("EvmSygmaBridge: calculate fee failed")
+                .or(Err("FailedToCalculateFee"))
+            })?;
+        Ok(SygmaFee::decode(fee, fee_token))
+    }
 
-        let fee: (Uint, Address) = resolve_ready(fee_handler.query(
-            "calculateFee",
-            (
-                sender,
-                self.from_domain_id,
-                self.to_domain_id,
-                resource_id,
-                hex!("").to_vec(),
-                hex!("").to_vec(),
+    /// Resource id of the spent asset and the fee of depositing it, fees in tokens are only
+    /// supported if charged in the spent asset
+    fn quote(
+        &self,
+        step: &Step,
+        calculate_fee: &impl Fn([u8; 32], Vec<u8>) -> Result<SygmaFee, &'static str>,
+    ) -> Result<([u8; 32], SygmaFee), &'static str> {
+        let spend_asset = Address::from_slice(&step.spend_asset);
+        let resource_id = self.resource_id(spend_asset)?;
+        let spend_amount = U256::from(step.spend_amount.ok_or("MissingSpendAmount")?);
+        let fee = calculate_fee(
+            resource_id,
+            self.deposit_data(step.recipient.clone(), spend_amount),
+        )?;
+        pink_extension::debug!("EvmSygmaBridge: quoted fee {:?}", &fee);
+        match fee {
+            SygmaFee::Token(token, amount) if token != spend_asset => {
+                pink_extension::error!(
+                    "EvmSygmaBridge: fee {:?} charged in token {:?} is not supported",
+                    amount,
+                    token
+                );
+                Err("UnsupportedFeeToken")
+            }
+            fee => Ok((resource_id, fee)),
+        }
+    }
+
+    /// Approve the fee handler to collect the fee in `token` from the handler
+    fn build_fee_approval(&self, token: Address, fee: U256) -> Result<Call, &'static str> {
+        let erc20 = Contract::from_json(
+            self.eth.clone(),
+            token,
+            include_bytes!("../../abi/erc20.json"),
+        )
+        .map_err(|_| "BadAbi")?;
+        let approve_calldata = erc20
+            .abi()
+            .function("approve")
+            .map_err(|_| "NoFunctionFound")?
+            .encode_input(&(self.fee_handler_address, fee).into_tokens())
+            .map_err(|_| "EncodeParamError")?;
+
+        Ok(Call {
+            params: CallParams::Evm(EvmCall {
+                target: token,
+                calldata: approve_calldata,
+                value: U256::from(0),
+                need_settle: false,
+                update_offset: U256::from(0),
+                update_len: U256::from(0),
+                // No spender
+                spender: Address::from(&[0; 20]),
+                spend_asset: token,
+                spend_amount: U256::from(0),
+                receive_asset: token,
+            }),
+            input_call: None,
+            call_index: None,
+        })
+    }
+
+    fn build_deposit(
+        &self,
+        step: &Step,
+        calculate_fee: &impl Fn([u8; 32], Vec<u8>) -> Result<SygmaFee, &'static str>,
+    ) -> Result<Vec<Call>, &'static str> {
+        let (resource_id, fee) = self.quote(step, calculate_fee)?;
+        let spend_asset = Address::from_slice(&step.spend_asset);
+        let spend_amount = U256::from(step.spend_amount.ok_or("MissingSpendAmount")?);
+        // Fee in the native asset is paid as value and the whole settled amount is deposited.
+        // Fee in the spent asset is collected from the handler on top of the deposit, so the
+        // deposited amount leaves the fee in the handler and is not updated during settlement
+        let (value, deposit_amount, update_len) = match fee {
+            SygmaFee::Native(fee) => (fee, spend_amount, 32),
+            SygmaFee::Token(_, fee) => (
+                U256::from(0),
+                spend_amount
+                    .checked_sub(fee)
+                    .filter(|amount| !amount.is_zero())
+                    .ok_or("FeeExceedsAmount")?,
+                0,
             ),
-            None,
-            Options::default(),
-            None,
-        ))
-        .unwrap();
+        };
+        let deposit_data = self.deposit_data(step.recipient.clone(), deposit_amount);
 
         let mut fee_data = vec![0u8; 32];
-        fee.0.to_big_endian(&mut fee_data);
+        value.to_big_endian(&mut fee_data);
         let nonzero_index = fee_data
             .iter()
             .position(|&x| x != 0)
@@ -158,23 +299,58 @@ impl CallBuilder for EvmSygmaBridge {
             .encode_input(&bridge_params.into_tokens())
             .map_err(|_| "EncodeParamError")?;
 
-        Ok(Call {
+        let deposit = Call {
             params: CallParams::Evm(EvmCall {
                 target: self.contract.address(),
                 calldata: bridge_calldata,
-                value: U256::from(self.fee_amount),
+                value,
                 spender: self.erc20_handler_address,
                 need_settle: false,
                 update_offset: U256::from(164),
-                update_len: U256::from(32),
+                update_len: U256::from(update_len),
                 spend_asset,
-                spend_amount,
+                spend_amount: deposit_amount,
                 receive_asset: spend_asset,
             }),
             input_call: None,
             call_index: None,
+        };
+        match fee {
+            SygmaFee::Native(_) => Ok(vec![deposit]),
+            SygmaFee::Token(token, fee) => Ok(vec![self.build_fee_approval(token, fee)?, deposit]),
+        }
+    }
+}
+
+impl CallBuilder for EvmSygmaBridge {
+    fn build_call(&self, step: Step) -> Result<Call, &'static str> {
+        let mut calls = self.build_calls(step)?;
+        // Fee in tokens needs the approval call before the deposit
+        if calls.len() != 1 {
+            return Err("DepositNeedsFeeApproval");
+        }
+        Ok(calls.remove(0))
+    }
+
+    fn build_calls(&self, step: Step) -> Result<Vec<Call>, &'static str> {
+        self.build_deposit(&step, &|resource_id, deposit_data| {
+            self.calculate_fee(resource_id, deposit_data)
         })
     }
+
+    fn quote_fee(&self, step: Step) -> Result<Option<ActionFee>, &'static str> {
+        let (_, fee) = self.quote(&step, &|resource_id, deposit_data| {
+            self.calculate_fee(resource_id, deposit_data)
+        })?;
+        let (asset, amount) = match fee {
+            SygmaFee::Native(amount) => (self.native, amount),
+            SygmaFee::Token(token, amount) => (token, amount),
+        };
+        Ok(Some(ActionFee {
+            asset: asset.as_bytes().to_vec(),
+            amount: amount.try_into().map_err(|_| "FeeOverflow")?,
+        }))
+    }
 }
 
 #[cfg(test)]
@@ -182,9 +358,170 @@ mod tests {
     use crate::utils::ToArray;
 
     use super::*;
+    use core::str::FromStr;
     use pink_web3::keys::pink::KeyPair;
     use pink_web3::types::H160;
 
+    const PHA: [u8; 20] = hex!("6c5bA91642F10282b576d91922Ae6448C9d52f4E");
+
+    fn pha_bridge() -> EvmSygmaBridge {
+        EvmSygmaBridge::new(
+            "https://eth.llamarpc.com",
+            hex!("4D878E8Fb90178588Cda4cf1DCcdC9a6d2757089").into(),
+            hex!("C832588193cd5ED2185daDA4A531e0B26eC5B830").into(),
+            hex!("e43F8245249d7fAF46408723Ab36D071dD85D7BB").into(),
+            hex!("d693bDC5cb0cF2a31F08744A0Ec135a68C26FE1c").into(),
+            [0; 20].into(),
+            1,
+            3,
+            None,
+            vec![SygmaResource {
+                asset: PHA,
+                resource_id: hex!(
+                    "0000000000000000000000000000000000000000000000000000000000000001"
+                ),
+            }],
+        )
+    }
+
+    fn bridge_step() -> Step {
+        Step {
+            exe: String::from("ethereum_sygmabridge_to_phala"),
+            source_chain: String::from("Ethereum"),
+            dest_chain: String::from("Phala"),
+            spend_asset: PHA.to_vec(),
+            receive_asset: hex::decode("0000").unwrap(),
+            sender: None,
+            recipient: hex::decode(
+                "04dba0677fc274ffaccc0fa1030a66b171d1da9226d2bb9d152654e6a746f276",
+            )
+            .unwrap(),
+            // 1 PHA
+            spend_amount: Some(1_000_000_000_000_000_000 as u128),
+            origin_balance: None,
+            nonce: None,
+        }
+    }
+
+    #[test]
+    fn test_resources_and_fee() {
+        let sygma_bridge = pha_bridge();
+        assert_eq!(
+            sygma_bridge.resource_id(PHA.into()),
+            Ok(hex!(
+                "0000000000000000000000000000000000000000000000000000000000000001"
+            ))
+        );
+        assert_eq!(
+            sygma_bridge.resource_id([0; 20].into()),
+            Err("NoResourceId")
+        );
+
+        let fee = U256::from(100_000_000_000_000u128);
+        assert_eq!(SygmaFee::decode(fee, [0; 20].into()), SygmaFee::Native(fee));
+        assert_eq!(
+            SygmaFee::decode(fee, PHA.into()),
+            SygmaFee::Token(PHA.into(), fee)
+        );
+    }
+
+    #[test]
+    fn test_deposit_with_mocked_fee_handler() {
+        let sygma_bridge = pha_bridge();
+        let step = bridge_step();
+        // 0.0001 ETH
+        let native_fee = U256::from(100_000_000_000_000u128);
+
+        // Fee in native asset is paid as value of the deposit
+        let calls = sygma_bridge
+            .build_deposit(&step, &|resource_id, deposit_data| {
+                assert_eq!(resource_id[31], 1);
+                // Percentage fee handlers read the amount from the deposit data
+                assert_eq!(
+                    U256::from_big_endian(&deposit_data[..32]),
+                    U256::from(step.spend_amount.unwrap())
+                );
+                Ok(SygmaFee::Native(native_fee))
+            })
+            .unwrap();
+        assert_eq!(calls.len(), 1);
+        match &calls[0].params {
+            CallParams::Evm(evm_call) => {
+                assert_eq!(evm_call.value, native_fee);
+                assert_eq!(evm_call.spender, sygma_bridge.erc20_handler_address);
+                // The whole settled amount is deposited
+                assert_eq!(evm_call.update_offset, U256::from(164));
+                assert_eq!(evm_call.update_len, U256::from(32));
+            }
+            _ => assert!(false),
+        }
+
+        // Fee in PHA is collected by the fee handler on top of the deposit
+        let token_fee = U256::from(1_000_000_000u128);
+        let calls = sygma_bridge
+            .build_deposit(&step, &|_, _| Ok(SygmaFee::Token(PHA.into(), token_fee)))
+            .unwrap();
+        assert_eq!(calls.len(), 2);
+        match &calls[0].params {
+            CallParams::Evm(evm_call) => {
+                assert_eq!(evm_call.target, Address::from(PHA));
+                assert!(!evm_call.need_settle);
+                assert_eq!(evm_call.spender, Address::from(&[0; 20]));
+                // approve(fee_handler, fee)
+                assert_eq!(evm_call.calldata[..4], hex!("095ea7b3"));
+                assert_eq!(
+                    Address::from_slice(&evm_call.calldata[16..36]),
+                    sygma_bridge.fee_handler_address
+                );
+                assert_eq!(U256::from_big_endian(&evm_call.calldata[36..68]), token_fee);
+            }
+            _ => assert!(false),
+        }
+        match &calls[1].params {
+            CallParams::Evm(evm_call) => {
+                let deposit_amount = U256::from(step.spend_amount.unwrap()) - token_fee;
+                assert_eq!(evm_call.value, U256::from(0));
+                assert_eq!(evm_call.spender, sygma_bridge.erc20_handler_address);
+                assert_eq!(evm_call.spend_amount, deposit_amount);
+                // Fee is deducted from the deposited amount, which is not updated
+                assert_eq!(
+                    U256::from_big_endian(&evm_call.calldata[164..196]),
+                    deposit_amount
+                );
+                assert_eq!(evm_call.update_len, U256::from(0));
+            }
+            _ => assert!(false),
+        }
+
+        // Fee in another token is not held by the handler
+        assert_eq!(
+            sygma_bridge.build_deposit(&step, &|_, _| {
+                Ok(SygmaFee::Token(
+                    hex!("C02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2").into(),
+                    token_fee,
+                ))
+            }),
+            Err("UnsupportedFeeToken")
+        );
+
+        // Fee in PHA can't exceed the deposited amount
+        assert_eq!(
+            sygma_bridge.build_deposit(&step, &|_, _| {
+                Ok(SygmaFee::Token(
+                    PHA.into(),
+                    U256::from(step.spend_amount.unwrap()),
+                ))
+            }),
+            Err("FeeExceedsAmount")
+        );
+
+        // Failed fee query is returned as an error
+        assert_eq!(
+            sygma_bridge.build_deposit(&step, &|_, _| Err("FailedToCalculateFee")),
+            Err("FailedToCalculateFee")
+        );
+    }
+
     #[test]
     #[ignore]
     fn test_pha_from_goerli_to_rhala() {
@@ -207,11 +544,18 @@ mod tests {
             Address::from_str("c26335a9f16398b5fDA4bC05b62C1429D8a4d755").unwrap(),
             Address::from_str("7Ed4B14a82B2F2C4DfB13DC4Eac00205EDEff6C2").unwrap(),
             Address::from_str("e6CE0ea4eC6ECbdC23eEF9f4fB165aCc979C56b5").unwrap(),
-            // 0.001 ETH on Goerli
-            1_000_000_000_000_000u128,
+            handler_address,
+            [0; 20].into(),
             1,
             3,
             None,
+            vec![SygmaResource {
+                // Goerli GPHA
+                asset: hex!("B376b0Ee6d8202721838e76376e81eEc0e2FE864"),
+                resource_id: hex!(
+                    "0000000000000000000000000000000000000000000000000000000000001000"
+                ),
+            }],
         );
 
         let mut call = sygma_bridge
@@ -0,0 +1,120 @@
+use pink_extension::ResultExt;
+use scale::{Decode, Encode};
+use xcm::v3::prelude::*;
+
+use crate::assets::AssetResolver;
+use crate::call::{Call, CallBuilder, CallParams, SubCall, SubExtrinsic};
+use crate::rpc::{RpcEndpoint, RpcHealth};
+use crate::step::Step;
+use alloc::{format, vec::Vec};
+
+/// Pallet index of Omnipool on HydraDX
+const OMNIPOOL_PALLET_ID: u8 = 0x3b;
+/// Call index of `Omnipool::sell`
+const SELL_CALL_ID: u8 = 0x05;
+
+/// Sell spend asset for receive asset in Omnipool of HydraDX.
+///
+/// All assets of Omnipool are paired with the hub asset, so any two of them can be traded
+/// with a single `sell` call
+#[derive(Clone)]
+pub struct HydraDxSwap {
+    assets: AssetResolver,
+}
+
+impl HydraDxSwap {
+    pub fn new(endpoints: Vec<RpcEndpoint>, health: &RpcHealth) -> Self
+    where
+        Self: Sized,
+    {
+        Self {
+            assets: AssetResolver::with_endpoints(endpoints, health),
+        }
+    }
+
+    fn asset_id(&self, chain: &str, asset: &[u8]) -> Result<u32, &'static str> {
+        let location = MultiLocation::decode(&mut &asset[..])
+            .log_err(&format!(
+                "HydraDxSwap: FailedToScaleDecode, asset: {:?}",
+                asset
+            ))
+            .map_err(|_| "FailedToScaleDecode")?;
+        let asset_id = self
+            .assets
+            .lookup_assetid(chain, &location)?
+            .ok_or("AssetNotRecognized")?;
+        u32::decode(&mut asset_id.as_slice()).map_err(|_| "InvalidAssetId")
+    }
+}
+
+impl CallBuilder for HydraDxSwap {
+    fn build_call(&self, step: Step) -> Result<Call, &'static str> {
+        let asset_in = self.asset_id(&step.source_chain, &step.spend_asset)?;
+        let asset_out = self.asset_id(&step.source_chain, &step.receive_asset)?;
+        if asset_in == asset_out {
+            return Err("InvalidTradingPair");
+        }
+        let amount: u128 = step.spend_amount.ok_or("MissingSpendAmount")?;
+        let min_buy_amount: u128 = 1;
+
+        Ok(Call {
+            params: CallParams::Sub(SubCall {
+                calldata: SubExtrinsic {
+                    pallet_id: OMNIPOOL_PALLET_ID,
+                    call_id: SELL_CALL_ID,
+                    call: (asset_in, asset_out, amount, min_buy_amount),
+                }
+                .encode(),
+            }),
+            input_call: None,
+            call_index: None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[ignore]
+    fn test_sell_hdx_for_dot() {
+        pink_extension_runtime::mock_ext::mock_all_ext();
+
+        let swap = HydraDxSwap::new(
+            vec![RpcEndpoint::new("https://rpc.hydradx.cloud", 0)],
+            &RpcHealth::default(),
+        );
+        let call = swap
+            .build_call(Step {
+                exe: String::from(""),
+                source_chain: String::from("HydraDX"),
+                dest_chain: String::from("HydraDX"),
+                // HDX
+                spend_asset: hex::decode("0000").unwrap(),
+                // DOT
+                receive_asset: hex::decode("0100").unwrap(),
+                sender: None,
+                recipient: hex::decode(
+                    "04dba0677fc274ffaccc0fa1030a66b171d1da9226d2bb9d152654e6a746f276",
+                )
+                .unwrap(),
+                // 100 HDX
+                spend_amount: Some(100_000_000_000_000 as u128),
+                origin_balance: None,
+                nonce: None,
+            })
+            .unwrap();
+
+        match &call.params {
+            CallParams::Sub(sub_call) => {
+                // DOT is asset 5 on HydraDX
+                assert_eq!(
+                    sub_call.calldata[..10],
+                    [0x3b, 0x05, 0, 0, 0, 0, 5, 0, 0, 0]
+                );
+            }
+            _ => assert!(false),
+        }
+    }
+}
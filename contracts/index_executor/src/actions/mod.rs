@@ -1,8 +1,19 @@
+use crate::account::AccountType;
+use crate::actions::base::xcm::{XcmConfig, XcmDestWeights};
+use crate::call::CallBuilder;
+use crate::chain::Chain;
+use crate::rpc::RpcHealth;
+use crate::utils::{serde_hex, serde_hex_list};
+use alloc::{boxed::Box, string::String, vec::Vec};
+use pink_web3::ethabi::Address;
+use serde::{Deserialize, Serialize};
 use sp_runtime::Permill;
 pub mod acala;
+pub mod assethub;
 pub mod astar;
 pub mod base;
 pub mod ethereum;
+pub mod hydradx;
 pub mod moonbeam;
 pub mod phala;
 pub mod polkadot;
@@ -24,3 +35,465 @@ pub struct ActionExtraInfo {
     pub percentage_proto_fee: Permill,
     pub confirm_time_in_sec: u16,
 }
+
+/// Kind of an action and the parameters needed to build its calls.
+///
+/// Actions are described as registry data, `create_action` builds the `CallBuilder` from it.
+/// XCM transfers carry the fee asset of the transfer in `xcm`, the dest weight is read from
+/// the entry of the destination chain.
+#[derive(Clone, Debug, scale::Decode, scale::Encode, Eq, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+pub enum ActionKind {
+    /// Swap through a UniswapV2 compatible router
+    UniswapV2 {
+        #[serde(with = "serde_hex")]
+        router: [u8; 20],
+    },
+    /// Swap through a router of Algebra V3 forks, e.g. StellaSwap
+    AlgebraV3 {
+        #[serde(with = "serde_hex")]
+        router: [u8; 20],
+    },
+    /// Wrap native asset to its ERC20 representation and vice versa
+    NativeWrapper {
+        #[serde(with = "serde_hex")]
+        weth: [u8; 20],
+        #[serde(with = "serde_hex")]
+        native: [u8; 20],
+    },
+    /// Deposit to Sygma bridge contract on EVM chains, from domain `from_domain` to `to_domain`.
+    /// Assets that can be deposited are listed in `resources`, fee is quoted from `fee_handler`
+    SygmaBridge {
+        #[serde(with = "serde_hex")]
+        bridge: [u8; 20],
+        #[serde(with = "serde_hex")]
+        erc20_handler: [u8; 20],
+        #[serde(with = "serde_hex")]
+        fee_handler: [u8; 20],
+        from_domain: u8,
+        to_domain: u8,
+        // Parachain the recipient is on if the destination is a relay chain
+        dest_parachain: Option<u32>,
+        resources: Vec<ethereum::sygma::SygmaResource>,
+    },
+    /// Transfer through Sygma with pallet XTransfer of Phala and Khala
+    XTransferSygma {
+        dest_domain: u8,
+    },
+    /// XCM transfer with pallet XTransfer of Phala and Khala, to the relay chain if `dest_parachain` is None
+    XTransferXcm {
+        dest_parachain: Option<u32>,
+        account_type: AccountType,
+        #[serde(default)]
+        xcm: XcmConfig,
+    },
+    /// XCM transfer through the xtokens precompile of Moonbeam, to the relay chain if `dest_parachain` is None
+    XTokenBridge {
+        #[serde(with = "serde_hex")]
+        xtoken: [u8; 20],
+        dest_parachain: Option<u32>,
+        #[serde(default)]
+        xcm: XcmConfig,
+    },
+    /// XCM transfer with pallet xtokens of Astar
+    AstarXtokens {
+        dest_parachain: u32,
+        #[serde(default)]
+        xcm: XcmConfig,
+    },
+    /// XCM transfer from relay chain with pallet xcm, in XCM v2
+    PolkadotXcmV2 {
+        dest_parachain: u32,
+        account_type: AccountType,
+        #[serde(default)]
+        xcm: XcmConfig,
+    },
+    /// XCM transfer from relay chain with pallet xcm, in XCM v3
+    PolkadotXcmV3 {
+        dest_parachain: u32,
+        account_type: AccountType,
+        // Recipient is an EVM account on the destination
+        is_evm: bool,
+        #[serde(default)]
+        xcm: XcmConfig,
+    },
+    /// Transfer native asset with the given pallet and call index
+    SubTransactor {
+        pallet_id: u8,
+        call_index: u8,
+    },
+    /// Transfer assets between accounts of Astar native and Astar EVM
+    AstarTransactor,
+    AstarSubToEvm,
+    /// Swap through the aggregated dex of Acala or Karura, routed through `hub_token` and the
+    /// Taiga `stable_pool` of the chain
+    AcalaSwap {
+        hub_token: acala::asset::TokenSymbol,
+        stable_pool: Option<acala::dex::TaigaPool>,
+    },
+    AcalaTransactor,
+    /// XCM transfer with pallet xtokens at `pallet_id`, e.g. on Karura, to the relay chain
+    /// if `dest_parachain` is None
+    OrmlXtokens {
+        pallet_id: u8,
+        dest_parachain: Option<u32>,
+        #[serde(default)]
+        xcm: XcmConfig,
+    },
+    /// Reserve transfer assets of AssetHub to parachains
+    AssetHubXcm {
+        dest_parachain: u32,
+        account_type: AccountType,
+        #[serde(default)]
+        xcm: XcmConfig,
+    },
+    /// Swap through Omnipool of HydraDX
+    HydraDxSwap,
+    /// Swap through the canonical Uniswap V3 router, pools are looked up with the factory and
+    /// quoted with the quoter. Multi-hop swaps go through `weth`
+    UniswapV3 {
+        #[serde(with = "serde_hex")]
+        router: [u8; 20],
+        #[serde(with = "serde_hex")]
+        factory: [u8; 20],
+        #[serde(with = "serde_hex")]
+        quoter: [u8; 20],
+        #[serde(with = "serde_hex")]
+        weth: [u8; 20],
+    },
+    /// Swap through a UniswapV2 compatible router, along the path with the best output among
+    /// the direct one and the ones through `intermediates`
+    UniswapV2Multihop {
+        #[serde(with = "serde_hex")]
+        router: [u8; 20],
+        #[serde(with = "serde_hex_list")]
+        intermediates: Vec<[u8; 20]>,
+        // Tokens that charge fee on transfer, swaps with one of them on the path use the
+        // `SupportingFeeOnTransferTokens` variants
+        #[serde(default, with = "serde_hex_list")]
+        fee_on_transfer_tokens: Vec<[u8; 20]>,
+    },
+    /// Swap with calldata requested from an aggregator API at `api_url`, only calls to
+    /// contracts in `allowlist` are accepted
+    Aggregator {
+        api: ethereum::aggregator::AggregatorApi,
+        api_url: String,
+        #[serde(with = "serde_hex_list")]
+        allowlist: Vec<[u8; 20]>,
+        // Key of the API, it can be imported but is never exported
+        #[serde(default, skip_serializing)]
+        api_key: Option<String>,
+    },
+    /// Swap through a Curve StableSwap pool, the underlying coins of the pool are swapped
+    /// if `underlying` is true
+    CurveStableSwap {
+        #[serde(with = "serde_hex")]
+        pool: [u8; 20],
+        underlying: bool,
+    },
+    /// Transfer an ERC20 token or the native asset to the recipient on EVM chains
+    EvmTransactor,
+}
+
+impl ActionKind {
+    /// The kind without secrets like API keys, for the registry returned to callers
+    pub fn redacted(&self) -> Self {
+        match self {
+            ActionKind::Aggregator {
+                api,
+                api_url,
+                allowlist,
+                ..
+            } => ActionKind::Aggregator {
+                api: api.clone(),
+                api_url: api_url.clone(),
+                allowlist: allowlist.clone(),
+                api_key: None,
+            },
+            kind => kind.clone(),
+        }
+    }
+
+    /// Take the API key of `existing` if the kind has none and calls the same API, keys are
+    /// never exported so they are missing from an exported registry imported back
+    pub fn keep_secrets(&mut self, existing: &ActionKind) {
+        if let (
+            ActionKind::Aggregator {
+                api,
+                api_key: api_key @ None,
+                ..
+            },
+            ActionKind::Aggregator {
+                api: existing_api,
+                api_key: existing_key,
+                ..
+            },
+        ) = (self, existing)
+        {
+            if *api == *existing_api {
+                *api_key = existing_key.clone();
+            }
+        }
+    }
+
+    /// Return true if the action sends transactions to EVM chains
+    pub fn is_evm(&self) -> bool {
+        matches!(
+            self,
+            ActionKind::UniswapV2 { .. }
+                | ActionKind::UniswapV2Multihop { .. }
+                | ActionKind::Aggregator { .. }
+                | ActionKind::CurveStableSwap { .. }
+                | ActionKind::AlgebraV3 { .. }
+                | ActionKind::UniswapV3 { .. }
+                | ActionKind::NativeWrapper { .. }
+                | ActionKind::EvmTransactor
+                | ActionKind::SygmaBridge { .. }
+                | ActionKind::XTokenBridge { .. }
+        )
+    }
+}
+
+/// Build the `CallBuilder` of an action on `chain`, endpoints are handed to the builder in
+/// the order ranked by `health`.
+///
+/// Builders that query the chain fail over between all the endpoints, the ones that only
+/// encode calldata (AlgebraV3, NativeWrapper, EvmTransactor, XTokenBridge) take the first.
+/// XCM transfers limit the weight on the destination with `dest_weights`
+pub fn create_action(
+    chain: &Chain,
+    kind: &ActionKind,
+    health: &RpcHealth,
+    dest_weights: &XcmDestWeights,
+) -> Result<Box<dyn CallBuilder>, &'static str> {
+    let endpoints = chain.ranked_endpoints(health);
+    let endpoint = endpoints
+        .first()
+        .map(|endpoint| endpoint.url.as_str())
+        .unwrap_or_default();
+    let action: Box<dyn CallBuilder> = match kind {
+        ActionKind::UniswapV2 { router } => Box::new(
+            base::uniswapv2::UniswapV2::new(
+                endpoint,
+                (*router).into(),
+                evm_address(&chain.native_asset)?,
+            )
+            .with_endpoints(endpoints.clone(), health),
+        ),
+        ActionKind::UniswapV2Multihop {
+            router,
+            intermediates,
+            fee_on_transfer_tokens,
+        } => Box::new(
+            base::uniswapv2::UniswapV2::new(
+                endpoint,
+                (*router).into(),
+                evm_address(&chain.native_asset)?,
+            )
+            .with_endpoints(endpoints.clone(), health)
+            .with_intermediates(intermediates.iter().map(|token| (*token).into()).collect())
+            .with_fee_on_transfer_tokens(
+                fee_on_transfer_tokens
+                    .iter()
+                    .map(|token| (*token).into())
+                    .collect(),
+            ),
+        ),
+        ActionKind::AlgebraV3 { router } => {
+            Box::new(base::algebrav3::AlgebraV3::new(endpoint, (*router).into()))
+        }
+        ActionKind::NativeWrapper { weth, native } => {
+            Box::new(base::native_wrapper::NativeWrapper::new(
+                endpoint,
+                (*weth).into(),
+                (*native).into(),
+                evm_address(&chain.handler_contract)?,
+            ))
+        }
+        ActionKind::SygmaBridge {
+            bridge,
+            erc20_handler,
+            fee_handler,
+            from_domain,
+            to_domain,
+            dest_parachain,
+            resources,
+        } => Box::new(
+            ethereum::sygma::EvmSygmaBridge::new(
+                endpoint,
+                (*bridge).into(),
+                (*erc20_handler).into(),
+                (*fee_handler).into(),
+                evm_address(&chain.handler_contract)?,
+                evm_address(&chain.native_asset)?,
+                *from_domain,
+                *to_domain,
+                *dest_parachain,
+                resources.clone(),
+            )
+            .with_endpoints(endpoints.clone(), health),
+        ),
+        ActionKind::XTransferSygma { dest_domain } => {
+            Box::new(phala::sygma::XTransferSygma::new(*dest_domain))
+        }
+        ActionKind::XTransferXcm {
+            dest_parachain,
+            account_type,
+            xcm,
+        } => Box::new(
+            phala::xtransfer::XTransferXcm::new(
+                match dest_parachain {
+                    Some(id) => phala::xtransfer::XTransferDestChain::ParaChain(*id),
+                    None => phala::xtransfer::XTransferDestChain::RelayChain,
+                },
+                account_type.clone(),
+            )
+            .with_xcm_config(xcm.clone())
+            .with_dest_weights(dest_weights.clone()),
+        ),
+        ActionKind::XTokenBridge {
+            xtoken,
+            dest_parachain,
+            xcm,
+        } => Box::new(
+            moonbeam::xtoken::XTokenBridge::new(
+                endpoint,
+                (*xtoken).into(),
+                match dest_parachain {
+                    Some(id) => moonbeam::xtoken::XTokenDestChain::Parachain(*id),
+                    None => moonbeam::xtoken::XTokenDestChain::Relaychain,
+                },
+            )
+            .with_xcm_config(xcm.clone())
+            .with_dest_weights(dest_weights.clone()),
+        ),
+        ActionKind::AstarXtokens {
+            dest_parachain,
+            xcm,
+        } => Box::new(
+            astar::xtokens::AstarXtokens::new(*dest_parachain)
+                .with_xcm_config(xcm.clone())
+                .with_dest_weights(dest_weights.clone()),
+        ),
+        ActionKind::PolkadotXcmV2 {
+            dest_parachain,
+            account_type,
+            xcm,
+        } => Box::new(
+            polkadot::xcm_v2::PolkadotXcm::new(*dest_parachain, account_type.clone())
+                .with_xcm_config(xcm.clone())
+                .with_dest_weights(dest_weights.clone()),
+        ),
+        ActionKind::PolkadotXcmV3 {
+            dest_parachain,
+            account_type,
+            is_evm,
+            xcm,
+        } => Box::new(
+            polkadot::xcm_v3::PolkadotXcm::new(*dest_parachain, account_type.clone(), *is_evm)
+                .with_xcm_config(xcm.clone())
+                .with_dest_weights(dest_weights.clone()),
+        ),
+        ActionKind::SubTransactor {
+            pallet_id,
+            call_index,
+        } => Box::new(base::sub_transactor::Transactor::new(
+            *pallet_id,
+            *call_index,
+        )),
+        ActionKind::AstarTransactor => Box::new(astar::sub::AstarTransactor::new(
+            endpoints.clone(),
+            health,
+            chain.native_asset.clone(),
+        )),
+        ActionKind::AstarSubToEvm => Box::new(astar::sub::AstarSubToEvmTransactor::new(
+            endpoints.clone(),
+            health,
+            chain.native_asset.clone(),
+        )),
+        ActionKind::AcalaSwap {
+            hub_token,
+            stable_pool,
+        } => Box::new(acala::dex::AcalaSwap::new(
+            endpoints.clone(),
+            health,
+            *hub_token,
+            *stable_pool,
+        )),
+        ActionKind::AcalaTransactor => Box::new(acala::transfer::AcalaTransactor::new()),
+        ActionKind::OrmlXtokens {
+            pallet_id,
+            dest_parachain,
+            xcm,
+        } => Box::new(
+            astar::xtokens::AstarXtokens::with_pallet_id(*pallet_id, *dest_parachain)
+                .with_xcm_config(xcm.clone())
+                .with_dest_weights(dest_weights.clone()),
+        ),
+        ActionKind::AssetHubXcm {
+            dest_parachain,
+            account_type,
+            xcm,
+        } => Box::new(
+            assethub::xcm::AssetHubXcm::new(*dest_parachain, account_type.clone())
+                .with_xcm_config(xcm.clone())
+                .with_dest_weights(dest_weights.clone()),
+        ),
+        ActionKind::HydraDxSwap => Box::new(hydradx::omnipool::HydraDxSwap::new(
+            endpoints.clone(),
+            health,
+        )),
+        ActionKind::UniswapV3 {
+            router,
+            factory,
+            quoter,
+            weth,
+        } => Box::new(
+            base::uniswapv3::UniswapV3::new(
+                endpoint,
+                (*router).into(),
+                (*factory).into(),
+                (*quoter).into(),
+                (*weth).into(),
+            )
+            .with_endpoints(endpoints.clone(), health),
+        ),
+        ActionKind::Aggregator {
+            api,
+            api_url,
+            allowlist,
+            api_key,
+        } => Box::new(
+            ethereum::aggregator::AggregatorSwap::new(
+                api.clone(),
+                api_url,
+                allowlist.iter().map(|target| (*target).into()).collect(),
+                evm_address(&chain.native_asset)?,
+                evm_address(&chain.handler_contract)?,
+            )
+            .with_api_key(api_key.clone()),
+        ),
+        ActionKind::CurveStableSwap { pool, underlying } => Box::new(
+            ethereum::curve::CurveStableSwap::new(
+                endpoint,
+                (*pool).into(),
+                *underlying,
+                evm_address(&chain.handler_contract)?,
+            )
+            .with_endpoints(endpoints.clone(), health),
+        ),
+        ActionKind::EvmTransactor => Box::new(base::evm_transactor::EvmTransactor::new(
+            endpoint,
+            evm_address(&chain.native_asset)?,
+        )),
+    };
+    Ok(action)
+}
+
+/// Address of an EVM chain field, which is 20 bytes long on a validated chain
+fn evm_address(bytes: &[u8]) -> Result<Address, &'static str> {
+    if bytes.len() != 20 {
+        return Err("InvalidChain");
+    }
+    Ok(Address::from_slice(bytes))
+}
@@ -7,7 +7,12 @@ use pink_web3::{
     types::U256,
 };
 
+use xcm::v3::prelude::*;
+
+use crate::account::AccountType;
+use crate::actions::base::xcm::{account_junction, XcmConfig, XcmDestWeights};
 use crate::call::{Call, CallBuilder, CallParams, EvmCall};
+use crate::constants::XCM_DEFAULT_DEST_WEIGHT;
 use crate::step::Step;
 
 #[derive(Clone)]
@@ -21,6 +26,8 @@ pub struct XTokenBridge {
     _eth: Eth<PinkHttp>,
     xtoken: Contract<PinkHttp>,
     dest_chain: XTokenDestChain,
+    config: XcmConfig,
+    dest_weights: XcmDestWeights,
 }
 
 impl XTokenBridge {
@@ -37,76 +44,144 @@ impl XTokenBridge {
             _eth: eth,
             xtoken,
             dest_chain,
+            config: XcmConfig::default(),
+            dest_weights: XcmDestWeights::default(),
+        }
+    }
+
+    /// Fee asset of the transfer, the fee asset is an ERC20 address
+    pub fn with_xcm_config(mut self, config: XcmConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Weight limits of the destination chains, the limit of the step's destination is applied
+    pub fn with_dest_weights(mut self, dest_weights: XcmDestWeights) -> Self {
+        self.dest_weights = dest_weights;
+        self
+    }
+
+    /// Weight the precompile requires for the XCM executed on the destination
+    fn dest_weight(&self, dest_chain: &str) -> u64 {
+        match self.dest_weights.weight_limit(dest_chain) {
+            WeightLimit::Limited(weight) => weight.ref_time(),
+            WeightLimit::Unlimited => XCM_DEFAULT_DEST_WEIGHT.ref_time(),
+        }
+    }
+
+    /// Name of the precompile function, its params and the offset of the spent amount in them.
+    ///
+    /// Fee is paid with `transferWithFee` if it is in the transferred asset, or with
+    /// `transferMultiCurrencies` if it is in another asset held by the handler
+    fn transfer_params(
+        &self,
+        spend_asset: Address,
+        spend_amount: U256,
+        location: Token,
+        dest_chain: &str,
+    ) -> Result<(&'static str, Vec<Token>, u64), &'static str> {
+        let weight = self.dest_weight(dest_chain);
+        match &self.config.fee {
+            None => Ok((
+                "transfer",
+                (spend_asset, spend_amount, location, weight).into_tokens(),
+                36,
+            )),
+            Some(fee) => {
+                if fee.asset.len() != 20 {
+                    return Err("InvalidFeeAsset");
+                }
+                let fee_asset = Address::from_slice(&fee.asset);
+                let fee_amount = U256::from(fee.amount);
+                if fee_asset == spend_asset {
+                    Ok((
+                        "transferWithFee",
+                        (spend_asset, spend_amount, fee_amount, location, weight).into_tokens(),
+                        36,
+                    ))
+                } else {
+                    let currencies = Token::Array(vec![
+                        Token::Tuple((spend_asset, spend_amount).into_tokens()),
+                        Token::Tuple((fee_asset, fee_amount).into_tokens()),
+                    ]);
+                    // Amount of the first currency follows the 4 head words, the length of
+                    // the currencies and the address of the first currency
+                    Ok((
+                        "transferMultiCurrencies",
+                        vec![
+                            currencies,
+                            Token::Uint(1.into()),
+                            location,
+                            Token::Uint(weight.into()),
+                        ],
+                        196,
+                    ))
+                }
+            }
         }
     }
 }
 
+/// Encode a MultiLocation in the layout the xtokens precompile expects:
+/// `(parents, [junction bytes])`, where each junction is its enum index followed
+/// by its fields, e.g. `0x00 ++ u32 BE` for `Parachain`
+fn precompile_location(location: &MultiLocation) -> Result<Token, &'static str> {
+    let mut junctions: Vec<Token> = vec![];
+    for junction in location.interior.iter() {
+        let mut bytes: Vec<u8> = vec![];
+        match junction {
+            Parachain(parachain_id) => {
+                bytes.push(0);
+                bytes.extend_from_slice(&parachain_id.to_be_bytes());
+            }
+            AccountId32 { id, .. } => {
+                bytes.push(1);
+                bytes.extend_from_slice(id);
+                // NetworkId: Any
+                bytes.push(0);
+            }
+            AccountKey20 { key, .. } => {
+                bytes.push(3);
+                bytes.extend_from_slice(key);
+                // NetworkId: Any
+                bytes.push(0);
+            }
+            _ => return Err("UnsupportedJunction"),
+        }
+        junctions.push(Token::Bytes(bytes));
+    }
+    Ok(Token::Tuple(vec![
+        Token::Uint(location.parents.into()),
+        Token::Array(junctions),
+    ]))
+}
+
 impl CallBuilder for XTokenBridge {
     fn build_call(&self, step: Step) -> Result<Call, &'static str> {
         let spend_asset = Address::from_slice(&step.spend_asset);
         // We don't use it
         let receive_asset = Address::from_slice(&[0; 20]);
-        let mut recipient = step.recipient;
         let spend_amount = U256::from(step.spend_amount.ok_or("MissingSpendAmount")?);
 
-        let weight: u64 = 6000000000;
-        let location = match self.dest_chain {
-            XTokenDestChain::Relaychain => {
-                Token::Tuple(vec![
-                    Token::Uint(1_u8.into()),
-                    Token::Array(vec![Token::Bytes(
-                        // AccountId32 { network: NetworkId, id: [u8; 32] },
-                        {
-                            let mut bytes: Vec<u8> = vec![];
-                            let mut enum_id = 1_u8.to_be_bytes().to_vec();
-                            let mut network_vec = 0_u8.to_be_bytes().to_vec();
-                            bytes.append(&mut enum_id);
-                            bytes.append(&mut recipient);
-                            bytes.append(&mut network_vec);
-                            bytes
-                        },
-                    )]),
-                ])
-            }
+        let dest = match self.dest_chain {
+            XTokenDestChain::Relaychain => MultiLocation::parent(),
             XTokenDestChain::Parachain(parachain_id) => {
-                Token::Tuple(vec![
-                    Token::Uint(1_u8.into()),
-                    Token::Array(vec![
-                        Token::Bytes(
-                            // Parachain(#[codec(compact)] u32),
-                            {
-                                let mut bytes: Vec<u8> = vec![];
-                                let mut enum_id = 0_u8.to_be_bytes().to_vec();
-                                let mut chain_id = parachain_id.to_be_bytes().to_vec();
-                                bytes.append(&mut enum_id);
-                                bytes.append(&mut chain_id);
-                                bytes
-                            },
-                        ),
-                        Token::Bytes(
-                            // AccountId32 { network: NetworkId, id: [u8; 32] },
-                            {
-                                let mut bytes: Vec<u8> = vec![];
-                                let mut enum_id = 1_u8.to_be_bytes().to_vec();
-                                let mut network_vec = 0_u8.to_be_bytes().to_vec();
-                                bytes.append(&mut enum_id);
-                                bytes.append(&mut recipient);
-                                bytes.append(&mut network_vec);
-                                bytes
-                            },
-                        ),
-                    ]),
-                ])
+                MultiLocation::new(1, X1(Parachain(parachain_id)))
             }
         };
-        let bridge_params = (spend_asset, spend_amount, location, weight);
+        let beneficiary = dest
+            .pushed_with_interior(account_junction(&step.recipient, &AccountType::Account32)?)
+            .map_err(|_| "InvalidRecipient")?;
+        let location = precompile_location(&beneficiary)?;
+        let (func, bridge_params, update_offset) =
+            self.transfer_params(spend_asset, spend_amount, location, &step.dest_chain)?;
         let bridge_func = self
             .xtoken
             .abi()
-            .function("transfer")
+            .function(func)
             .map_err(|_| "NoFunctionFound")?;
         let bridge_calldata = bridge_func
-            .encode_input(&bridge_params.into_tokens())
+            .encode_input(&bridge_params)
             .map_err(|_| "EncodeParamError")?;
 
         Ok(Call {
@@ -118,7 +193,7 @@ impl CallBuilder for XTokenBridge {
                 // Bridge operation do not need do settlement on source chain, because it must be the
                 // last step on source chain
                 need_settle: false,
-                update_offset: U256::from(36),
+                update_offset: U256::from(update_offset),
                 update_len: U256::from(32),
                 spender: self.xtoken.address(),
                 spend_asset,
@@ -141,6 +216,90 @@ mod tests {
     use pink_web3::transports::{resolve_ready, PinkHttp};
     use pink_web3::types::H160;
 
+    #[test]
+    fn test_precompile_location() {
+        let location = MultiLocation::new(
+            1,
+            X2(
+                Parachain(2035),
+                AccountId32 {
+                    network: None,
+                    id: [1; 32],
+                },
+            ),
+        );
+        let mut account = vec![1];
+        account.extend_from_slice(&[1; 32]);
+        account.push(0);
+        assert_eq!(
+            precompile_location(&location).unwrap(),
+            Token::Tuple(vec![
+                Token::Uint(1_u8.into()),
+                Token::Array(vec![
+                    Token::Bytes(hex::decode("00000007f3").unwrap()),
+                    Token::Bytes(account),
+                ]),
+            ])
+        );
+        assert_eq!(
+            precompile_location(&MultiLocation::new(1, X1(PalletInstance(10)))),
+            Err("UnsupportedJunction")
+        );
+    }
+
+    #[test]
+    fn test_transfer_with_fee_asset() {
+        use crate::actions::base::xcm::XcmFee;
+
+        // xcDOT, paying the fee in xcUSDT
+        let dot: Address = hex_literal::hex!("FfFFfFff1FcaCBd218EDc0EbA20Fc2308C778080").into();
+        let usdt: [u8; 20] = hex_literal::hex!("FFFFFFfFea09FB06d082fd1275CD48b191cbCD1d");
+        let xtoken = XTokenBridge::new(
+            "https://rpc.api.moonbeam.network",
+            hex_literal::hex!("0000000000000000000000000000000000000804").into(),
+            XTokenDestChain::Parachain(2035),
+        );
+        let location = precompile_location(&MultiLocation::parent()).unwrap();
+
+        let (func, params, offset) = xtoken
+            .transfer_params(dot, U256::from(100), location.clone(), "Phala")
+            .unwrap();
+        assert_eq!((func, offset), ("transfer", 36));
+        // Not limited without the weight of the destination chain
+        assert_eq!(
+            params[3],
+            Token::Uint(XCM_DEFAULT_DEST_WEIGHT.ref_time().into())
+        );
+
+        let xtoken = xtoken
+            .with_xcm_config(XcmConfig {
+                fee: Some(XcmFee {
+                    asset: usdt.to_vec(),
+                    amount: 1_000_000,
+                }),
+            })
+            .with_dest_weights(XcmDestWeights::new(
+                &crate::registry::Registry::new().chains,
+            ));
+        let (func, params, offset) = xtoken
+            .transfer_params(dot, U256::from(100), location, "Phala")
+            .unwrap();
+        assert_eq!((func, offset), ("transferMultiCurrencies", 196));
+        let calldata = xtoken
+            .xtoken
+            .abi()
+            .function(func)
+            .unwrap()
+            .encode_input(&params)
+            .unwrap();
+        assert_eq!(
+            U256::from_big_endian(&calldata[offset as usize..offset as usize + 32]),
+            U256::from(100)
+        );
+        assert_eq!(params[1], Token::Uint(1.into()));
+        assert_eq!(params[3], Token::Uint(6_000_000_000u64.into()));
+    }
+
     #[test]
     #[ignore]
     fn test_transfer_dot_to_polkadot() {
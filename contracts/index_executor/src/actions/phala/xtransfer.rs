@@ -1,13 +1,18 @@
-use scale::{Decode, Encode};
+use scale::Decode;
 
-use crate::call::{Call, CallBuilder, CallParams, SubCall, SubExtrinsic};
+use crate::actions::base::xcm::{
+    account_junction, XcmConfig, XcmDestWeights, XcmPallet, XcmTransfer,
+};
+use crate::call::{Call, CallBuilder};
 use crate::step::Step;
 
-use crate::utils::ToArray;
-use xcm::v3::{prelude::*, AssetId, Fungibility, Junctions, MultiAsset, MultiLocation, Weight};
+use xcm::v3::prelude::*;
 
 use crate::account::AccountType;
 
+/// Pallet index of xtransfer on Phala and Khala
+const XTRANSFER_PALLET_ID: u8 = 0x52;
+
 #[derive(Clone)]
 pub enum XTransferDestChain {
     RelayChain,
@@ -19,6 +24,8 @@ pub struct XTransferXcm {
     dest_chain_id: XTransferDestChain,
     // dest chain account type
     account_type: AccountType,
+    config: XcmConfig,
+    dest_weights: XcmDestWeights,
 }
 
 impl XTransferXcm {
@@ -29,55 +36,49 @@ impl XTransferXcm {
         Self {
             dest_chain_id,
             account_type,
+            config: XcmConfig::default(),
+            dest_weights: XcmDestWeights::default(),
         }
     }
+
+    /// Fee asset of the transfer
+    pub fn with_xcm_config(mut self, config: XcmConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Weight limits of the destination chains, the limit of the step's destination is applied
+    pub fn with_dest_weights(mut self, dest_weights: XcmDestWeights) -> Self {
+        self.dest_weights = dest_weights;
+        self
+    }
 }
 
 impl CallBuilder for XTransferXcm {
     fn build_call(&self, step: Step) -> Result<Call, &'static str> {
-        let recipient = step.recipient;
         let asset_location: MultiLocation =
             Decode::decode(&mut step.spend_asset.as_slice()).map_err(|_| "InvalidMultilocation")?;
         let multi_asset = MultiAsset {
             id: AssetId::Concrete(asset_location),
             fun: Fungibility::Fungible(step.spend_amount.ok_or("MissingSpendAmount")?),
         };
-        let account = match &self.account_type {
-            AccountType::Account20 => {
-                let recipient: [u8; 20] = recipient.to_array();
-                AccountKey20 {
-                    network: None,
-                    key: recipient,
-                }
-            }
-            AccountType::Account32 => {
-                let recipient: [u8; 32] = recipient.to_array();
-                AccountId32 {
-                    network: None,
-                    id: recipient,
-                }
-            }
-        };
         let dest = MultiLocation::new(
             1,
             match &self.dest_chain_id {
-                XTransferDestChain::RelayChain => Junctions::X1(account),
-                XTransferDestChain::ParaChain(id) => Junctions::X2(Parachain(*id), account),
+                XTransferDestChain::RelayChain => Here,
+                XTransferDestChain::ParaChain(id) => X1(Parachain(*id)),
             },
         );
-        let dest_weight: Weight = Weight::from_parts(6000000000_u64, 1000000_u64);
 
-        Ok(Call {
-            params: CallParams::Sub(SubCall {
-                calldata: SubExtrinsic {
-                    pallet_id: 0x52u8,
-                    call_id: 0x0u8,
-                    call: (multi_asset, dest, Some(dest_weight)),
-                }
-                .encode(),
-            }),
-            input_call: None,
-            call_index: None,
+        XcmTransfer::new(
+            dest,
+            account_junction(&step.recipient, &self.account_type)?,
+            multi_asset,
+        )
+        .with_weight_limit(self.dest_weights.weight_limit(&step.dest_chain))
+        .with_config(&self.config)?
+        .build(XcmPallet::XTransfer {
+            pallet_id: XTRANSFER_PALLET_ID,
         })
     }
 }
@@ -85,6 +86,7 @@ impl CallBuilder for XTransferXcm {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::call::CallParams;
     use crate::constants::ASTAR_PARACHAIN_ID;
 
     #[test]
@@ -93,6 +95,8 @@ mod tests {
             dest_chain_id: XTransferDestChain::ParaChain(ASTAR_PARACHAIN_ID),
             // dest chain account type
             account_type: AccountType::Account32,
+            config: XcmConfig::default(),
+            dest_weights: XcmDestWeights::default(),
         };
         let call = xtransfer
             .build_call(Step {
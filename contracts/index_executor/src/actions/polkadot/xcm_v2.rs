@@ -1,15 +1,22 @@
 use crate::account::AccountType;
-use crate::call::{Call, CallBuilder, CallParams, SubCall, SubExtrinsic};
+use crate::actions::base::xcm::{
+    account_junction, XcmConfig, XcmDestWeights, XcmPallet, XcmTransfer,
+};
+use crate::call::{Call, CallBuilder};
 use crate::step::Step;
-use crate::utils::ToArray;
-use alloc::vec;
-use scale::{Decode, Encode};
-use xcm::{v2::prelude::*, VersionedMultiAssets, VersionedMultiLocation};
+use scale::Decode;
+use xcm::v3::prelude::*;
 
+/// Pallet index of xcmPallet on Polkadot and Kusama
+const XCM_PALLET_ID: u8 = 0x63;
+
+/// XCM transfer from the relay chain, sent in XCM v2
 #[derive(Clone)]
 pub struct PolkadotXcm {
     dest_chain_id: u32,
     account_type: AccountType,
+    config: XcmConfig,
+    dest_weights: XcmDestWeights,
 }
 
 impl PolkadotXcm {
@@ -20,58 +27,52 @@ impl PolkadotXcm {
         Self {
             dest_chain_id,
             account_type,
+            config: XcmConfig::default(),
+            dest_weights: XcmDestWeights::default(),
         }
     }
+
+    /// Fee asset of the transfer
+    pub fn with_xcm_config(mut self, config: XcmConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Weight limits of the destination chains, the limit of the step's destination is applied
+    pub fn with_dest_weights(mut self, dest_weights: XcmDestWeights) -> Self {
+        self.dest_weights = dest_weights;
+        self
+    }
+}
+
+/// Assets are registered with v2 locations on the relay chain
+fn asset_location(asset: &[u8]) -> Result<MultiLocation, &'static str> {
+    let location: xcm::v2::MultiLocation =
+        Decode::decode(&mut &asset[..]).map_err(|_| "InvalidMultilocation")?;
+    MultiLocation::try_from(location).map_err(|_| "InvalidMultilocation")
 }
 
 impl CallBuilder for PolkadotXcm {
     fn build_call(&self, step: Step) -> Result<Call, &'static str> {
-        let recipient = step.recipient;
-        let asset_location: MultiLocation =
-            Decode::decode(&mut step.spend_asset.as_slice()).map_err(|_| "InvalidMultilocation")?;
-        let dest = VersionedMultiLocation::V2(MultiLocation::new(
-            0,
-            Junctions::X1(Parachain(self.dest_chain_id)),
-        ));
-        let beneficiary = VersionedMultiLocation::V2(MultiLocation::new(
-            0,
-            Junctions::X1(match &self.account_type {
-                AccountType::Account20 => {
-                    let recipient: [u8; 20] = recipient.to_array();
-                    AccountKey20 {
-                        network: NetworkId::Any,
-                        key: recipient,
-                    }
-                }
-                AccountType::Account32 => {
-                    let recipient: [u8; 32] = recipient.to_array();
-                    AccountId32 {
-                        network: NetworkId::Any,
-                        id: recipient,
-                    }
-                }
-            }),
-        ));
-        let assets = VersionedMultiAssets::V2(MultiAssets::from(vec![MultiAsset {
-            id: AssetId::Concrete(asset_location),
+        let multi_asset = MultiAsset {
+            id: AssetId::Concrete(asset_location(&step.spend_asset)?),
             fun: Fungibility::Fungible(step.spend_amount.ok_or("MissingSpendAmount")?),
-        }]));
-
-        let fee_asset_item: u32 = 0;
-
-        let weight_limit = WeightLimit::Unlimited;
+        };
 
-        Ok(Call {
-            params: CallParams::Sub(SubCall {
-                calldata: SubExtrinsic {
-                    pallet_id: 0x63u8,
-                    call_id: 0x08u8,
-                    call: (dest, beneficiary, assets, fee_asset_item, weight_limit),
-                }
-                .encode(),
-            }),
-            input_call: None,
-            call_index: None,
+        let transfer = XcmTransfer::new(
+            MultiLocation::new(0, X1(Parachain(self.dest_chain_id))),
+            account_junction(&step.recipient, &self.account_type)?,
+            multi_asset,
+        )
+        .with_weight_limit(self.dest_weights.weight_limit(&step.dest_chain));
+        // Fee asset is also a v2 location, so the config is not applied as is
+        let transfer = match &self.config.fee {
+            Some(fee) => transfer.with_fee((asset_location(&fee.asset)?, fee.amount).into()),
+            None => transfer,
+        };
+        transfer.build(XcmPallet::PolkadotXcm {
+            pallet_id: XCM_PALLET_ID,
+            xcm_version: 2,
         })
     }
 }
@@ -79,6 +80,7 @@ impl CallBuilder for PolkadotXcm {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::call::CallParams;
     use crate::constants::MOONBEAM_PARACHAIN_ID;
 
     #[test]
@@ -86,6 +88,8 @@ mod tests {
         let xcm = PolkadotXcm {
             dest_chain_id: MOONBEAM_PARACHAIN_ID,
             account_type: AccountType::Account20,
+            config: XcmConfig::default(),
+            dest_weights: XcmDestWeights::default(),
         };
         let call = xcm
             .build_call(Step {
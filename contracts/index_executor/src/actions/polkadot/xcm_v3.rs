@@ -1,16 +1,24 @@
 use crate::account::AccountType;
-use crate::call::{Call, CallBuilder, CallParams, SubCall, SubExtrinsic};
+use crate::actions::base::xcm::{
+    account_junction, XcmConfig, XcmDestWeights, XcmPallet, XcmTransfer,
+};
+use crate::call::{Call, CallBuilder};
 use crate::step::Step;
-use crate::utils::{h160_to_sr25519_pub, ToArray};
-use alloc::vec;
-use scale::{Decode, Encode};
-use xcm::{v3::prelude::*, VersionedMultiAssets, VersionedMultiLocation};
+use crate::utils::h160_to_sr25519_pub;
+use scale::Decode;
+use xcm::v3::prelude::*;
 
+/// Pallet index of xcmPallet on Polkadot and Kusama
+const XCM_PALLET_ID: u8 = 0x63;
+
+/// XCM transfer from the relay chain, sent in XCM v3
 #[derive(Clone)]
 pub struct PolkadotXcm {
     dest_chain_id: u32,
     account_type: AccountType,
     is_evm: bool,
+    config: XcmConfig,
+    dest_weights: XcmDestWeights,
 }
 
 impl PolkadotXcm {
@@ -22,52 +30,48 @@ impl PolkadotXcm {
             dest_chain_id,
             account_type,
             is_evm,
+            config: XcmConfig::default(),
+            dest_weights: XcmDestWeights::default(),
         }
     }
+
+    /// Fee asset of the transfer
+    pub fn with_xcm_config(mut self, config: XcmConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Weight limits of the destination chains, the limit of the step's destination is applied
+    pub fn with_dest_weights(mut self, dest_weights: XcmDestWeights) -> Self {
+        self.dest_weights = dest_weights;
+        self
+    }
 }
 
 impl CallBuilder for PolkadotXcm {
     fn build_call(&self, step: Step) -> Result<Call, &'static str> {
-        let recipient = step.recipient;
         let asset_location: MultiLocation =
             Decode::decode(&mut step.spend_asset.as_slice()).map_err(|_| "InvalidMultilocation")?;
-        let dest = VersionedMultiLocation::V3(MultiLocation::new(
-            0,
-            Junctions::X1(Parachain(self.dest_chain_id)),
-        ));
-        let beneficiary = VersionedMultiLocation::V3(MultiLocation::new(
-            0,
-            Junctions::X1(match &self.account_type {
-                AccountType::Account20 => AccountKey20 {
-                    network: None,
-                    key: recipient.to_array(),
-                },
-                AccountType::Account32 => AccountId32 {
-                    network: None,
-                    id: match self.is_evm {
-                        true => h160_to_sr25519_pub(&recipient),
-                        false => recipient.to_array(),
-                    },
-                },
-            }),
-        ));
-        let assets = VersionedMultiAssets::V3(MultiAssets::from(vec![MultiAsset {
+        let multi_asset = MultiAsset {
             id: AssetId::Concrete(asset_location),
             fun: Fungibility::Fungible(step.spend_amount.ok_or("MissingSpendAmount")?),
-        }]));
-        let fee_asset_item: u32 = 0;
+        };
+        // EVM recipient receives with the substrate account mapped from it
+        let recipient = match (&self.account_type, self.is_evm) {
+            (AccountType::Account32, true) => h160_to_sr25519_pub(&step.recipient).to_vec(),
+            _ => step.recipient.clone(),
+        };
 
-        Ok(Call {
-            params: CallParams::Sub(SubCall {
-                calldata: SubExtrinsic {
-                    pallet_id: 0x63u8,
-                    call_id: 0x02u8,
-                    call: (dest, beneficiary, assets, fee_asset_item),
-                }
-                .encode(),
-            }),
-            input_call: None,
-            call_index: None,
+        XcmTransfer::new(
+            MultiLocation::new(0, X1(Parachain(self.dest_chain_id))),
+            account_junction(&recipient, &self.account_type)?,
+            multi_asset,
+        )
+        .with_weight_limit(self.dest_weights.weight_limit(&step.dest_chain))
+        .with_config(&self.config)?
+        .build(XcmPallet::PolkadotXcm {
+            pallet_id: XCM_PALLET_ID,
+            xcm_version: 3,
         })
     }
 }
@@ -75,6 +79,7 @@ impl CallBuilder for PolkadotXcm {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::call::CallParams;
     use crate::constants::ASTAR_PARACHAIN_ID;
 
     #[test]
@@ -83,6 +88,8 @@ mod tests {
             dest_chain_id: ASTAR_PARACHAIN_ID,
             account_type: AccountType::Account32,
             is_evm: true,
+            config: XcmConfig::default(),
+            dest_weights: XcmDestWeights::default(),
         };
         let call = xcm
             .build_call(Step {
@@ -114,6 +121,8 @@ mod tests {
             dest_chain_id: ASTAR_PARACHAIN_ID,
             account_type: AccountType::Account32,
             is_evm: false,
+            config: XcmConfig::default(),
+            dest_weights: XcmDestWeights::default(),
         };
         let call = xcm
             .build_call(Step {
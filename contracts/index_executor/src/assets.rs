@@ -1,31 +1,355 @@
-use crate::actions::acala::asset::AcalaAssets;
-use crate::actions::astar::asset::AstarAssets;
-use crate::actions::phala::asset::PhalaAssets;
+use crate::actions::acala::asset::{AcalaAssets, CurrencyId};
+use crate::constants::{ASSETHUB_ASSETS_PALLET_ID, ASSETHUB_PARACHAIN_ID, HYDRADX_NATIVE_ASSET_ID};
+use crate::rpc::{self, RpcEndpoint, RpcHealth};
 use crate::traits::AssetRegistry;
 
-use alloc::{string::String, vec, vec::Vec};
-use scale::Encode;
-use xcm::v3::MultiLocation;
-
-/// Return empty if assetid not found
-pub fn get_assetid_by_location(chain: &String, location: &MultiLocation) -> Vec<u8> {
-    pink_extension::debug!("Lookup assert registry module for {:?}", &chain);
-    if chain == "Acala" || chain == "Karura" {
-        match AcalaAssets.get_assetid(chain, location) {
-            Some(id) => id.encode().to_vec(),
-            _ => vec![],
+use alloc::{vec, vec::Vec};
+use pink_extension::ResultExt;
+use pink_subrpc::{
+    get_storage,
+    hasher::{Blake2_128Concat, Twox64Concat},
+    storage::{storage_map_prefix, storage_prefix},
+};
+use scale::{Decode, Encode};
+use xcm::{
+    v3::{prelude::*, MultiLocation},
+    VersionedMultiLocation,
+};
+
+/// Prefix of keys of the resolved asset ids in the local cache of the worker
+const CACHE_KEY_PREFIX: &[u8] = b"asset_resolver:";
+/// Cached entries expire after one day, in case assets are re-registered on chain
+const CACHE_EXPIRATION_SECS: u64 = 24 * 60 * 60;
+
+/// Resolves asset ids of Substrate chains from the asset registry pallet on chain:
+///
+/// - Phala/Khala: `AssetsRegistry`, asset id is `u32`
+/// - Astar/Shiden: `XcAssetConfig`, asset id is `u128`
+/// - Acala/Karura: `AssetRegistry`, asset id is `CurrencyId`, native tokens are not
+///   registered in the pallet and are resolved by `AcalaAssets`
+/// - HydraDX: `AssetRegistry`, asset id is `u32`, native HDX is `0`
+/// - AssetHub: asset id `u32` of pallet assets is part of the location, nothing to read
+///
+/// Asset ids are SCALE encoded. Found entries are kept in the local cache of the worker,
+/// so storage of an asset is only read once.
+#[derive(Clone, Debug)]
+pub struct AssetResolver {
+    endpoints: Vec<RpcEndpoint>,
+    health: RpcHealth,
+}
+
+impl AssetResolver {
+    pub fn new(endpoint: &str) -> Self {
+        Self::with_endpoints(vec![RpcEndpoint::new(endpoint, 0)], &RpcHealth::default())
+    }
+
+    /// Resolver failing over between `endpoints`, requests are recorded to `health`
+    pub fn with_endpoints(endpoints: Vec<RpcEndpoint>, health: &RpcHealth) -> Self {
+        Self {
+            endpoints,
+            health: health.clone(),
+        }
+    }
+
+    /// Return the encoded asset id of the location, `None` if the asset is not registered
+    pub fn lookup_assetid(
+        &self,
+        chain: &str,
+        location: &MultiLocation,
+    ) -> Result<Option<Vec<u8>>, &'static str> {
+        pink_extension::debug!("Lookup asset registry of {:?}", chain);
+        match chain {
+            "Phala" | "Khala" => self.read_cached(
+                chain,
+                &storage_map_prefix::<Twox64Concat>(
+                    &storage_prefix("AssetsRegistry", "IdByLocations")[..],
+                    &location.encode(),
+                ),
+                |raw| u32::decode(&mut &raw[..]).ok().map(|id| id.encode()),
+            ),
+            "Astar" | "Shiden" => self.read_cached(
+                chain,
+                &storage_map_prefix::<Twox64Concat>(
+                    &storage_prefix("XcAssetConfig", "AssetLocationToId")[..],
+                    &VersionedMultiLocation::V3(*location).encode(),
+                ),
+                |raw| u128::decode(&mut &raw[..]).ok().map(|id| id.encode()),
+            ),
+            "Acala" | "Karura" => {
+                if let Some(currency_id) = AcalaAssets::get_currency_id(location) {
+                    return Ok(Some(currency_id.encode()));
+                }
+                self.read_cached(
+                    chain,
+                    &storage_map_prefix::<Twox64Concat>(
+                        &storage_prefix("AssetRegistry", "LocationToCurrencyIds")[..],
+                        &location.encode(),
+                    ),
+                    |raw| match CurrencyId::decode(&mut &raw[..]) {
+                        Ok(currency_id @ CurrencyId::ForeignAsset(_)) => Some(currency_id.encode()),
+                        _ => None,
+                    },
+                )
+            }
+            "HydraDX" => {
+                if location == &MultiLocation::here() {
+                    return Ok(Some(HYDRADX_NATIVE_ASSET_ID.encode()));
+                }
+                self.read_cached(
+                    chain,
+                    &storage_map_prefix::<Blake2_128Concat>(
+                        &storage_prefix("AssetRegistry", "LocationAssets")[..],
+                        &location.encode(),
+                    ),
+                    |raw| u32::decode(&mut &raw[..]).ok().map(|id| id.encode()),
+                )
+            }
+            "AssetHub" => Ok(assethub_assetid(location).map(|id| id.encode())),
+            _ => Err("AssetRegistryNotFound"),
+        }
+    }
+
+    /// Return the location of the encoded asset id, `None` if the asset is not registered
+    pub fn lookup_location(
+        &self,
+        chain: &str,
+        asset_id: &[u8],
+    ) -> Result<Option<MultiLocation>, &'static str> {
+        pink_extension::debug!("Lookup asset registry of {:?}", chain);
+        let encoded_location = match chain {
+            "Phala" | "Khala" => {
+                let id = u32::decode(&mut &asset_id[..]).or(Err("InvalidAssetId"))?;
+                // Location is the first field of `AssetRegistryInfo`
+                self.read_cached(
+                    chain,
+                    &storage_map_prefix::<Twox64Concat>(
+                        &storage_prefix("AssetsRegistry", "RegistryInfoByIds")[..],
+                        &id.encode(),
+                    ),
+                    |raw| {
+                        MultiLocation::decode(&mut &raw[..])
+                            .ok()
+                            .map(|location| location.encode())
+                    },
+                )?
+            }
+            "Astar" | "Shiden" => {
+                let id = u128::decode(&mut &asset_id[..]).or(Err("InvalidAssetId"))?;
+                self.read_cached(
+                    chain,
+                    &storage_map_prefix::<Twox64Concat>(
+                        &storage_prefix("XcAssetConfig", "AssetIdToLocation")[..],
+                        &id.encode(),
+                    ),
+                    |raw| {
+                        VersionedMultiLocation::decode(&mut &raw[..])
+                            .ok()
+                            .and_then(|location| MultiLocation::try_from(location).ok())
+                            .map(|location| location.encode())
+                    },
+                )?
+            }
+            "Acala" | "Karura" => {
+                match CurrencyId::decode(&mut &asset_id[..]).or(Err("InvalidAssetId"))? {
+                    CurrencyId::Token(symbol) => AcalaAssets::get_location_by_symbol(symbol)
+                        .map(|location| location.encode()),
+                    CurrencyId::ForeignAsset(id) => self.read_cached(
+                        chain,
+                        &storage_map_prefix::<Twox64Concat>(
+                            &storage_prefix("AssetRegistry", "ForeignAssetLocations")[..],
+                            &id.encode(),
+                        ),
+                        |raw| {
+                            MultiLocation::decode(&mut &raw[..])
+                                .ok()
+                                .map(|location| location.encode())
+                        },
+                    )?,
+                    _ => None,
+                }
+            }
+            "HydraDX" => {
+                let id = u32::decode(&mut &asset_id[..]).or(Err("InvalidAssetId"))?;
+                if id == HYDRADX_NATIVE_ASSET_ID {
+                    Some(MultiLocation::here().encode())
+                } else {
+                    self.read_cached(
+                        chain,
+                        &storage_map_prefix::<Twox64Concat>(
+                            &storage_prefix("AssetRegistry", "AssetLocations")[..],
+                            &id.encode(),
+                        ),
+                        |raw| {
+                            MultiLocation::decode(&mut &raw[..])
+                                .ok()
+                                .map(|location| location.encode())
+                        },
+                    )?
+                }
+            }
+            "AssetHub" => {
+                let id = u32::decode(&mut &asset_id[..]).or(Err("InvalidAssetId"))?;
+                Some(
+                    MultiLocation::new(
+                        0,
+                        X2(
+                            PalletInstance(ASSETHUB_ASSETS_PALLET_ID),
+                            GeneralIndex(id as u128),
+                        ),
+                    )
+                    .encode(),
+                )
+            }
+            _ => return Err("AssetRegistryNotFound"),
+        };
+        encoded_location
+            .map(|raw| MultiLocation::decode(&mut raw.as_slice()).or(Err("DecodeStorageFailed")))
+            .transpose()
+    }
+
+    /// Read the storage item, values are converted by `parse` before being cached.
+    /// Missing or unrecognized values are not cached since they may be registered later
+    fn read_cached(
+        &self,
+        chain: &str,
+        storage_key: &[u8],
+        parse: impl Fn(Vec<u8>) -> Option<Vec<u8>>,
+    ) -> Result<Option<Vec<u8>>, &'static str> {
+        let cache_key = cache_key(chain, storage_key);
+        if let Some(value) = pink_extension::ext().cache_get(&cache_key) {
+            return Ok(Some(value));
+        }
+        let value = rpc::with_failover(&self.endpoints, &self.health, |endpoint| {
+            get_storage(endpoint, storage_key, None)
+                .log_err("AssetResolver: read storage of asset registry failed")
+                .or(Err("FetchDataFailed"))
+        })?
+        .and_then(parse);
+        if let Some(value) = &value {
+            if pink_extension::ext().cache_set(&cache_key, value).is_ok() {
+                pink_extension::ext().cache_set_expiration(&cache_key, CACHE_EXPIRATION_SECS);
+            }
         }
-    } else if chain == "Astar" {
-        match AstarAssets::new().get_assetid(chain, location) {
-            Some(id) => id.to_le_bytes().to_vec(),
-            _ => vec![],
+        Ok(value)
+    }
+}
+
+/// Chains sharing the same runtime, e.g. Phala and Khala, have the same storage keys, so keys
+/// of the cache are scoped by chain
+fn cache_key(chain: &str, storage_key: &[u8]) -> Vec<u8> {
+    [CACHE_KEY_PREFIX, chain.as_bytes(), b":", storage_key].concat()
+}
+
+/// Asset id of pallet assets on AssetHub, the location can be either seen from AssetHub or
+/// from other parachains
+fn assethub_assetid(location: &MultiLocation) -> Option<u32> {
+    let (pallet, index) = match (location.parents, location.interior) {
+        (0, X2(PalletInstance(pallet), GeneralIndex(index))) => (pallet, index),
+        (1, X3(Parachain(ASSETHUB_PARACHAIN_ID), PalletInstance(pallet), GeneralIndex(index))) => {
+            (pallet, index)
         }
-    } else if chain == "Khala" || chain == "Phala" {
-        match PhalaAssets::new().get_assetid(chain, location) {
-            Some(id) => id.to_le_bytes().to_vec(),
-            _ => vec![],
+        _ => return None,
+    };
+    if pallet != ASSETHUB_ASSETS_PALLET_ID {
+        return None;
+    }
+    index.try_into().ok()
+}
+
+impl AssetRegistry<Vec<u8>> for AssetResolver {
+    fn get_assetid(&self, chain: &str, location: &MultiLocation) -> Option<Vec<u8>> {
+        self.lookup_assetid(chain, location).ok().flatten()
+    }
+
+    fn get_location(&self, chain: &str, asset_id: Vec<u8>) -> Option<MultiLocation> {
+        self.lookup_location(chain, &asset_id).ok().flatten()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_without_storage() {
+        pink_extension_runtime::mock_ext::mock_all_ext();
+
+        // Native tokens are resolved without reading storage
+        let resolver = AssetResolver::new("");
+        let aca = MultiLocation::new(
+            1,
+            X2(
+                Parachain(2000),
+                crate::utils::slice_to_generalkey(&[0x00, 0x00]),
+            ),
+        );
+        let aca_id = resolver.get_assetid("Acala", &aca).unwrap();
+        assert_eq!(resolver.get_location("Acala", aca_id), Some(aca));
+        assert_eq!(
+            resolver.lookup_assetid("Unknown", &aca),
+            Err("AssetRegistryNotFound")
+        );
+
+        assert_eq!(
+            resolver.get_assetid("HydraDX", &MultiLocation::here()),
+            Some(0u32.encode())
+        );
+        let usdt = MultiLocation::new(
+            1,
+            X3(Parachain(1000), PalletInstance(50), GeneralIndex(1984)),
+        );
+        let usdt_id = resolver.get_assetid("AssetHub", &usdt).unwrap();
+        assert_eq!(usdt_id, 1984u32.encode());
+        assert_eq!(
+            resolver.get_location("AssetHub", usdt_id),
+            Some(MultiLocation::new(
+                0,
+                X2(PalletInstance(50), GeneralIndex(1984))
+            ))
+        );
+    }
+
+    #[test]
+    fn test_cache_scoped_by_chain() {
+        pink_extension_runtime::mock_ext::mock_all_ext();
+
+        // Same location is registered with different ids on Phala and Khala, which share
+        // the storage key
+        let dot = MultiLocation::new(1, Here);
+        let storage_key = storage_map_prefix::<Twox64Concat>(
+            &storage_prefix("AssetsRegistry", "IdByLocations")[..],
+            &dot.encode(),
+        );
+        for (chain, id) in [("Phala", 0u32), ("Khala", 1u32)] {
+            pink_extension::ext()
+                .cache_set(&cache_key(chain, &storage_key), &id.encode())
+                .unwrap();
         }
-    } else {
-        vec![]
+
+        let resolver = AssetResolver::new("");
+        assert_eq!(resolver.get_assetid("Phala", &dot), Some(0u32.encode()));
+        assert_eq!(resolver.get_assetid("Khala", &dot), Some(1u32.encode()));
+    }
+
+    #[test]
+    #[ignore]
+    fn test_resolve_registered_assets() {
+        pink_extension_runtime::mock_ext::mock_all_ext();
+
+        let pha = MultiLocation::new(1, X1(Parachain(2035)));
+        let resolver = AssetResolver::new("https://astar.public.blastapi.io");
+        let pha_id = resolver.get_assetid("Astar", &pha).unwrap();
+        assert_eq!(pha_id, 18446744073709551622_u128.encode());
+        assert_eq!(resolver.get_location("Astar", pha_id), Some(pha));
+
+        let dot = MultiLocation::new(1, Here);
+        let resolver = AssetResolver::new("https://api.phala.network/rpc");
+        let dot_id = resolver.get_assetid("Phala", &dot).unwrap();
+        assert_eq!(dot_id, 0u32.encode());
+        assert_eq!(resolver.get_location("Phala", dot_id), Some(dot));
+
+        let resolver = AssetResolver::new("https://acala-rpc.dwellir.com");
+        let pha_id = resolver.get_assetid("Acala", &pha).unwrap();
+        assert_eq!(resolver.get_location("Acala", pha_id), Some(pha));
     }
 }
@@ -90,7 +90,33 @@ impl Tokenizable for Call {
 
 impl TokenizableItem for Call {}
 
+/// Fee an action charges on top of the spent amount, e.g. fee of bridge deposits
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ActionFee {
+    // Location of the asset the fee is paid in, on the source chain of the step
+    pub asset: Vec<u8>,
+    pub amount: u128,
+}
+
 pub trait CallBuilder: DynClone {
     fn build_call(&self, step: Step) -> Result<Call, &'static str>;
+
+    /// Build all calls of the step, for actions that need more than one call in the batch,
+    /// e.g. forward the output to the recipient
+    fn build_calls(&self, step: Step) -> Result<Vec<Call>, &'static str> {
+        Ok(vec![self.build_call(step)?])
+    }
+
+    /// Quote the fee the action charges for the step, most actions charge nothing
+    fn quote_fee(&self, _step: Step) -> Result<Option<ActionFee>, &'static str> {
+        Ok(None)
+    }
+
+    /// Whether the calls only work for the exact spend amount they are built with, e.g. calldata
+    /// quoted by an aggregator, which goes stale once the handler updates the amount with the
+    /// output of a call before them
+    fn needs_final_amount(&self) -> bool {
+        false
+    }
 }
 dyn_clone::clone_trait_object!(CallBuilder);
@@ -1,7 +1,10 @@
 use crate::actions::base::account::{
     AccountData, AccountInfo, AssetAccount, Balance, Index, OrmlTokenAccountData,
 };
-use crate::assets::get_assetid_by_location;
+use crate::assets::AssetResolver;
+use crate::gas::FeeStrategy;
+use crate::rpc::{self, RpcEndpoint, RpcHealth};
+use crate::tx::XcmEvents;
 use alloc::{format, string::String, vec::Vec};
 
 use pink_extension::ResultExt;
@@ -18,9 +21,12 @@ use pink_web3::{
     types::{Address, U256},
     Web3,
 };
+use serde::{Deserialize, Serialize};
 use xcm::v3::MultiLocation;
 
-#[derive(Clone, Debug, Default, PartialEq, Eq, scale::Encode, scale::Decode)]
+#[derive(
+    Clone, Debug, Default, PartialEq, Eq, scale::Encode, scale::Decode, Serialize, Deserialize,
+)]
 #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
 pub enum ChainType {
     #[default]
@@ -28,7 +34,9 @@ pub enum ChainType {
     Sub,
 }
 
-#[derive(Clone, Debug, Default, PartialEq, Eq, scale::Encode, scale::Decode)]
+#[derive(
+    Clone, Debug, Default, PartialEq, Eq, scale::Encode, scale::Decode, Serialize, Deserialize,
+)]
 #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
 pub enum ForeignAssetModule {
     #[default]
@@ -36,21 +44,64 @@ pub enum ForeignAssetModule {
     OrmlToken,
 }
 
+/// When a block is considered irreversible
+#[derive(Clone, Debug, Default, PartialEq, Eq, scale::Encode, scale::Decode)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+pub enum Finality {
+    /// Block has been buried by the given number of blocks
+    Depth(u32),
+    /// Block has been finalized, e.g. by GRANDPA or the `finalized` tag of EVM chains
+    #[default]
+    Finalized,
+}
+
 #[derive(Debug, Clone, Default, scale::Encode, scale::Decode)]
 #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
 pub struct Chain {
     pub id: u32,
     pub name: String,
-    pub endpoint: String,
+    // RPC endpoints, failover happens between them
+    pub endpoints: Vec<RpcEndpoint>,
     pub chain_type: ChainType,
     // Encoded native asset location for Sub-chains
     pub native_asset: Vec<u8>,
     pub foreign_asset: Option<ForeignAssetModule>,
     pub handler_contract: Vec<u8>,
     pub tx_indexer_url: String,
+    // Fee strategy applied to transactions sent to EVM chains
+    pub fee_strategy: FeeStrategy,
+    // Finality a transaction must reach before the step is settled
+    pub finality: Finality,
+    // Pallets and events reporting XCM messages of Sub-chains, `None` if not tracked
+    pub xcm_events: Option<XcmEvents>,
+    // Weight limit (ref_time, proof_size) of XCM executed on the chain when it's the
+    // destination of a transfer, the weight is not limited if `None`
+    pub xcm_dest_weight: Option<(u64, u64)>,
 }
 
 impl Chain {
+    /// Endpoints in the order requests are sent to them, priority is reassigned to the rank.
+    ///
+    /// Action builders are cached during an execution, they fail over between the endpoints
+    /// in this order and record their requests to the statistics of the execution
+    pub fn ranked_endpoints(&self, health: &RpcHealth) -> Vec<RpcEndpoint> {
+        health
+            .rank(&self.endpoints)
+            .into_iter()
+            .enumerate()
+            .map(|(rank, endpoint)| RpcEndpoint::new(&endpoint.url, rank as u8))
+            .collect()
+    }
+
+    /// Send RPC requests with `f` to endpoints of the chain, fail over to the next endpoint on error
+    pub fn with_rpc<T>(
+        &self,
+        health: &RpcHealth,
+        f: impl FnMut(&str) -> Result<T, &'static str>,
+    ) -> Result<T, &'static str> {
+        rpc::with_failover(&self.endpoints, health, f)
+    }
+
     pub fn is_native(&self, asset: &Vec<u8>) -> bool {
         asset == &self.native_asset
     }
@@ -62,28 +113,46 @@ impl Chain {
     pub fn is_sub_chain(&self) -> bool {
         self.chain_type == ChainType::Sub
     }
+
+    /// Handler of EVM chains is the zero address until it is deployed, no transaction can be
+    /// sent to the chain before that
+    pub fn has_handler(&self) -> bool {
+        !(self.is_evm_chain() && self.handler_contract.iter().all(|b| *b == 0))
+    }
 }
 
 /// Query on-chain `account` nonce
 pub trait NonceFetcher {
-    fn get_nonce(&self, account: Vec<u8>) -> core::result::Result<u64, &'static str>;
+    fn get_nonce(
+        &self,
+        account: Vec<u8>,
+        health: &RpcHealth,
+    ) -> core::result::Result<u64, &'static str>;
 }
 impl NonceFetcher for Chain {
-    fn get_nonce(&self, account: Vec<u8>) -> core::result::Result<u64, &'static str> {
+    fn get_nonce(
+        &self,
+        account: Vec<u8>,
+        health: &RpcHealth,
+    ) -> core::result::Result<u64, &'static str> {
         Ok(match self.chain_type {
             ChainType::Evm => {
                 let account20: [u8; 20] = account.try_into().map_err(|_| "InvalidAddress")?;
                 let evm_account: Address = account20.into();
-                let eth = Eth::new(PinkHttp::new(self.endpoint.clone()));
-                let nonce = resolve_ready(eth.transaction_count(evm_account, None))
-                    .map_err(|_| "FetchDataFailed")?;
+                let nonce = self.with_rpc(health, |endpoint| {
+                    let eth = Eth::new(PinkHttp::new(endpoint));
+                    resolve_ready(eth.transaction_count(evm_account, None))
+                        .map_err(|_| "FetchDataFailed")
+                })?;
                 nonce.try_into().expect("Nonce onverflow")
             }
             ChainType::Sub => {
                 let version = get_ss58addr_version(&self.name).map_err(|_| "Ss58")?;
                 let public_key: [u8; 32] = account.try_into().map_err(|_| "InvalidAddress")?;
                 let addr = public_key.to_ss58check_with_version(version.prefix());
-                get_next_nonce(&self.endpoint, &addr).map_err(|_| "FetchDataFailed")?
+                self.with_rpc(health, |endpoint| {
+                    get_next_nonce(endpoint, &addr).map_err(|_| "FetchDataFailed")
+                })?
             }
         })
     }
@@ -95,6 +164,7 @@ pub trait BalanceFetcher {
         &self,
         asset: Vec<u8>,
         account: Vec<u8>,
+        health: &RpcHealth,
     ) -> core::result::Result<u128, &'static str>;
 }
 
@@ -103,53 +173,58 @@ impl BalanceFetcher for Chain {
         &self,
         asset: Vec<u8>,
         account: Vec<u8>,
+        health: &RpcHealth,
     ) -> core::result::Result<u128, &'static str> {
         match self.chain_type {
             ChainType::Evm => {
-                let transport = PinkHttp::new(&self.endpoint);
                 let account20: [u8; 20] = account.try_into().map_err(|_| "InvalidAddress")?;
                 let evm_account: Address = account20.into();
 
                 if self.is_native(&asset) {
-                    let web3 = Web3::new(transport);
-                    let balance = resolve_ready(web3.eth().balance(evm_account, None))
-                    .log_err(&format!("chain::get_balance: fetch data [evm native balance] failed, chain: {:?}, asset: {:?}", self.name, hex::encode(&asset)))
-                        .or(Err("FetchDataFailed"))?;
+                    let balance = self.with_rpc(health, |endpoint| {
+                        let web3 = Web3::new(PinkHttp::new(endpoint));
+                        resolve_ready(web3.eth().balance(evm_account, None))
+                        .log_err(&format!("chain::get_balance: fetch data [evm native balance] failed, chain: {:?}, asset: {:?}", self.name, hex::encode(&asset)))
+                            .or(Err("FetchDataFailed"))
+                    })?;
                     balance.try_into().map_err(|_| "BalanceOverflow")
                 } else {
-                    let eth = Eth::new(transport);
                     let asset_account20: [u8; 20] =
                         asset.clone().try_into().map_err(|_| "InvalidAddress")?;
                     let token_address: Address = asset_account20.into();
-                    let token =
-                        Contract::from_json(eth, token_address, include_bytes!("./abi/erc20.json"))
-                            .expect("Bad abi data");
-                    let balance: U256 = resolve_ready(token.query(
-                        "balanceOf",
-                        evm_account,
-                        None,
-                        Options::default(),
-                        None,
-                    ))
-                    .log_err(&format!("chain::get_balance: fetch data [evm erc20 balance] failed, chain: {:?}, asset: {:?}", self.name, hex::encode(&asset)))
-                    .or(Err("FetchDataFailed"))?;
+                    let balance: U256 = self.with_rpc(health, |endpoint| {
+                        let eth = Eth::new(PinkHttp::new(endpoint));
+                        let token =
+                            Contract::from_json(eth, token_address, include_bytes!("./abi/erc20.json"))
+                                .expect("Bad abi data");
+                        resolve_ready(token.query(
+                            "balanceOf",
+                            evm_account,
+                            None,
+                            Options::default(),
+                            None,
+                        ))
+                        .log_err(&format!("chain::get_balance: fetch data [evm erc20 balance] failed, chain: {:?}, asset: {:?}", self.name, hex::encode(&asset)))
+                        .or(Err("FetchDataFailed"))
+                    })?;
                     balance.try_into().map_err(|_| "BalanceOverflow")
                 }
             }
             ChainType::Sub => {
                 let public_key: [u8; 32] = account.try_into().map_err(|_| "InvalidAddress")?;
                 if self.is_native(&asset) {
-                    if let Some(raw_storage) = get_storage(
-                        &self.endpoint,
-                        &storage_map_prefix::<Blake2_128Concat>(
-                            &storage_prefix("System", "Account")[..],
-                            &public_key,
-                        ),
-                        None,
-                    )
-                    .log_err("chain::get_balance, read storage [sub native balance] failed")
-                    .or(Err("FetchDataFailed"))?
-                    {
+                    if let Some(raw_storage) = self.with_rpc(health, |endpoint| {
+                        get_storage(
+                            endpoint,
+                            &storage_map_prefix::<Blake2_128Concat>(
+                                &storage_prefix("System", "Account")[..],
+                                &public_key,
+                            ),
+                            None,
+                        )
+                        .log_err("chain::get_balance, read storage [sub native balance] failed")
+                        .or(Err("FetchDataFailed"))
+                    })? {
                         let account_info: AccountInfo<Index, AccountData<Balance>> =
                             scale::Decode::decode(&mut raw_storage.as_slice())
                                 .log_err("chain::get_balance, decode storage [sub native balance] failed")
@@ -164,12 +239,13 @@ impl BalanceFetcher for Chain {
                             .map_err(|_| "InvalidMultilocation")?;
                     match self.foreign_asset {
                         Some(ForeignAssetModule::PalletAsset) => {
-                            let asset_id = get_assetid_by_location(&self.name, &asset_location);
-                            if asset_id.is_empty() {
-                                return Err("AssetNotRecognized");
-                            }
-                            if let Some(raw_storage) = get_storage(
-                                &self.endpoint,
+                            let asset_id =
+                                AssetResolver::with_endpoints(self.endpoints.clone(), health)
+                                    .lookup_assetid(&self.name, &asset_location)?
+                                    .ok_or("AssetNotRecognized")?;
+                            if let Some(raw_storage) = self.with_rpc(health, |endpoint| {
+                                get_storage(
+                                endpoint,
                                 &storage_double_map_prefix::<Blake2_128Concat, Blake2_128Concat>(
                                     &storage_prefix("Assets", "Account")[..],
                                     &asset_id,
@@ -180,7 +256,8 @@ impl BalanceFetcher for Chain {
                             .log_err(
                                 "chain::get_balance: read storage [sub foreign asset balance] from pallet-asset failed",
                             )
-                            .or(Err("FetchDataFailed"))?
+                            .or(Err("FetchDataFailed"))
+                                })?
                             {
                                 let account_info: AssetAccount<Balance, Balance, ()> =
                                     scale::Decode::decode(&mut raw_storage.as_slice())
@@ -192,12 +269,13 @@ impl BalanceFetcher for Chain {
                             }
                         }
                         Some(ForeignAssetModule::OrmlToken) => {
-                            let currency_id = get_assetid_by_location(&self.name, &asset_location);
-                            if currency_id.is_empty() {
-                                return Err("AssetNotRecognized");
-                            }
-                            if let Some(raw_storage) = get_storage(
-                                &self.endpoint,
+                            let currency_id =
+                                AssetResolver::with_endpoints(self.endpoints.clone(), health)
+                                    .lookup_assetid(&self.name, &asset_location)?
+                                    .ok_or("AssetNotRecognized")?;
+                            if let Some(raw_storage) = self.with_rpc(health, |endpoint| {
+                                get_storage(
+                                endpoint,
                                 &storage_double_map_prefix::<Blake2_128Concat, Twox64Concat>(
                                     &storage_prefix("Tokens", "Accounts")[..],
                                     &public_key,
@@ -208,7 +286,8 @@ impl BalanceFetcher for Chain {
                             .log_err(
                                 "chain::get_balance: read storage [sub foreign asset balance] from orml-token failed",
                             )
-                            .or(Err("FetchDataFailed"))?
+                            .or(Err("FetchDataFailed"))
+                                })?
                             {
                                 let account_info: OrmlTokenAccountData<Balance> =
                                     scale::Decode::decode(&mut raw_storage.as_slice())
@@ -235,6 +314,36 @@ mod tests {
     use scale::Encode;
     use xcm::v3::{prelude::*, MultiLocation};
 
+    #[test]
+    fn test_ranked_endpoints() {
+        let mut phala = crate::registry::Registry::new()
+            .get_chain(&"Phala".to_string())
+            .unwrap();
+        phala.endpoints = vec![
+            RpcEndpoint::new("https://backup", 1),
+            RpcEndpoint::new("https://primary", 0),
+        ];
+        let health = RpcHealth::default();
+        health.record("https://primary", false);
+
+        // Failing primary endpoint is ranked after the backup
+        let ranked = phala.ranked_endpoints(&health);
+        assert_eq!(
+            ranked,
+            vec![
+                RpcEndpoint::new("https://backup", 0),
+                RpcEndpoint::new("https://primary", 1),
+            ]
+        );
+        let mut tried = vec![];
+        let result = rpc::with_failover(&ranked, &health, |url| {
+            tried.push(String::from(url));
+            Err::<(), _>("Timeout")
+        });
+        assert_eq!(result, Err("Timeout"));
+        assert_eq!(tried, vec!["https://backup", "https://primary"]);
+    }
+
     #[test]
     fn test_get_evm_account_nonce() {
         dotenv().ok();
@@ -243,18 +352,26 @@ mod tests {
         let goerli = Chain {
             id: 1,
             name: String::from("Goerli"),
-            endpoint: String::from(
+            endpoints: vec![RpcEndpoint::new(
                 "https://eth-goerli.g.alchemy.com/v2/lLqSMX_1unN9Xrdy_BB9LLZRgbrXwZv2",
-            ),
+                0,
+            )],
             chain_type: ChainType::Evm,
             native_asset: vec![0],
             foreign_asset: None,
             handler_contract: hex!("056C0E37d026f9639313C281250cA932C9dbe921").into(),
             tx_indexer_url: Default::default(),
+            fee_strategy: Default::default(),
+            finality: Default::default(),
+            xcm_events: None,
+            xcm_dest_weight: None,
         };
         assert_eq!(
             goerli
-                .get_nonce(hex!("0E275F8839b788B2674935AD97C01cF73A9E8c41").into())
+                .get_nonce(
+                    hex!("0E275F8839b788B2674935AD97C01cF73A9E8c41").into(),
+                    &RpcHealth::default()
+                )
                 .unwrap(),
             2
         );
@@ -268,17 +385,25 @@ mod tests {
         let khala = Chain {
             id: 1,
             name: String::from("Khala"),
-            endpoint: String::from("https://khala.api.onfinality.io:443/public-ws"),
+            endpoints: vec![RpcEndpoint::new(
+                "https://khala.api.onfinality.io:443/public-ws",
+                0,
+            )],
             chain_type: ChainType::Sub,
             native_asset: MultiLocation::new(1, X1(Parachain(2004))).encode(),
             foreign_asset: Some(ForeignAssetModule::PalletAsset),
             handler_contract: hex!("056C0E37d026f9639313C281250cA932C9dbe921").into(),
             tx_indexer_url: Default::default(),
+            fee_strategy: Default::default(),
+            finality: Default::default(),
+            xcm_events: None,
+            xcm_dest_weight: None,
         };
         assert_eq!(
             khala
                 .get_nonce(
-                    hex!("92436be04f9dc677f9f51b092161b6e5ba00163ad6328fb2c920fcb30b6c7362").into()
+                    hex!("92436be04f9dc677f9f51b092161b6e5ba00163ad6328fb2c920fcb30b6c7362").into(),
+                    &RpcHealth::default()
                 )
                 .unwrap(),
             2
@@ -293,21 +418,27 @@ mod tests {
         let goerli = Chain {
             id: 1,
             name: String::from("Goerli"),
-            endpoint: String::from(
+            endpoints: vec![RpcEndpoint::new(
                 "https://eth-goerli.g.alchemy.com/v2/lLqSMX_1unN9Xrdy_BB9LLZRgbrXwZv2",
-            ),
+                0,
+            )],
             chain_type: ChainType::Evm,
             native_asset: vec![0],
             foreign_asset: None,
             handler_contract: hex!("056C0E37d026f9639313C281250cA932C9dbe921").into(),
             tx_indexer_url: Default::default(),
+            fee_strategy: Default::default(),
+            finality: Default::default(),
+            xcm_events: None,
+            xcm_dest_weight: None,
         };
         // Get native asset balance
         assert_eq!(
             goerli
                 .get_balance(
                     hex!("00").into(),
-                    hex!("0E275F8839b788B2674935AD97C01cF73A9E8c41").into()
+                    hex!("0E275F8839b788B2674935AD97C01cF73A9E8c41").into(),
+                    &RpcHealth::default()
                 )
                 .unwrap(),
             6_850_126_116_190_000u128
@@ -317,7 +448,8 @@ mod tests {
             goerli
                 .get_balance(
                     hex!("B376b0Ee6d8202721838e76376e81eEc0e2FE864").into(),
-                    hex!("0E275F8839b788B2674935AD97C01cF73A9E8c41").into()
+                    hex!("0E275F8839b788B2674935AD97C01cF73A9E8c41").into(),
+                    &RpcHealth::default()
                 )
                 .unwrap(),
             5_000_000_000_000_000_000u128
@@ -333,17 +465,24 @@ mod tests {
         let khala = Chain {
             id: 1,
             name: String::from("Khala"),
-            endpoint: String::from("https://khala.api.onfinality.io:443/public-ws"),
+            endpoints: vec![RpcEndpoint::new(
+                "https://khala.api.onfinality.io:443/public-ws",
+                0,
+            )],
             chain_type: ChainType::Sub,
             native_asset: MultiLocation::new(1, X1(Parachain(2004))).encode(),
             foreign_asset: Some(ForeignAssetModule::PalletAsset),
             handler_contract: hex!("056C0E37d026f9639313C281250cA932C9dbe921").into(),
             tx_indexer_url: Default::default(),
+            fee_strategy: Default::default(),
+            finality: Default::default(),
+            xcm_events: None,
+            xcm_dest_weight: None,
         };
         let karura = Chain {
             id: 2,
             name: String::from("Karura"),
-            endpoint: String::from("https://karura-rpc.dwellir.com"),
+            endpoints: vec![RpcEndpoint::new("https://karura-rpc.dwellir.com", 0)],
             chain_type: ChainType::Sub,
             native_asset: MultiLocation::new(
                 1,
@@ -356,25 +495,37 @@ mod tests {
             foreign_asset: Some(ForeignAssetModule::OrmlToken),
             handler_contract: hex!("056C0E37d026f9639313C281250cA932C9dbe921").into(),
             tx_indexer_url: Default::default(),
+            fee_strategy: Default::default(),
+            finality: Default::default(),
+            xcm_events: None,
+            xcm_dest_weight: None,
         };
         // Get native asset (PHA on Khala)
         assert_eq!(
             khala
-                .get_balance(khala.native_asset.clone(), account32.into())
+                .get_balance(
+                    khala.native_asset.clone(),
+                    account32.into(),
+                    &RpcHealth::default()
+                )
                 .unwrap(),
             96_879_782_174u128
         );
         // Get foreign asset managed by pallet-assets (KAR on Khala)
         assert_eq!(
             khala
-                .get_balance(karura.native_asset.clone(), account32.into())
+                .get_balance(
+                    karura.native_asset.clone(),
+                    account32.into(),
+                    &RpcHealth::default()
+                )
                 .unwrap(),
             40_000_000_000u128
         );
         // Get foreign asset managed by orml tokens (PHA on Karura)
         assert_eq!(
             karura
-                .get_balance(khala.native_asset, account32.into())
+                .get_balance(khala.native_asset, account32.into(), &RpcHealth::default())
                 .unwrap(),
             80_000_000_000u128
         );
@@ -1,3 +1,5 @@
+use xcm::v3::Weight;
+
 // Chainbridge chain ID
 #[allow(dead_code)]
 pub const CHAINBRIDGE_ID_ETHEREUM: u8 = 0;
@@ -10,13 +12,28 @@ pub const ACALA_PARACHAIN_ID: u32 = 2000;
 pub const ASTAR_PARACHAIN_ID: u32 = 2006;
 pub const MOONBEAM_PARACHAIN_ID: u32 = 2004;
 pub const PHALA_PARACHAIN_ID: u32 = 2035;
-#[allow(dead_code)]
 pub const ASSETHUB_PARACHAIN_ID: u32 = 1000;
-#[allow(dead_code)]
+pub const KARURA_PARACHAIN_ID: u32 = 2000;
 pub const KHALA_PARACHAIN_ID: u32 = 2004;
-#[allow(dead_code)]
 pub const MOONRIVER_PARACHAIN_ID: u32 = 2023;
+pub const SHIDEN_PARACHAIN_ID: u32 = 2007;
+pub const HYDRADX_PARACHAIN_ID: u32 = 2034;
+// Sygma domain ids
 pub const SYGMA_ETHEREUM_DOMAIN_ID: u8 = 1;
+pub const SYGMA_KHALA_DOMAIN_ID: u8 = 2;
+pub const SYGMA_PHALA_DOMAIN_ID: u8 = 3;
+// Index of pallet assets on AssetHub
+pub const ASSETHUB_ASSETS_PALLET_ID: u8 = 50;
+// Asset ids of stablecoins on AssetHub
+pub const ASSETHUB_USDT_ID: u128 = 1984;
+pub const ASSETHUB_USDC_ID: u128 = 1337;
+// Asset id of HDX in pallet asset registry of HydraDX
+pub const HYDRADX_NATIVE_ASSET_ID: u32 = 0;
+// Weight limit (ref_time, proof_size) of XCM executed on Phala and Khala, configured on their
+// chain entries
+pub const PHALA_XCM_DEST_WEIGHT: (u64, u64) = (6_000_000_000, 1_000_000);
+// Weight for pallets that require a dest weight if the destination is not limited
+pub const XCM_DEFAULT_DEST_WEIGHT: Weight = Weight::from_parts(6_000_000_000, 1_000_000);
 
 // Ethereum block time in seconds
 pub const ETHEREUM_BLOCK_TIME: u16 = 15;
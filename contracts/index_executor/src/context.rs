@@ -1,12 +1,44 @@
 use super::account::AccountInfo;
 use crate::actions::ActionExtraInfo;
+use crate::rpc::RpcHealth;
+use crate::task::ExecuteTx;
+use crate::tx::{TxStatus, XcmDelivery};
 use crate::{call::CallBuilder, registry::Registry};
-use alloc::{boxed::Box, string::String, vec::Vec};
+use alloc::{boxed::Box, collections::BTreeMap, string::String, vec::Vec};
+use core::cell::RefCell;
+
+/// Action builders constructed during the query, keyed by (chain, lowercased action id).
+///
+/// Building an action parses the ABI of its contract, which is expensive under the gas
+/// limit of a query, so every action is built at most once per `Context`.
+#[derive(Default)]
+pub struct ActionCache(RefCell<BTreeMap<(String, String), Box<dyn CallBuilder>>>);
+
+/// XCM deliveries looked up during the query, keyed by hashes of the transactions sent the
+/// message.
+///
+/// Delivery is found by scanning blocks of the destination chain, a step checks it when
+/// confirming and again when settling, so it is looked up at most once per `Context`.
+#[derive(Default)]
+pub struct XcmDeliveryCache(RefCell<BTreeMap<Vec<Vec<u8>>, Option<XcmDelivery>>>);
+
+/// Transaction results fetched during the query, keyed by (chain, nonce, hashes of the sent
+/// transactions).
+///
+/// A step checks the result when confirming and again when checking finality, so it is
+/// fetched at most once per `Context`.
+#[derive(Default)]
+pub struct TxStatusCache(RefCell<BTreeMap<(String, u64, Vec<Vec<u8>>), Option<TxStatus>>>);
 
 pub struct Context<'a> {
     pub signer: [u8; 32],
     pub registry: &'a Registry,
     pub worker_accounts: Vec<AccountInfo>,
+    /// Error statistics of RPC endpoints collected during execution
+    pub rpc_health: &'a RpcHealth,
+    pub action_cache: ActionCache,
+    pub xcm_delivery_cache: XcmDeliveryCache,
+    pub tx_status_cache: TxStatusCache,
 }
 
 impl<'a> Context<'a> {
@@ -17,16 +49,209 @@ impl<'a> Context<'a> {
             .map(|idx| self.worker_accounts[idx].clone())
     }
 
-    pub fn get_actions(&self, chain: &String, exe: &str) -> Option<Box<dyn CallBuilder>> {
-        pink_extension::debug!("Lookup actions on {:?}", &chain);
-        let actions: Vec<(String, Box<dyn CallBuilder>)> = self.registry.create_actions(chain);
-        actions
-            .iter()
-            .position(|e| e.0.to_lowercase() == exe.to_lowercase())
-            .map(|idx| dyn_clone::clone_box(&*actions[idx].1))
+    pub fn get_actions(
+        &self,
+        chain: &String,
+        exe: &str,
+    ) -> Result<Box<dyn CallBuilder>, &'static str> {
+        let key = (chain.clone(), exe.to_lowercase());
+        if let Some(action) = self.action_cache.0.borrow().get(&key) {
+            return Ok(dyn_clone::clone_box(&**action));
+        }
+        pink_extension::debug!("Build action {:?} on {:?}", exe, &chain);
+        let action = self.registry.create_action(chain, exe, self.rpc_health)?;
+        self.action_cache
+            .0
+            .borrow_mut()
+            .insert(key, dyn_clone::clone_box(&*action));
+        Ok(action)
+    }
+
+    /// XCM delivery of the message sent by `sent_txs`, looked up with `lookup` if not cached
+    pub fn get_xcm_delivery(
+        &self,
+        sent_txs: &[ExecuteTx],
+        lookup: impl FnOnce() -> Result<Option<XcmDelivery>, &'static str>,
+    ) -> Result<Option<XcmDelivery>, &'static str> {
+        let key: Vec<Vec<u8>> = sent_txs.iter().map(|tx| tx.hash.clone()).collect();
+        if let Some(delivery) = self.xcm_delivery_cache.0.borrow().get(&key) {
+            return Ok(delivery.clone());
+        }
+        let delivery = lookup()?;
+        self.xcm_delivery_cache
+            .0
+            .borrow_mut()
+            .insert(key, delivery.clone());
+        Ok(delivery)
+    }
+
+    /// Result of the transaction sent with `nonce` on `chain`, fetched with `fetch` if not cached
+    pub fn get_tx_status(
+        &self,
+        chain: &str,
+        nonce: u64,
+        sent_txs: &[ExecuteTx],
+        fetch: impl FnOnce() -> Result<Option<TxStatus>, &'static str>,
+    ) -> Result<Option<TxStatus>, &'static str> {
+        let key = (
+            String::from(chain),
+            nonce,
+            sent_txs.iter().map(|tx| tx.hash.clone()).collect(),
+        );
+        if let Some(status) = self.tx_status_cache.0.borrow().get(&key) {
+            return Ok(status.clone());
+        }
+        let status = fetch()?;
+        self.tx_status_cache
+            .0
+            .borrow_mut()
+            .insert(key, status.clone());
+        Ok(status)
     }
 
     pub fn get_action_extra_info(&self, chain: &str, action: &str) -> Option<ActionExtraInfo> {
         self.registry.get_action_extra_info(chain, action)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn context<'a>(registry: &'a Registry, rpc_health: &'a RpcHealth) -> Context<'a> {
+        Context {
+            signer: [0; 32],
+            registry,
+            worker_accounts: vec![],
+            rpc_health,
+            action_cache: ActionCache::default(),
+            xcm_delivery_cache: XcmDeliveryCache::default(),
+            tx_status_cache: TxStatusCache::default(),
+        }
+    }
+
+    #[test]
+    fn test_action_cache() {
+        let registry = Registry::new();
+        let rpc_health = RpcHealth::default();
+        let moonbeam = "Moonbeam".to_string();
+
+        let context0 = context(&registry, &rpc_health);
+        assert!(context0
+            .get_actions(&moonbeam, "moonbeam_stellaswap")
+            .is_ok());
+        // Cached per (chain, id), the id is case insensitive
+        assert!(context0
+            .get_actions(&moonbeam, "MOONBEAM_STELLASWAP")
+            .is_ok());
+        assert_eq!(context0.action_cache.0.borrow().len(), 1);
+        assert!(context0
+            .action_cache
+            .0
+            .borrow()
+            .contains_key(&(moonbeam.clone(), "moonbeam_stellaswap".to_string())));
+        assert!(context0
+            .get_actions(&moonbeam, "moonbeam_transactor")
+            .is_ok());
+        assert_eq!(context0.action_cache.0.borrow().len(), 2);
+
+        // Actions failed to build are not cached
+        assert_eq!(
+            context0.get_actions(&moonbeam, "acala_dex").err(),
+            Some("NoActionFound")
+        );
+        assert_eq!(context0.action_cache.0.borrow().len(), 2);
+
+        // Cache is scoped to one context
+        let context1 = context(&registry, &rpc_health);
+        assert!(context1.action_cache.0.borrow().is_empty());
+        assert!(context1
+            .get_actions(&moonbeam, "moonbeam_stellaswap")
+            .is_ok());
+        assert_eq!(context1.action_cache.0.borrow().len(), 1);
+        assert_eq!(context0.action_cache.0.borrow().len(), 2);
+    }
+
+    #[test]
+    fn test_xcm_delivery_cache() {
+        let registry = Registry::new();
+        let rpc_health = RpcHealth::default();
+        let context = context(&registry, &rpc_health);
+        let sent_tx = |hash: u8| ExecuteTx {
+            step_index: 0,
+            nonce: 1,
+            hash: vec![hash; 32],
+            kind: crate::task::TxKind::Execute,
+            sent_at: 0,
+            sent_block: None,
+            dest_block: None,
+        };
+        let lookups = RefCell::new(0);
+        let lookup = || {
+            *lookups.borrow_mut() += 1;
+            Ok(Some(XcmDelivery::Succeeded { received: Some(1) }))
+        };
+        let delivery = context.get_xcm_delivery(&[sent_tx(1)], lookup).unwrap();
+        assert_eq!(
+            context.get_xcm_delivery(&[sent_tx(1)], lookup),
+            Ok(delivery)
+        );
+        assert_eq!(*lookups.borrow(), 1);
+        // Messages sent by other transactions are looked up separately
+        assert!(context.get_xcm_delivery(&[sent_tx(2)], lookup).is_ok());
+        assert_eq!(*lookups.borrow(), 2);
+
+        // Failed lookups are not cached
+        let failed = || Err("BlockNotFound");
+        assert_eq!(
+            context.get_xcm_delivery(&[sent_tx(3)], failed),
+            Err("BlockNotFound")
+        );
+        assert_eq!(context.xcm_delivery_cache.0.borrow().len(), 2);
+    }
+
+    #[test]
+    fn test_tx_status_cache() {
+        let registry = Registry::new();
+        let rpc_health = RpcHealth::default();
+        let context = context(&registry, &rpc_health);
+        let sent_txs = [ExecuteTx {
+            step_index: 0,
+            nonce: 1,
+            hash: vec![1; 32],
+            kind: crate::task::TxKind::Execute,
+            sent_at: 0,
+            sent_block: None,
+            dest_block: None,
+        }];
+        let fetches = RefCell::new(0);
+        let fetch = || {
+            *fetches.borrow_mut() += 1;
+            Ok(Some(TxStatus {
+                result: true,
+                block_number: 100,
+            }))
+        };
+        let status = context
+            .get_tx_status("Ethereum", 1, &sent_txs, fetch)
+            .unwrap();
+        assert_eq!(
+            context.get_tx_status("Ethereum", 1, &sent_txs, fetch),
+            Ok(status)
+        );
+        assert_eq!(*fetches.borrow(), 1);
+        // Transactions of other chains or nonces are fetched separately
+        assert!(context
+            .get_tx_status("Moonbeam", 1, &sent_txs, fetch)
+            .is_ok());
+        assert!(context.get_tx_status("Ethereum", 2, &[], fetch).is_ok());
+        assert_eq!(*fetches.borrow(), 3);
+
+        // Failed fetches are not cached
+        assert_eq!(
+            context.get_tx_status("Ethereum", 3, &sent_txs, || Err("FetchFailed")),
+            Err("FetchFailed")
+        );
+        assert_eq!(context.tx_status_cache.0.borrow().len(), 3);
+    }
+}
@@ -0,0 +1,506 @@
+use crate::chain::Chain;
+use crate::price;
+use alloc::{vec, vec::Vec};
+use pink_web3::{
+    api::Eth,
+    contract::Options,
+    transports::{resolve_ready, PinkHttp},
+    types::{BlockNumber, TransactionId, H256, U256, U64},
+};
+
+/// Number of recent blocks sampled with `eth_feeHistory`
+const FEE_HISTORY_BLOCK_COUNT: u64 = 10;
+/// EIP-2718 transaction type of EIP-1559 transactions
+const EIP1559_TX_TYPE: u64 = 2;
+/// Fee bump of a replacement transaction, nodes reject replacements bumped less than 10%
+const REPLACEMENT_FEE_PERCENT: u32 = 125;
+
+/// How fast we want a transaction to be included
+#[derive(Clone, Debug, Default, PartialEq, Eq, scale::Encode, scale::Decode)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+pub enum Urgency {
+    Low,
+    #[default]
+    Normal,
+    High,
+}
+
+impl Urgency {
+    /// Percentile of the priority fees paid in recent blocks that we tip with
+    fn reward_percentile(&self) -> f64 {
+        match self {
+            Urgency::Low => 10.0,
+            Urgency::Normal => 50.0,
+            Urgency::High => 90.0,
+        }
+    }
+
+    /// Percentage of the next block base fee reserved in `maxFeePerGas`, it keeps the
+    /// transaction includable when base fee keeps rising in the following blocks
+    fn base_fee_percent(&self) -> u32 {
+        match self {
+            Urgency::Low => 125,
+            Urgency::Normal => 200,
+            Urgency::High => 300,
+        }
+    }
+
+    /// Percentage applied on `eth_gasPrice` for chains only accept legacy transactions
+    fn gas_price_percent(&self) -> u32 {
+        match self {
+            Urgency::Low => 100,
+            Urgency::Normal => 110,
+            Urgency::High => 130,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, scale::Encode, scale::Decode)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+pub struct FeeStrategy {
+    pub urgency: Urgency,
+    /// Send type-2 transactions, disable it on chains that haven't activated EIP-1559
+    pub eip1559: bool,
+    /// The most a single transaction is allowed to cost, the USD amount is the value / 10000.
+    /// Transactions that would cost more than it will not be submitted
+    pub max_tx_fee_in_usd: Option<u32>,
+    /// Gas limit is set to estimated gas * gas_limit_percent / 100
+    pub gas_limit_percent: u32,
+    /// A transaction still pending after this many seconds will be replaced with a bumped fee
+    pub replace_after_sec: u64,
+}
+
+impl Default for FeeStrategy {
+    fn default() -> Self {
+        Self {
+            urgency: Urgency::Normal,
+            eip1559: true,
+            max_tx_fee_in_usd: None,
+            // Give 50% gas for potentially gas exceeding
+            gas_limit_percent: 150,
+            replace_after_sec: 180,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum GasFee {
+    Legacy {
+        gas_price: U256,
+    },
+    Eip1559 {
+        // Base fee of the next block
+        base_fee: U256,
+        max_fee_per_gas: U256,
+        max_priority_fee_per_gas: U256,
+    },
+}
+
+impl GasFee {
+    /// Price per gas the transaction is expected to pay
+    pub fn expected_gas_price(&self) -> U256 {
+        match self {
+            GasFee::Legacy { gas_price } => *gas_price,
+            GasFee::Eip1559 {
+                base_fee,
+                max_fee_per_gas,
+                max_priority_fee_per_gas,
+            } => (*base_fee + *max_priority_fee_per_gas).min(*max_fee_per_gas),
+        }
+    }
+
+    /// The least fee a transaction replacing the one sent with this fee should pay
+    pub fn bump(&self) -> GasFee {
+        match self {
+            GasFee::Legacy { gas_price } => GasFee::Legacy {
+                gas_price: percent_of(*gas_price, REPLACEMENT_FEE_PERCENT),
+            },
+            GasFee::Eip1559 {
+                base_fee,
+                max_fee_per_gas,
+                max_priority_fee_per_gas,
+            } => GasFee::Eip1559 {
+                base_fee: *base_fee,
+                max_fee_per_gas: percent_of(*max_fee_per_gas, REPLACEMENT_FEE_PERCENT),
+                max_priority_fee_per_gas: percent_of(
+                    *max_priority_fee_per_gas,
+                    REPLACEMENT_FEE_PERCENT,
+                ),
+            },
+        }
+    }
+
+    /// Raise the fee to be no less than `floor`, both fee cap and tip are compared
+    pub fn raise_to(self, floor: &GasFee) -> GasFee {
+        let (floor_max_fee, floor_priority_fee) = floor.fee_caps();
+        match self {
+            GasFee::Legacy { gas_price } => GasFee::Legacy {
+                gas_price: gas_price.max(floor_max_fee),
+            },
+            GasFee::Eip1559 {
+                base_fee,
+                max_fee_per_gas,
+                max_priority_fee_per_gas,
+            } => {
+                let max_priority_fee_per_gas = max_priority_fee_per_gas.max(floor_priority_fee);
+                GasFee::Eip1559 {
+                    base_fee,
+                    max_fee_per_gas: max_fee_per_gas
+                        .max(floor_max_fee)
+                        .max(max_priority_fee_per_gas),
+                    max_priority_fee_per_gas,
+                }
+            }
+        }
+    }
+
+    // [max_fee_per_gas, max_priority_fee_per_gas], legacy transactions pay all gas price as tip
+    fn fee_caps(&self) -> (U256, U256) {
+        match self {
+            GasFee::Legacy { gas_price } => (*gas_price, *gas_price),
+            GasFee::Eip1559 {
+                max_fee_per_gas,
+                max_priority_fee_per_gas,
+                ..
+            } => (*max_fee_per_gas, *max_priority_fee_per_gas),
+        }
+    }
+
+    fn apply(&self, opt: &mut Options) {
+        match self {
+            GasFee::Legacy { gas_price } => {
+                opt.gas_price = Some(*gas_price);
+            }
+            GasFee::Eip1559 {
+                max_fee_per_gas,
+                max_priority_fee_per_gas,
+                ..
+            } => {
+                opt.transaction_type = Some(U64::from(EIP1559_TX_TYPE));
+                opt.max_fee_per_gas = Some(*max_fee_per_gas);
+                opt.max_priority_fee_per_gas = Some(*max_priority_fee_per_gas);
+            }
+        }
+    }
+}
+
+impl FeeStrategy {
+    /// Suggest gas fee according to fees paid in recent blocks
+    pub fn suggest_fee(&self, eth: &Eth<PinkHttp>) -> Result<GasFee, &'static str> {
+        if !self.eip1559 {
+            let gas_price = resolve_ready(eth.gas_price()).or(Err("FailedToGetGasPrice"))?;
+            return Ok(GasFee::Legacy {
+                gas_price: percent_of(gas_price, self.urgency.gas_price_percent()),
+            });
+        }
+
+        let history = resolve_ready(eth.fee_history(
+            U256::from(FEE_HISTORY_BLOCK_COUNT),
+            BlockNumber::Latest,
+            Some(vec![self.urgency.reward_percentile()]),
+        ))
+        .map_err(|e| {
+            pink_extension::error!("Failed to get fee history with error: {:?}", e);
+            "FailedToGetFeeHistory"
+        })?;
+        // The last one is the base fee of the next block
+        let base_fee = *history.base_fee_per_gas.last().ok_or("EmptyFeeHistory")?;
+        let rewards: Vec<U256> = history
+            .reward
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|block_rewards| block_rewards.first().cloned())
+            .collect();
+        let priority_fee = if rewards.is_empty() {
+            U256::zero()
+        } else {
+            rewards.iter().fold(U256::zero(), |acc, r| acc + *r) / U256::from(rewards.len())
+        };
+
+        Ok(GasFee::Eip1559 {
+            base_fee,
+            max_fee_per_gas: percent_of(base_fee, self.urgency.base_fee_percent()) + priority_fee,
+            max_priority_fee_per_gas: priority_fee,
+        })
+    }
+
+    /// Suggest gas fee for a transaction replacing `replaced_tx`. The fee of the replaced
+    /// transaction is unknown if it has been dropped by the node, then we just bump current suggestion
+    pub fn suggest_replacement_fee(
+        &self,
+        eth: &Eth<PinkHttp>,
+        replaced_tx: Option<&[u8]>,
+    ) -> Result<GasFee, &'static str> {
+        let suggested = self.suggest_fee(eth)?;
+        let replaced = match replaced_tx {
+            Some(tx_hash) => sent_tx_fee(eth, tx_hash)?,
+            None => None,
+        }
+        .unwrap_or_else(|| suggested.clone());
+        Ok(suggested.raise_to(&replaced.bump()))
+    }
+
+    pub fn gas_limit(&self, estimated_gas: U256) -> U256 {
+        percent_of(estimated_gas, self.gas_limit_percent)
+    }
+
+    /// Lower the fee to keep the transaction cost under `max_tx_fee_in_usd`, returns error if
+    /// the transaction can not afford even the expected gas price
+    pub fn cap_fee(
+        &self,
+        chain: &Chain,
+        fee: GasFee,
+        gas_limit: U256,
+    ) -> Result<GasFee, &'static str> {
+        let Some(max_tx_fee_in_usd) = self.max_tx_fee_in_usd else {
+            return Ok(fee);
+        };
+        let native_asset_price =
+            price::get_price(&chain.name, &chain.native_asset).ok_or("MissingPriceData")?;
+        if gas_limit.is_zero() || native_asset_price == 0 {
+            return Ok(fee);
+        }
+        // TODO: here we presume the decimals of all EVM native asset is 18, but we should get it from asset info
+        let max_gas_price = U256::from(max_tx_fee_in_usd) * U256::from(10u128.pow(18))
+            / (gas_limit * U256::from(native_asset_price));
+        if fee.expected_gas_price() > max_gas_price {
+            pink_extension::error!(
+                "Gas fee {:?} exceeds the cap of {:?}, max gas price allowed: {:?}",
+                &fee,
+                &chain.name,
+                max_gas_price
+            );
+            return Err("TxFeeExceedsCap");
+        }
+
+        Ok(match fee {
+            GasFee::Legacy { gas_price } => GasFee::Legacy { gas_price },
+            GasFee::Eip1559 {
+                base_fee,
+                max_fee_per_gas,
+                max_priority_fee_per_gas,
+            } => GasFee::Eip1559 {
+                base_fee,
+                max_fee_per_gas: max_fee_per_gas.min(max_gas_price),
+                max_priority_fee_per_gas: max_priority_fee_per_gas.min(max_gas_price),
+            },
+        })
+    }
+}
+
+/// Build options of a transaction sent to an EVM chain according to the fee strategy of the chain.
+///
+/// Every EVM transaction the executor submits should take its options from here
+pub fn tx_options(
+    chain: &Chain,
+    eth: &Eth<PinkHttp>,
+    estimated_gas: U256,
+    nonce: Option<u64>,
+    value: Option<U256>,
+) -> Result<Options, &'static str> {
+    let fee = chain.fee_strategy.suggest_fee(eth)?;
+    options_with_fee(chain, fee, estimated_gas, nonce, value)
+}
+
+/// Build options of a transaction replacing a pending one with the same `nonce`, fee is bumped
+/// over `replaced_tx` so that nodes accept the replacement
+pub fn replacement_tx_options(
+    chain: &Chain,
+    eth: &Eth<PinkHttp>,
+    estimated_gas: U256,
+    nonce: u64,
+    value: Option<U256>,
+    replaced_tx: Option<&[u8]>,
+) -> Result<Options, &'static str> {
+    let fee = chain
+        .fee_strategy
+        .suggest_replacement_fee(eth, replaced_tx)?;
+    options_with_fee(chain, fee, estimated_gas, Some(nonce), value)
+}
+
+/// Fee paid by a sent transaction, returns `None` if the transaction is unknown to the node
+pub fn sent_tx_fee(eth: &Eth<PinkHttp>, tx_hash: &[u8]) -> Result<Option<GasFee>, &'static str> {
+    if tx_hash.len() != 32 {
+        return Err("InvalidTxHash");
+    }
+    let tx = resolve_ready(eth.transaction(TransactionId::Hash(H256::from_slice(tx_hash))))
+        .map_err(|e| {
+            pink_extension::error!("Failed to get transaction with error: {:?}", e);
+            "FailedToGetTransaction"
+        })?;
+
+    Ok(tx.and_then(
+        |tx| match (tx.max_fee_per_gas, tx.max_priority_fee_per_gas) {
+            (Some(max_fee_per_gas), Some(max_priority_fee_per_gas)) => Some(GasFee::Eip1559 {
+                base_fee: U256::zero(),
+                max_fee_per_gas,
+                max_priority_fee_per_gas,
+            }),
+            _ => tx.gas_price.map(|gas_price| GasFee::Legacy { gas_price }),
+        },
+    ))
+}
+
+fn options_with_fee(
+    chain: &Chain,
+    fee: GasFee,
+    estimated_gas: U256,
+    nonce: Option<u64>,
+    value: Option<U256>,
+) -> Result<Options, &'static str> {
+    let strategy = &chain.fee_strategy;
+    let gas = strategy.gas_limit(estimated_gas);
+    let fee = strategy.cap_fee(chain, fee, gas)?;
+    pink_extension::debug!(
+        "Apply gas fee {:?} with gas limit {:?} to transaction on {:?}",
+        &fee,
+        gas,
+        &chain.name
+    );
+
+    Ok(Options::with(|opt| {
+        opt.gas = Some(gas);
+        opt.nonce = nonce.map(U256::from);
+        opt.value = value;
+        fee.apply(opt);
+    }))
+}
+
+fn percent_of(value: U256, percent: u32) -> U256 {
+    value * U256::from(percent) / U256::from(100)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chain::ChainType;
+    use alloc::string::String;
+
+    fn ethereum(max_tx_fee_in_usd: Option<u32>) -> Chain {
+        Chain {
+            name: String::from("Ethereum"),
+            chain_type: ChainType::Evm,
+            native_asset: [0; 20].to_vec(),
+            fee_strategy: FeeStrategy {
+                max_tx_fee_in_usd,
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_cap_fee() {
+        // 20 gwei base fee, 2 gwei tip
+        let fee = GasFee::Eip1559 {
+            base_fee: U256::from(20_000_000_000u64),
+            max_fee_per_gas: U256::from(42_000_000_000u64),
+            max_priority_fee_per_gas: U256::from(2_000_000_000u64),
+        };
+        let gas_limit = U256::from(100_000);
+
+        // No cap configured
+        let chain = ethereum(None);
+        assert_eq!(
+            chain
+                .fee_strategy
+                .cap_fee(&chain, fee.clone(), gas_limit)
+                .unwrap(),
+            fee
+        );
+
+        // With ETH at 2000 USD, 10 USD allows 50 gwei per gas
+        let chain = ethereum(Some(100_000));
+        assert_eq!(
+            chain
+                .fee_strategy
+                .cap_fee(&chain, fee.clone(), gas_limit)
+                .unwrap(),
+            fee
+        );
+
+        // 6 USD allows 30 gwei per gas, max fee is lowered
+        let chain = ethereum(Some(60_000));
+        assert_eq!(
+            chain
+                .fee_strategy
+                .cap_fee(&chain, fee.clone(), gas_limit)
+                .unwrap(),
+            GasFee::Eip1559 {
+                base_fee: U256::from(20_000_000_000u64),
+                max_fee_per_gas: U256::from(30_000_000_000u64),
+                max_priority_fee_per_gas: U256::from(2_000_000_000u64),
+            }
+        );
+
+        // 4 USD allows 20 gwei per gas, not enough to pay base fee and tip
+        let chain = ethereum(Some(40_000));
+        assert_eq!(
+            chain.fee_strategy.cap_fee(&chain, fee, gas_limit),
+            Err("TxFeeExceedsCap")
+        );
+    }
+
+    #[test]
+    fn test_replacement_fee() {
+        let pending = GasFee::Eip1559 {
+            base_fee: U256::from(20),
+            max_fee_per_gas: U256::from(42),
+            max_priority_fee_per_gas: U256::from(20),
+        };
+        // Fee market calmed down, replacement still has to pay more than the pending one
+        let suggested = GasFee::Eip1559 {
+            base_fee: U256::from(10),
+            max_fee_per_gas: U256::from(22),
+            max_priority_fee_per_gas: U256::from(2),
+        };
+        assert_eq!(
+            suggested.raise_to(&pending.bump()),
+            GasFee::Eip1559 {
+                base_fee: U256::from(10),
+                max_fee_per_gas: U256::from(52),
+                max_priority_fee_per_gas: U256::from(25),
+            }
+        );
+
+        // Replacing a legacy transaction requires tip no less than its gas price
+        let pending = GasFee::Legacy {
+            gas_price: U256::from(40),
+        };
+        assert_eq!(
+            suggested.raise_to(&pending.bump()),
+            GasFee::Eip1559 {
+                base_fee: U256::from(10),
+                max_fee_per_gas: U256::from(50),
+                max_priority_fee_per_gas: U256::from(50),
+            }
+        );
+    }
+
+    #[test]
+    fn test_apply_fee_to_options() {
+        let mut opt = Options::default();
+        GasFee::Eip1559 {
+            base_fee: U256::from(10),
+            max_fee_per_gas: U256::from(21),
+            max_priority_fee_per_gas: U256::from(1),
+        }
+        .apply(&mut opt);
+        assert_eq!(opt.transaction_type, Some(U64::from(2)));
+        assert_eq!(opt.max_fee_per_gas, Some(U256::from(21)));
+        assert_eq!(opt.max_priority_fee_per_gas, Some(U256::from(1)));
+        assert_eq!(opt.gas_price, None);
+
+        let mut opt = Options::default();
+        GasFee::Legacy {
+            gas_price: U256::from(10),
+        }
+        .apply(&mut opt);
+        assert_eq!(opt.transaction_type, None);
+        assert_eq!(opt.gas_price, Some(U256::from(10)));
+        assert_eq!(
+            FeeStrategy::default().gas_limit(U256::from(100)),
+            U256::from(150)
+        );
+    }
+}
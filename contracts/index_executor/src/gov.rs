@@ -1,103 +1,164 @@
+use crate::chain::Chain;
+use crate::gas;
+use crate::rpc::RpcHealth;
 use crate::task::TaskId;
-use alloc::string::String;
-use alloc::vec::Vec;
+use crate::tx;
+use alloc::{vec, vec::Vec};
 use pink_web3::{
     api::{Eth, Namespace},
-    contract::{Contract, Options},
+    contract::{tokens::Tokenize, Contract, Options},
     keys::pink::KeyPair,
     signing::Key,
     transports::{resolve_ready, PinkHttp},
     types::{Address, U256},
 };
 
+/// Gas cost of a plain transfer
+const TRANSFER_GAS: u64 = 21000;
+
 pub struct WorkerGov;
 
 impl WorkerGov {
     pub fn drop_task(
         worker_key: [u8; 32],
-        endpoint: String,
-        handler: Address,
+        chain: &Chain,
         id: TaskId,
+        health: &RpcHealth,
     ) -> Result<Vec<u8>, &'static str> {
-        let transport = Eth::new(PinkHttp::new(endpoint));
-        let handler_contract =
-            Contract::from_json(transport, handler, include_bytes!("./abi/handler.json"))
-                .or(Err("ConstructContractFailed"))?;
-        let worker = KeyPair::from(worker_key);
-
-        let gas = resolve_ready(handler_contract.estimate_gas(
-            "drop",
-            id,
-            worker.address(),
-            Options::default(),
-        ))
-        .or(Err("GasEstimateFailed"))?;
+        let handler = Address::from_slice(&chain.handler_contract);
+        let signed_tx = chain.with_rpc(health, |endpoint| {
+            let eth = Eth::new(PinkHttp::new(endpoint));
+            let handler_contract =
+                Contract::from_json(eth.clone(), handler, include_bytes!("./abi/handler.json"))
+                    .or(Err("ConstructContractFailed"))?;
+            let worker = KeyPair::from(worker_key);
 
-        // Submit the `approve` transaction
-        let tx_id = resolve_ready(handler_contract.signed_call(
-            "drop",
-            id,
-            Options::with(|opt| {
-                opt.gas = Some(gas);
-            }),
-            worker,
-        ))
-        .or(Err("DropTaskSubmitFailed"))?;
+            let gas = resolve_ready(handler_contract.estimate_gas(
+                "drop",
+                id,
+                worker.address(),
+                Options::default(),
+            ))
+            .or(Err("GasEstimateFailed"))?;
+
+            let data = handler_contract
+                .abi()
+                .function("drop")
+                .and_then(|function| function.encode_input(&id.into_tokens()))
+                .or(Err("EncodeParamError"))?;
+            tx::sign_tx(
+                &eth,
+                handler,
+                data,
+                gas::tx_options(chain, &eth, gas, None, None)?,
+                worker,
+            )
+        })?;
+        // Submit the `drop` transaction
+        let tx_id = tx::send_raw_tx(chain, &signed_tx, health).or(Err("DropTaskSubmitFailed"))?;
         pink_extension::info!(
             "Submit transaction to do task drop, task {:?} , tx id: {:?}",
             hex::encode(id),
-            hex::encode(tx_id.clone().as_bytes())
+            hex::encode(&tx_id)
         );
 
-        Ok(tx_id.as_bytes().to_vec())
+        Ok(tx_id)
     }
 
     pub fn erc20_approve(
         worker_key: [u8; 32],
-        endpoint: String,
+        chain: &Chain,
         token: Address,
         spender: Address,
         amount: u128,
+        health: &RpcHealth,
     ) -> Result<Vec<u8>, &'static str> {
-        let transport = Eth::new(PinkHttp::new(endpoint));
-        let erc20_token = Contract::from_json(transport, token, include_bytes!("./abi/erc20.json"))
-            .or(Err("ConstructContractFailed"))?;
-        let worker = KeyPair::from(worker_key);
-
-        // Estiamte gas before submission
-        let gas = resolve_ready(erc20_token.estimate_gas(
-            "approve",
-            (spender, U256::from(amount)),
-            worker.address(),
-            Options::default(),
-        ))
-        .or(Err("GasEstimateFailed"))?;
+        let signed_tx = chain.with_rpc(health, |endpoint| {
+            let eth = Eth::new(PinkHttp::new(endpoint));
+            let erc20_token =
+                Contract::from_json(eth.clone(), token, include_bytes!("./abi/erc20.json"))
+                    .or(Err("ConstructContractFailed"))?;
+            let worker = KeyPair::from(worker_key);
 
+            // Estiamte gas before submission
+            let gas = resolve_ready(erc20_token.estimate_gas(
+                "approve",
+                (spender, U256::from(amount)),
+                worker.address(),
+                Options::default(),
+            ))
+            .or(Err("GasEstimateFailed"))?;
+
+            let data = erc20_token
+                .abi()
+                .function("approve")
+                .and_then(|function| {
+                    function.encode_input(&(spender, U256::from(amount)).into_tokens())
+                })
+                .or(Err("EncodeParamError"))?;
+            tx::sign_tx(
+                &eth,
+                token,
+                data,
+                gas::tx_options(chain, &eth, gas, None, None)?,
+                worker,
+            )
+        })?;
         // Submit the `approve` transaction
-        let tx_id = resolve_ready(erc20_token.signed_call(
-            "approve",
-            (spender, U256::from(amount)),
-            Options::with(|opt| {
-                opt.gas = Some(gas);
-            }),
-            worker,
-        ))
-        .or(Err("ERC20ApproveSubmitFailed"))?;
+        let tx_id =
+            tx::send_raw_tx(chain, &signed_tx, health).or(Err("ERC20ApproveSubmitFailed"))?;
         pink_extension::info!(
             "Submit transaction to do ERC20 approve, token {:?} , spender ${:?}, amount: {:?}, tx id: {:?}",
             hex::encode(token),
             hex::encode(spender),
             amount,
-            hex::encode(tx_id.clone().as_bytes())
+            hex::encode(&tx_id)
+        );
+
+        Ok(tx_id)
+    }
+
+    /// Cancel the pending transaction with `nonce` by replacing it with a zero-value self-transfer.
+    /// `replaced_tx` is the hash of the pending transaction, used to bump the fee over it
+    pub fn cancel_nonce(
+        worker_key: [u8; 32],
+        chain: &Chain,
+        nonce: u64,
+        replaced_tx: Option<&[u8]>,
+        health: &RpcHealth,
+    ) -> Result<Vec<u8>, &'static str> {
+        let signed_tx = chain.with_rpc(health, |endpoint| {
+            let eth = Eth::new(PinkHttp::new(endpoint));
+            let worker = KeyPair::from(worker_key);
+
+            let options = gas::replacement_tx_options(
+                chain,
+                &eth,
+                U256::from(TRANSFER_GAS),
+                nonce,
+                None,
+                replaced_tx,
+            )?;
+            tx::sign_tx(&eth, worker.address(), vec![], options, worker)
+        })?;
+        // Submit the self-transfer
+        let tx_id = tx::send_raw_tx(chain, &signed_tx, health).or(Err("CancelTxSubmitFailed"))?;
+        pink_extension::info!(
+            "Submit transaction to cancel nonce {:?} on {:?}, tx id: {:?}",
+            nonce,
+            &chain.name,
+            hex::encode(&tx_id)
         );
 
-        Ok(tx_id.as_bytes().to_vec())
+        Ok(tx_id)
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::chain::ChainType;
+    use crate::rpc::RpcEndpoint;
     use crate::utils::ToArray;
     use alloc::string::String;
     use dotenv::dotenv;
@@ -120,9 +181,18 @@ mod tests {
         // Issue command: SECRET_KEY=<private key> cargo test --package index_executor --lib -- gov::tests::test_worker_approve --exact --nocapture
         // Send approve transaction
         // https://goerli.etherscan.io/tx/0x6d711af99d4836c8febe3d27e14bc0ad9b8353d89ebcae3f465a6cc70519e35c
+        let goerli = Chain {
+            name: String::from("Goerli"),
+            endpoints: vec![RpcEndpoint::new(
+                "https://eth-goerli.g.alchemy.com/v2/lLqSMX_1unN9Xrdy_BB9LLZRgbrXwZv2",
+                0,
+            )],
+            chain_type: ChainType::Evm,
+            ..Default::default()
+        };
         let tx_id = WorkerGov::erc20_approve(
             signer,
-            String::from("https://eth-goerli.g.alchemy.com/v2/lLqSMX_1unN9Xrdy_BB9LLZRgbrXwZv2"),
+            &goerli,
             pha_on_goerli,
             spender,
             // 100 PHA
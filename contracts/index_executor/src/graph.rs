@@ -0,0 +1,388 @@
+use crate::actions::{ActionExtraInfo, ActionKind};
+use crate::chain::{Chain, ChainType, ForeignAssetModule};
+use crate::registry::{ActionInfo, Asset, Registry};
+use crate::rpc::RpcEndpoint;
+use crate::tx::XcmEvents;
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
+use serde::{Deserialize, Serialize};
+use sp_runtime::Permill;
+
+/// JSON representation of the registry, which is shared with the off-chain solver.
+///
+/// Locations, addresses and handlers are hex strings with `0x` prefix, decimals are strings.
+/// `graph.json` at the repo root is a graph of this schema. Fee strategy and finality of chains are not part of it, they are
+/// managed by the executor separately.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct Graph {
+    pub chains: Vec<GraphChain>,
+    pub assets: Vec<GraphAsset>,
+    pub actions: Vec<GraphAction>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct GraphEndpoint {
+    pub url: String,
+    pub priority: u8,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct GraphChain {
+    pub id: u32,
+    pub name: String,
+    pub endpoints: Vec<GraphEndpoint>,
+    pub chain_type: ChainType,
+    pub native_asset: String,
+    pub foreign_asset: Option<ForeignAssetModule>,
+    pub handler_contract: String,
+    pub tx_indexer_url: String,
+    #[serde(default)]
+    pub xcm_events: Option<XcmEvents>,
+    #[serde(default)]
+    pub xcm_dest_weight: Option<(u64, u64)>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct GraphAsset {
+    pub chain: String,
+    pub location: String,
+    pub name: String,
+    pub symbol: String,
+    pub decimals: String,
+    pub canonical_id: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct GraphAction {
+    pub chain: String,
+    pub id: String,
+    pub kind: ActionKind,
+    // USD amount is the value / 10000
+    pub extra_proto_fee_in_usd: u32,
+    // USD amount is the value / 10000
+    pub const_proto_fee_in_usd: u32,
+    // In permill
+    pub percentage_proto_fee: u32,
+    pub confirm_time_in_sec: u16,
+}
+
+fn encode_hex(data: &[u8]) -> String {
+    format!("0x{}", hex::encode(data))
+}
+
+fn decode_hex(s: &str) -> Result<Vec<u8>, &'static str> {
+    hex::decode(s.trim_start_matches("0x")).or(Err("InvalidHex"))
+}
+
+impl From<&Chain> for GraphChain {
+    fn from(chain: &Chain) -> Self {
+        Self {
+            id: chain.id,
+            name: chain.name.clone(),
+            endpoints: chain
+                .endpoints
+                .iter()
+                .map(|endpoint| GraphEndpoint {
+                    url: endpoint.url.clone(),
+                    priority: endpoint.priority,
+                })
+                .collect(),
+            chain_type: chain.chain_type.clone(),
+            native_asset: encode_hex(&chain.native_asset),
+            foreign_asset: chain.foreign_asset.clone(),
+            handler_contract: encode_hex(&chain.handler_contract),
+            tx_indexer_url: chain.tx_indexer_url.clone(),
+            xcm_events: chain.xcm_events.clone(),
+            xcm_dest_weight: chain.xcm_dest_weight,
+        }
+    }
+}
+
+impl TryFrom<GraphChain> for Chain {
+    type Error = &'static str;
+
+    fn try_from(chain: GraphChain) -> Result<Self, Self::Error> {
+        Ok(Self {
+            id: chain.id,
+            name: chain.name,
+            endpoints: chain
+                .endpoints
+                .iter()
+                .map(|endpoint| RpcEndpoint::new(&endpoint.url, endpoint.priority))
+                .collect(),
+            chain_type: chain.chain_type,
+            native_asset: decode_hex(&chain.native_asset)?,
+            foreign_asset: chain.foreign_asset,
+            handler_contract: decode_hex(&chain.handler_contract)?,
+            tx_indexer_url: chain.tx_indexer_url,
+            fee_strategy: Default::default(),
+            finality: Default::default(),
+            xcm_events: chain.xcm_events,
+            xcm_dest_weight: chain.xcm_dest_weight,
+        })
+    }
+}
+
+impl From<&Asset> for GraphAsset {
+    fn from(asset: &Asset) -> Self {
+        Self {
+            chain: asset.chain.clone(),
+            location: encode_hex(&asset.location),
+            name: asset.name.clone(),
+            symbol: asset.symbol.clone(),
+            decimals: asset.decimals.to_string(),
+            canonical_id: asset.canonical_id.as_deref().map(encode_hex),
+        }
+    }
+}
+
+impl TryFrom<GraphAsset> for Asset {
+    type Error = &'static str;
+
+    fn try_from(asset: GraphAsset) -> Result<Self, Self::Error> {
+        Ok(Self {
+            chain: asset.chain,
+            name: asset.name,
+            symbol: asset.symbol,
+            location: decode_hex(&asset.location)?,
+            decimals: asset.decimals.parse().or(Err("InvalidDecimals"))?,
+            canonical_id: asset
+                .canonical_id
+                .map(|canonical_id| decode_hex(&canonical_id))
+                .transpose()?,
+        })
+    }
+}
+
+impl From<&ActionInfo> for GraphAction {
+    fn from(action: &ActionInfo) -> Self {
+        Self {
+            chain: action.chain.clone(),
+            id: action.id.clone(),
+            kind: action.kind.clone(),
+            extra_proto_fee_in_usd: action.extra_info.extra_proto_fee_in_usd,
+            const_proto_fee_in_usd: action.extra_info.const_proto_fee_in_usd,
+            percentage_proto_fee: action.extra_info.percentage_proto_fee.deconstruct(),
+            confirm_time_in_sec: action.extra_info.confirm_time_in_sec,
+        }
+    }
+}
+
+impl From<GraphAction> for ActionInfo {
+    fn from(action: GraphAction) -> Self {
+        Self {
+            chain: action.chain,
+            id: action.id,
+            kind: action.kind,
+            extra_info: ActionExtraInfo {
+                extra_proto_fee_in_usd: action.extra_proto_fee_in_usd,
+                const_proto_fee_in_usd: action.const_proto_fee_in_usd,
+                percentage_proto_fee: Permill::from_parts(action.percentage_proto_fee),
+                confirm_time_in_sec: action.confirm_time_in_sec,
+            },
+        }
+    }
+}
+
+impl From<&Registry> for Graph {
+    fn from(registry: &Registry) -> Self {
+        Self {
+            chains: registry.chains.iter().map(Into::into).collect(),
+            assets: registry.assets.iter().map(Into::into).collect(),
+            actions: registry.actions.iter().map(Into::into).collect(),
+        }
+    }
+}
+
+impl TryFrom<Graph> for Registry {
+    type Error = &'static str;
+
+    /// Build a registry from the graph, every chain, asset and action is validated
+    fn try_from(graph: Graph) -> Result<Self, Self::Error> {
+        let mut registry = Registry {
+            chains: vec![],
+            assets: vec![],
+            actions: vec![],
+        };
+        for chain in graph.chains {
+            registry.add_chain(chain.try_into()?)?;
+        }
+        for asset in graph.assets {
+            registry.register_asset(asset.try_into()?)?;
+        }
+        for action in graph.actions {
+            registry.set_action(action.into())?;
+        }
+        Ok(registry)
+    }
+}
+
+/// Parse registry from its JSON representation
+pub fn import(json: &[u8]) -> Result<Registry, &'static str> {
+    let graph: Graph = pink_json::from_slice(json).or(Err("InvalidJson"))?;
+    graph.try_into()
+}
+
+/// Export registry to its JSON representation
+pub fn export(registry: &Registry) -> Result<String, &'static str> {
+    pink_json::to_string(&Graph::from(registry)).or(Err("SerializeFailed"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rpc::RpcHealth;
+
+    #[test]
+    fn test_import_export() {
+        let registry = Registry::new();
+        let json = export(&registry).unwrap();
+        let imported = import(json.as_bytes()).unwrap();
+        assert_eq!(imported.chains.len(), registry.chains.len());
+        assert_eq!(imported.assets.len(), registry.assets.len());
+        assert_eq!(
+            imported.assets[0].canonical_id,
+            registry.assets[0].canonical_id
+        );
+        assert_eq!(
+            imported
+                .get_chain(&"Moonbeam".to_string())
+                .unwrap()
+                .endpoints,
+            registry
+                .get_chain(&"Moonbeam".to_string())
+                .unwrap()
+                .endpoints
+        );
+        // Actions are carried along with their fee information
+        assert_eq!(imported.actions, registry.actions);
+        assert_eq!(
+            imported.get_action_extra_info("Moonbeam", "moonbeam_stellaswap"),
+            registry.get_action_extra_info("Moonbeam", "moonbeam_stellaswap")
+        );
+        assert_eq!(export(&imported).unwrap(), json);
+
+        let json = r#"{
+            "chains": [{
+                "id": 100,
+                "name": "Karura",
+                "endpoints": [{"url": "https://karura-rpc.dwellir.com", "priority": 0}],
+                "chain_type": "Sub",
+                "native_asset": "0x010200411f06080080",
+                "foreign_asset": "OrmlToken",
+                "handler_contract": "0x00",
+                "tx_indexer_url": ""
+            }],
+            "assets": [{
+                "chain": "Karura",
+                "location": "0x010200411f06080081",
+                "name": "Karura USD",
+                "symbol": "kUSD",
+                "decimals": "12"
+            }],
+            "actions": [{
+                "chain": "Karura",
+                "id": "karura_dex",
+                "kind": {"AcalaSwap": {
+                    "hub_token": "KUSD",
+                    "stable_pool": {"pool_id": 0, "token": "KSM", "liquid_token": "LKSM"}
+                }},
+                "extra_proto_fee_in_usd": 0,
+                "const_proto_fee_in_usd": 0,
+                "percentage_proto_fee": 3000,
+                "confirm_time_in_sec": 24
+            }]
+        }"#;
+        let karura = import(json.as_bytes()).unwrap();
+        assert_eq!(karura.assets[0].decimals, 12);
+        assert_eq!(
+            karura
+                .get_action_extra_info("Karura", "karura_dex")
+                .unwrap()
+                .percentage_proto_fee,
+            Permill::from_parts(3000)
+        );
+        assert_eq!(
+            karura
+                .create_actions(&"Karura".to_string(), &RpcHealth::default())
+                .len(),
+            1
+        );
+
+        // Assets must belong to a known chain
+        let json = json.replacen(r#""chain": "Karura""#, r#""chain": "Unknown""#, 1);
+        assert_eq!(import(json.as_bytes()).err(), Some("ChainNotFound"));
+        assert_eq!(import(b"{}").err(), Some("InvalidJson"));
+
+        let mut registry = Registry::new();
+        registry.merge(karura).unwrap();
+        assert!(registry.get_chain(&"Karura".to_string()).is_some());
+        assert!(registry.get_chain(&"Ethereum".to_string()).is_some());
+    }
+
+    #[test]
+    fn test_import_graph_json() {
+        let graph = import(include_bytes!("../../../graph.json")).unwrap();
+        let registry = Registry::new();
+        // Chains and actions of the graph are the ones deployed along with the contract
+        for chain in graph.chains.iter() {
+            let deployed = registry.get_chain(&chain.name).unwrap();
+            assert_eq!(GraphChain::from(&deployed), GraphChain::from(chain));
+        }
+        for action in graph.actions.iter() {
+            assert!(registry.actions.contains(action), "{}", action.id);
+        }
+        assert_eq!(graph.assets.len(), 6);
+        let khala = graph.get_chain(&"Khala".to_string()).unwrap();
+        assert_eq!(
+            graph
+                .get_asset(&khala.name, &khala.native_asset)
+                .map(|asset| asset.symbol),
+            Some("PHA".to_string())
+        );
+    }
+
+    #[test]
+    fn test_import_xcm_config() {
+        use crate::actions::base::xcm::{XcmConfig, XcmFee};
+
+        // XCM config is optional
+        let kind: ActionKind =
+            pink_json::from_slice(br#"{"AstarXtokens": {"dest_parachain": 2035}}"#).unwrap();
+        assert_eq!(
+            kind,
+            ActionKind::AstarXtokens {
+                dest_parachain: 2035,
+                xcm: XcmConfig::default(),
+            }
+        );
+
+        let kind: ActionKind = pink_json::from_slice(
+            br#"{"AstarXtokens": {"dest_parachain": 2035, "xcm": {
+                "fee": {"asset": "0x010100cd1f", "amount": "1000000000000"}
+            }}}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            kind,
+            ActionKind::AstarXtokens {
+                dest_parachain: 2035,
+                xcm: XcmConfig {
+                    fee: Some(XcmFee {
+                        asset: hex::decode("010100cd1f").unwrap(),
+                        amount: 1_000_000_000_000,
+                    }),
+                },
+            }
+        );
+        let json = pink_json::to_string(&kind).unwrap();
+        assert_eq!(
+            pink_json::from_slice::<ActionKind>(json.as_bytes()).unwrap(),
+            kind
+        );
+    }
+}
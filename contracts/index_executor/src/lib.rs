@@ -9,9 +9,12 @@ mod call;
 mod chain;
 mod constants;
 mod context;
+mod gas;
 mod gov;
+mod graph;
 mod price;
 mod registry;
+mod rpc;
 mod step;
 mod storage;
 mod task;
@@ -25,13 +28,15 @@ mod utils;
 #[ink::contract(env = pink_extension::PinkEnvironment)]
 mod index_executor {
     use crate::account::AccountInfo;
-    use crate::chain::ChainType;
+    use crate::chain::{Chain, ChainType};
     use crate::context::Context;
+    use crate::gas::FeeStrategy;
     use crate::gov::WorkerGov;
-    use crate::registry::Registry;
+    use crate::registry::{ActionInfo, Asset, Registry};
+    use crate::rpc::{EndpointStat, RpcEndpoint, RpcHealth};
     use crate::step::{MultiStep, Simulate as StepSimulate, StepSimulateResult};
     use crate::storage::StorageClient;
-    use crate::task::{Task, TaskId, TaskStatus};
+    use crate::task::{Task, TaskId, TaskStatus, TxKind};
     use crate::task_deposit::Solution;
     use crate::task_fetcher::ActivedTaskFetcher;
     use alloc::{string::String, vec, vec::Vec};
@@ -41,8 +46,6 @@ mod index_executor {
     use scale::{Decode, Encode};
     use worker_key_store::KeyStoreRef;
 
-    use pink_web3::ethabi::Address;
-
     #[derive(Encode, Decode, Debug, PartialEq, Eq)]
     #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
     pub enum Error {
@@ -70,6 +73,15 @@ mod index_executor {
         ExecutorPaused,
         ExecutorNotPaused,
         MissingAssetInfo,
+        InvalidEndpoints,
+        ChainAlreadyExist,
+        InvalidChainInfo,
+        AssetAlreadyExist,
+        AssetNotFound,
+        InvalidAssetInfo,
+        InvalidRegistryJson,
+        ActionNotFound,
+        InvalidActionInfo,
     }
 
     type Result<T> = core::result::Result<T, Error>;
@@ -91,6 +103,65 @@ mod index_executor {
     #[ink(event)]
     pub struct WorkerSetToStorage;
 
+    /// Event emitted when a chain is added to the registry.
+    #[ink(event)]
+    pub struct ChainAdded {
+        name: String,
+    }
+
+    /// Event emitted when information of a chain in the registry is changed.
+    #[ink(event)]
+    pub struct ChainUpdated {
+        name: String,
+    }
+
+    /// Event emitted when a chain is removed from the registry.
+    #[ink(event)]
+    pub struct ChainRemoved {
+        name: String,
+    }
+
+    /// Event emitted when an asset is registered.
+    #[ink(event)]
+    pub struct AssetRegistered {
+        chain: String,
+        location: Vec<u8>,
+    }
+
+    /// Event emitted when information of a registered asset is changed.
+    #[ink(event)]
+    pub struct AssetUpdated {
+        chain: String,
+        location: Vec<u8>,
+    }
+
+    /// Event emitted when an asset is unregistered.
+    #[ink(event)]
+    pub struct AssetUnregistered {
+        chain: String,
+        location: Vec<u8>,
+    }
+
+    /// Event emitted when an action is added or updated.
+    #[ink(event)]
+    pub struct ActionUpdated {
+        chain: String,
+        id: String,
+    }
+
+    /// Event emitted when an action is removed.
+    #[ink(event)]
+    pub struct ActionRemoved {
+        chain: String,
+        id: String,
+    }
+
+    /// Event emitted when the registry is replaced or merged from JSON.
+    #[ink(event)]
+    pub struct RegistryImported {
+        merge: bool,
+    }
+
     #[derive(Clone, Encode, Decode, Debug)]
     #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
     pub enum RunningType {
@@ -191,23 +262,133 @@ mod index_executor {
         pub fn update_chain_info(
             &mut self,
             chain: String,
-            endpoint: String,
+            endpoints: Vec<RpcEndpoint>,
             indexer_url: String,
         ) -> Result<()> {
             self.ensure_owner()?;
+            if endpoints.is_empty() || endpoints.iter().any(|endpoint| endpoint.url.is_empty()) {
+                return Err(Error::InvalidEndpoints);
+            }
+
+            let index = self
+                .registry
+                .chains
+                .iter()
+                .position(|x| x.name == chain)
+                .ok_or(Error::ChainNotFound)?;
+            // Replace the whole endpoint list, failover happens between them in order of priority
+            self.registry.chains[index].endpoints = endpoints;
+            self.registry.chains[index].tx_indexer_url = indexer_url;
+            Self::env().emit_event(ChainUpdated { name: chain });
+            Ok(())
+        }
+
+        /// Update the fee strategy applied to transactions sent to an EVM chain
+        #[ink(message)]
+        pub fn update_fee_strategy(&mut self, chain: String, strategy: FeeStrategy) -> Result<()> {
+            self.ensure_owner()?;
 
-            if let Some(index) = self.registry.chains.iter().position(|x| x.name == chain) {
-                // Update the value at the found index
-                self.registry.chains[index].endpoint = endpoint;
-                self.registry.chains[index].tx_indexer_url = indexer_url;
+            let index = self
+                .registry
+                .chains
+                .iter()
+                .position(|x| x.name == chain)
+                .ok_or(Error::ChainNotFound)?;
+            if self.registry.chains[index].chain_type != ChainType::Evm {
+                return Err(Error::UnexpectedChainType);
             }
+            self.registry.chains[index].fee_strategy = strategy;
+            Self::env().emit_event(ChainUpdated { name: chain });
             Ok(())
         }
 
+        /// Add a new chain to the registry
         #[ink(message)]
-        pub fn register_asset(&mut self, asset: crate::registry::Asset) -> Result<()> {
+        pub fn add_chain(&mut self, chain: Chain) -> Result<()> {
             self.ensure_owner()?;
-            self.registry.assets.push(asset);
+            let name = chain.name.clone();
+            self.registry
+                .add_chain(chain)
+                .map_err(Self::registry_error)?;
+            Self::env().emit_event(ChainAdded { name });
+            Ok(())
+        }
+
+        /// Replace all information of the chain with the same name
+        #[ink(message)]
+        pub fn update_chain(&mut self, chain: Chain) -> Result<()> {
+            self.ensure_owner()?;
+            let name = chain.name.clone();
+            self.registry
+                .update_chain(chain)
+                .map_err(Self::registry_error)?;
+            Self::env().emit_event(ChainUpdated { name });
+            Ok(())
+        }
+
+        /// Remove the chain and all assets registered on it
+        #[ink(message)]
+        pub fn remove_chain(&mut self, name: String) -> Result<()> {
+            self.ensure_owner()?;
+            self.registry
+                .remove_chain(&name)
+                .map_err(Self::registry_error)?;
+            Self::env().emit_event(ChainRemoved { name });
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn register_asset(&mut self, asset: Asset) -> Result<()> {
+            self.ensure_owner()?;
+            let (chain, location) = (asset.chain.clone(), asset.location.clone());
+            self.registry
+                .register_asset(asset)
+                .map_err(Self::registry_error)?;
+            Self::env().emit_event(AssetRegistered { chain, location });
+            Ok(())
+        }
+
+        /// Update the registered asset with the same chain and location
+        #[ink(message)]
+        pub fn update_asset(&mut self, asset: Asset) -> Result<()> {
+            self.ensure_owner()?;
+            let (chain, location) = (asset.chain.clone(), asset.location.clone());
+            self.registry
+                .update_asset(asset)
+                .map_err(Self::registry_error)?;
+            Self::env().emit_event(AssetUpdated { chain, location });
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn unregister_asset(&mut self, chain: String, location: Vec<u8>) -> Result<()> {
+            self.ensure_owner()?;
+            self.registry
+                .unregister_asset(&chain, &location)
+                .map_err(Self::registry_error)?;
+            Self::env().emit_event(AssetUnregistered { chain, location });
+            Ok(())
+        }
+
+        /// Add an action, or replace the action with the same chain and id
+        #[ink(message)]
+        pub fn set_action(&mut self, action: ActionInfo) -> Result<()> {
+            self.ensure_owner()?;
+            let (chain, id) = (action.chain.clone(), action.id.clone());
+            self.registry
+                .set_action(action)
+                .map_err(Self::registry_error)?;
+            Self::env().emit_event(ActionUpdated { chain, id });
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn remove_action(&mut self, chain: String, id: String) -> Result<()> {
+            self.ensure_owner()?;
+            self.registry
+                .remove_action(&chain, &id)
+                .map_err(Self::registry_error)?;
+            Self::env().emit_event(ActionRemoved { chain, id });
             Ok(())
         }
 
@@ -238,7 +419,7 @@ mod index_executor {
             amount: u128,
         ) -> Result<()> {
             self.ensure_owner()?;
-            let _ = self.ensure_configured()?;
+            let config = self.ensure_configured()?;
             // To avoid race condiction happened on `nonce`, we should make sure no task will be executed.
             self.ensure_paused()?;
 
@@ -249,13 +430,18 @@ mod index_executor {
             if chain.chain_type != ChainType::Evm {
                 return Err(Error::UnexpectedChainType);
             }
-            WorkerGov::erc20_approve(
-                self.pub_to_prv(worker).ok_or(Error::WorkerNotFound)?,
-                chain.endpoint,
-                token.into(),
-                spender.into(),
-                amount,
-            )
+            let worker_key = self.pub_to_prv(worker).ok_or(Error::WorkerNotFound)?;
+            let client = StorageClient::new(config.db_url.clone(), config.db_token.clone());
+            Self::with_rpc_health(&client, |rpc_health| {
+                WorkerGov::erc20_approve(
+                    worker_key,
+                    &chain,
+                    token.into(),
+                    spender.into(),
+                    amount,
+                    rpc_health,
+                )
+            })
             .log_err("failed to submit worker approve tx")
             .or(Err(Error::FailedToSendTransaction))?;
             Ok(())
@@ -264,7 +450,7 @@ mod index_executor {
         #[ink(message)]
         pub fn worker_drop_task(&self, worker: [u8; 32], chain: String, id: TaskId) -> Result<()> {
             self.ensure_owner()?;
-            let _ = self.ensure_configured()?;
+            let config = self.ensure_configured()?;
 
             // To avoid race condiction happened on `nonce`, we should make sure no task will be executed.
             self.ensure_paused()?;
@@ -277,18 +463,82 @@ mod index_executor {
             if chain.chain_type != ChainType::Evm {
                 return Err(Error::UnexpectedChainType);
             }
-            WorkerGov::drop_task(
-                self.pub_to_prv(worker).ok_or(Error::WorkerNotFound)?,
-                chain.endpoint,
-                Address::from_slice(&chain.handler_contract),
-                id,
-            )
+            let worker_key = self.pub_to_prv(worker).ok_or(Error::WorkerNotFound)?;
+            let client = StorageClient::new(config.db_url.clone(), config.db_token.clone());
+            Self::with_rpc_health(&client, |rpc_health| {
+                WorkerGov::drop_task(worker_key, &chain, id, rpc_health)
+            })
             .log_err("failed to submit worker drop task tx")
             .or(Err(Error::FailedToSendTransaction))?;
 
             Ok(())
         }
 
+        /// Cancel the pending transaction with `nonce` sent by the worker on an EVM chain
+        /// by replacing it with a zero-value self-transfer.
+        ///
+        /// If the transaction belongs to the task being executed by the worker, the cancellation
+        /// is recorded and the task will wait, call `retry` after the cancellation confirmed.
+        #[ink(message)]
+        pub fn worker_cancel_tx(&self, worker: [u8; 32], chain: String, nonce: u64) -> Result<()> {
+            self.ensure_owner()?;
+            let config = self.ensure_configured()?;
+            // To avoid race condiction happened on `nonce`, we should make sure no task will be executed.
+            self.ensure_paused()?;
+
+            let chain = self
+                .registry
+                .get_chain(&chain)
+                .ok_or(Error::ChainNotFound)?;
+            if chain.chain_type != ChainType::Evm {
+                return Err(Error::UnexpectedChainType);
+            }
+
+            let client = StorageClient::new(config.db_url.clone(), config.db_token.clone());
+            let mut task = match client
+                .read::<TaskId>(&worker)
+                .map_err(|_| Error::FailedToReadStorage)?
+            {
+                Some(task_id) => {
+                    Task::read(&client, &task_id).map_err(|_| Error::FailedToReadStorage)?
+                }
+                None => None,
+            };
+            let step_index = task
+                .as_ref()
+                .and_then(|task| task.step_index_by_nonce(&chain.name, nonce));
+            // Bump fee over the latest transaction sent with the nonce
+            let replaced_tx = task.as_ref().and_then(|task| {
+                task.execute_txs
+                    .iter()
+                    .rev()
+                    .find(|tx| Some(tx.step_index) == step_index && tx.nonce == nonce)
+                    .map(|tx| tx.hash.clone())
+            });
+
+            let worker_key = self.pub_to_prv(worker).ok_or(Error::WorkerNotFound)?;
+            let tx_id = Self::with_rpc_health(&client, |rpc_health| {
+                WorkerGov::cancel_nonce(
+                    worker_key,
+                    &chain,
+                    nonce,
+                    replaced_tx.as_deref(),
+                    rpc_health,
+                )
+            })
+            .log_err("failed to submit worker cancel tx")
+            .or(Err(Error::FailedToSendTransaction))?;
+
+            if let (Some(task), Some(step_index)) = (task.as_mut(), step_index) {
+                task.record_execute_tx(step_index, nonce, tx_id, TxKind::Cancel, None, None);
+                client
+                    .update(task.id.as_ref(), &task.encode())
+                    .map_err(|_| Error::FailedToUploadTask)?;
+            }
+
+            Ok(())
+        }
+
         #[ink(message)]
         pub fn upload_solution(&self, id: TaskId, solution: Vec<u8>) -> Result<()> {
             self.ensure_running()?;
@@ -322,38 +572,43 @@ mod index_executor {
                 Decode::decode(&mut solution.as_slice()).or(Err(Error::FailedToDecodeSolution))?;
 
             let signer: [u8; 32] = self.pub_to_prv(worker).ok_or(Error::WorkerNotFound)?;
-            let context = Context {
-                signer,
-                worker_accounts: self.worker_accounts.clone(),
-                registry: &self.registry,
-            };
-            let mut simulate_results: Vec<StepSimulateResult> = vec![];
-            for multi_step_input in solution.iter() {
-                let mut multi_step: MultiStep = multi_step_input
-                    .clone()
-                    .try_into()
-                    .or(Err(Error::InvalidSolutionData))?;
-                let asset_location = multi_step.as_single_step().spend_asset;
-                let asset_info = context
-                    .registry
-                    .get_asset(&multi_step.as_single_step().source_chain, &asset_location)
-                    .ok_or(Error::MissingAssetInfo)?;
-                // Set spend asset 0.0001
-                multi_step.set_spend(10u128.pow(asset_info.decimals as u32) / 10000);
-                let step_simulate_result = multi_step
-                    .simulate(&Context {
-                        signer,
-                        registry: &self.registry,
-                        worker_accounts: self.worker_accounts.clone(),
-                    })
-                    .map_err(|err| {
+            let config = self.ensure_configured()?;
+            let client = StorageClient::new(config.db_url.clone(), config.db_token.clone());
+            Self::with_rpc_health(&client, |rpc_health| {
+                let context = Context {
+                    signer,
+                    worker_accounts: self.worker_accounts.clone(),
+                    registry: &self.registry,
+                    rpc_health,
+                    action_cache: Default::default(),
+                    xcm_delivery_cache: Default::default(),
+                    tx_status_cache: Default::default(),
+                };
+                let mut simulate_results: Vec<StepSimulateResult> = vec![];
+                for multi_step_input in solution.iter() {
+                    let mut multi_step: MultiStep = multi_step_input
+                        .clone()
+                        .try_into()
+                        .or(Err(Error::InvalidSolutionData))?;
+                    multi_step
+                        .check_assets(&context)
+                        .or(Err(Error::InvalidSolutionData))?;
+                    let asset_location = multi_step.as_single_step().spend_asset;
+                    let asset_info = context
+                        .registry
+                        .get_asset(&multi_step.as_single_step().source_chain, &asset_location)
+                        .ok_or(Error::MissingAssetInfo)?;
+                    // Set spend asset 0.0001
+                    multi_step.set_spend(10u128.pow(asset_info.decimals as u32) / 10000);
+                    let step_simulate_result = multi_step.simulate(&context).map_err(|err| {
                         pink_extension::error!("Solution simulation failed with error: {}", err);
                         Error::FailedToSimulateSolution
                     })?;
-                simulate_results.push(step_simulate_result);
-            }
+                    simulate_results.push(step_simulate_result);
+                }
 
-            Ok(simulate_results)
+                Ok(simulate_results)
+            })
         }
 
         #[ink(message)]
@@ -363,14 +618,12 @@ mod index_executor {
             let config = self.ensure_configured()?;
             let client = StorageClient::new(config.db_url.clone(), config.db_token.clone());
 
-            match running_type {
+            Self::with_rpc_health(&client, |rpc_health| match running_type {
                 RunningType::Fetch(source_chain, worker) => {
-                    self.fetch_task(&client, &source_chain, worker)?
+                    self.fetch_task(&client, &source_chain, worker, rpc_health)
                 }
-                RunningType::Execute(worker) => self.execute_task(&client, worker)?,
-            };
-
-            Ok(())
+                RunningType::Execute(worker) => self.execute_task(&client, worker, rpc_health),
+            })
         }
 
         /// Re-apply nonce for task steps from current execution index, then re-run the failed step
@@ -381,32 +634,37 @@ mod index_executor {
             self.ensure_running()?;
             let config = self.ensure_configured()?;
             let client = StorageClient::new(config.db_url.clone(), config.db_token.clone());
-            let mut task = client
-                .read::<Task>(&id)
+            let mut task = Task::read(&client, &id)
                 .map_err(|_| Error::FailedToReadStorage)?
                 .ok_or(Error::TaskNotFoundInStorage)?;
 
             if let TaskStatus::Executing(execute_index, _) = task.status {
-                let context = Context {
-                    signer: self.pub_to_prv(task.worker).unwrap(),
-                    worker_accounts: self.worker_accounts.clone(),
-                    registry: &self.registry,
-                };
-                task.retry_counter = 0;
-                task.reapply_nonce(execute_index as u64, &context, &client)
-                    .map_err(|_| Error::FailedToReApplyNonce)?;
-                pink_extension::info!(
-                    "Step nonce re-applied from execution index: {:?}",
-                    &execute_index
-                );
-                // Now re-run the step
-                let _ = task
-                    .execute_step(&context, &client)
-                    .map_err(|_| Error::FailedToReRunTask)?;
-                // Upload task data to storage
-                client
-                    .update(task.id.as_ref(), &task.encode())
-                    .map_err(|_| Error::FailedToUploadTask)?;
+                Self::with_rpc_health(&client, |rpc_health| {
+                    let context = Context {
+                        signer: self.pub_to_prv(task.worker).unwrap(),
+                        worker_accounts: self.worker_accounts.clone(),
+                        registry: &self.registry,
+                        rpc_health,
+                        action_cache: Default::default(),
+                        xcm_delivery_cache: Default::default(),
+                        tx_status_cache: Default::default(),
+                    };
+                    task.retry_counter = 0;
+                    task.reapply_nonce(execute_index as u64, &context, &client)
+                        .map_err(|_| Error::FailedToReApplyNonce)?;
+                    pink_extension::info!(
+                        "Step nonce re-applied from execution index: {:?}",
+                        &execute_index
+                    );
+                    // Now re-run the step
+                    let _ = task
+                        .execute_step(&context, &client)
+                        .map_err(|_| Error::FailedToReRunTask)?;
+                    // Upload task data to storage
+                    client
+                        .update(task.id.as_ref(), &task.encode())
+                        .map_err(|_| Error::FailedToUploadTask)
+                })?;
             }
             Ok(())
         }
@@ -438,8 +696,7 @@ mod index_executor {
                         "Trying to read pending task data from remote storage, task id: {:?}",
                         &hex::encode(task_id)
                     );
-                    let task = client
-                        .read::<Task>(&task_id)
+                    let task = Task::read(&client, &task_id)
                         .map_err(|_| Error::FailedToReadStorage)?
                         .ok_or(Error::TaskNotFoundInStorage)?;
 
@@ -454,9 +711,7 @@ mod index_executor {
         pub fn get_task(&self, id: TaskId) -> Result<Option<Task>> {
             let config = self.ensure_configured()?;
             let client = StorageClient::new(config.db_url.clone(), config.db_token.clone());
-            client
-                .read::<Task>(&id)
-                .map_err(|_| Error::FailedToReadStorage)
+            Task::read(&client, &id).map_err(|_| Error::FailedToReadStorage)
         }
 
         #[ink(message)]
@@ -472,10 +727,69 @@ mod index_executor {
                 .map(|solution| solution.encode()))
         }
 
-        /// Returs the interior registry, callable to all
+        /// Import registry from its JSON representation. If `merge` is true, chains, assets and actions
+        /// are added or updated one by one, otherwise the whole registry is replaced.
+        /// Fee strategy and finality of chains that already exist are kept in both cases, so are
+        /// API keys of existing actions imported without one, which `export_registry` never includes.
+        #[ink(message)]
+        pub fn import_registry(&mut self, json: String, merge: bool) -> Result<()> {
+            self.ensure_owner()?;
+            let mut imported = crate::graph::import(json.as_bytes())
+                .log_err("Failed to import registry")
+                .map_err(Self::registry_error)?;
+            imported.keep_secrets(&self.registry);
+
+            if merge {
+                // Apply to a copy so that the registry stays untouched if any item is invalid
+                let mut registry = self.registry.clone();
+                registry.merge(imported).map_err(Self::registry_error)?;
+                self.registry = registry;
+            } else {
+                for chain in imported.chains.iter_mut() {
+                    if let Some(existing) = self.registry.get_chain(&chain.name) {
+                        chain.fee_strategy = existing.fee_strategy;
+                        chain.finality = existing.finality;
+                    }
+                }
+                self.registry = imported;
+            }
+            Self::env().emit_event(RegistryImported { merge });
+            Ok(())
+        }
+
+        /// Export registry to its JSON representation, callable to all
+        #[ink(message)]
+        pub fn export_registry(&self) -> Result<String> {
+            crate::graph::export(&self.registry).or(Err(Error::InvalidRegistryJson))
+        }
+
+        /// Returs the interior registry without secrets of the actions, callable to all
         #[ink(message)]
         pub fn get_registry(&self) -> Result<Registry> {
-            Ok(self.registry.clone())
+            Ok(self.registry.redacted())
+        }
+
+        /// Return error statistics of RPC endpoints collected from previous runs
+        #[ink(message)]
+        pub fn get_rpc_health(&self) -> Result<Vec<(String, EndpointStat)>> {
+            let config = self.ensure_configured()?;
+            let client = StorageClient::new(config.db_url.clone(), config.db_token.clone());
+            Ok(RpcHealth::load(&client)
+                .map_err(|_| Error::FailedToReadStorage)?
+                .stats())
+        }
+
+        /// Return assets with the symbol on all chains, callable to all
+        #[ink(message)]
+        pub fn get_assets_by_symbol(&self, symbol: String) -> Result<Vec<Asset>> {
+            Ok(self.registry.get_assets_by_symbol(&symbol))
+        }
+
+        /// Return all chain local representations of the asset with the canonical id,
+        /// callable to all
+        #[ink(message)]
+        pub fn get_assets_by_canonical_id(&self, canonical_id: Vec<u8>) -> Result<Vec<Asset>> {
+            Ok(self.registry.get_assets_by_canonical_id(&canonical_id))
         }
 
         /// Return whole worker account information
@@ -491,6 +805,7 @@ mod index_executor {
             source_chain: &String,
             // Worker sr25519 public key
             worker: [u8; 32],
+            rpc_health: &RpcHealth,
         ) -> Result<()> {
             let signer = self.pub_to_prv(worker).ok_or(Error::WorkerNotFound)?;
             // Fetch one actived task that completed initial confirmation from specific chain that belong to current worker
@@ -500,7 +815,7 @@ mod index_executor {
                     .ok_or(Error::ChainNotFound)?,
                 AccountInfo::from(signer),
             )
-            .fetch_task(client)
+            .fetch_task(client, rpc_health)
             .map_err(|_| Error::FailedToFetchTask)?;
             let Some(mut actived_task) = actived_task else {
                 pink_extension::debug!("No actived task found from {:?}", &source_chain);
@@ -514,6 +829,10 @@ mod index_executor {
                         signer,
                         registry: &self.registry,
                         worker_accounts: self.worker_accounts.clone(),
+                        rpc_health,
+                        action_cache: Default::default(),
+                        xcm_delivery_cache: Default::default(),
+                        tx_status_cache: Default::default(),
                     },
                     client,
                 )
@@ -536,7 +855,12 @@ mod index_executor {
 
         /// Execute tasks from all supported blockchains. This is a query operation
         /// that scheduler invokes periodically.
-        pub fn execute_task(&self, client: &StorageClient, worker: [u8; 32]) -> Result<()> {
+        pub fn execute_task(
+            &self,
+            client: &StorageClient,
+            worker: [u8; 32],
+            rpc_health: &RpcHealth,
+        ) -> Result<()> {
             if let Some(id) = client
                 .read::<TaskId>(&worker)
                 .map_err(|_| Error::FailedToReadStorage)?
@@ -545,8 +869,7 @@ mod index_executor {
                     "Trying to read pending task data from remote storage, task id: {:?}",
                     &hex::encode(id)
                 );
-                let mut task = client
-                    .read::<Task>(&id)
+                let mut task = Task::read(client, &id)
                     .map_err(|_| Error::FailedToReadStorage)?
                     .ok_or(Error::TaskNotFoundInStorage)?;
 
@@ -559,6 +882,10 @@ mod index_executor {
                         signer: self.pub_to_prv(task.worker).unwrap(),
                         worker_accounts: self.worker_accounts.clone(),
                         registry: &self.registry,
+                        rpc_health,
+                        action_cache: Default::default(),
+                        xcm_delivery_cache: Default::default(),
+                        tx_status_cache: Default::default(),
                     },
                     client,
                 ) {
@@ -571,6 +898,15 @@ mod index_executor {
                         task.destroy(client)
                             .map_err(|_| Error::FailedToDestoryTask)?;
                     }
+                    Ok(TaskStatus::Failed(step_index)) => {
+                        pink_extension::error!(
+                            "Task failed on step {:?}, recycle worker and keep it for DAO to handle: {:?}",
+                            step_index,
+                            &task
+                        );
+                        task.destroy(client)
+                            .map_err(|_| Error::FailedToDestoryTask)?;
+                    }
                     Err(err) => {
                         pink_extension::error!(
                             "Failed to execute task on step {:?} with error {}, task data: {:?}",
@@ -635,6 +971,36 @@ mod index_executor {
             Ok(())
         }
 
+        /// Convert errors returned by the registry
+        fn registry_error(err: &'static str) -> Error {
+            match err {
+                "ChainNotFound" => Error::ChainNotFound,
+                "ChainAlreadyExist" => Error::ChainAlreadyExist,
+                "InvalidChain" => Error::InvalidChainInfo,
+                "AssetAlreadyExist" => Error::AssetAlreadyExist,
+                "AssetNotFound" => Error::AssetNotFound,
+                "InvalidAsset" => Error::InvalidAssetInfo,
+                "ActionNotFound" => Error::ActionNotFound,
+                "InvalidAction" => Error::InvalidActionInfo,
+                // Errors of JSON parsing
+                _ => Error::InvalidRegistryJson,
+            }
+        }
+
+        /// Run `f` with RPC health loaded from storage, statistics are saved even if `f` failed,
+        /// failures are what we care about
+        fn with_rpc_health<T>(client: &StorageClient, f: impl FnOnce(&RpcHealth) -> T) -> T {
+            let rpc_health = RpcHealth::load(client).unwrap_or_else(|e| {
+                pink_extension::error!("Failed to load RPC health, start from empty: {:?}", e);
+                RpcHealth::default()
+            });
+            let result = f(&rpc_health);
+            if let Err(e) = rpc_health.save(client) {
+                pink_extension::error!("Failed to save RPC health: {:?}", e);
+            }
+            result
+        }
+
         fn pub_to_prv(&self, pub_key: [u8; 32]) -> Option<[u8; 32]> {
             self.worker_accounts
                 .iter()
@@ -669,6 +1035,60 @@ mod index_executor {
             );
         }
 
+        #[ink::test]
+        fn registry_management_should_work() {
+            let mut executor = deploy_executor();
+            let polkadot = executor
+                .registry
+                .get_chain(&"Polkadot".to_string())
+                .unwrap();
+
+            assert_eq!(
+                executor.add_chain(polkadot.clone()),
+                Err(Error::ChainAlreadyExist)
+            );
+            assert_eq!(
+                executor.update_chain(Chain {
+                    handler_contract: vec![],
+                    ..polkadot.clone()
+                }),
+                Err(Error::InvalidChainInfo)
+            );
+            assert_eq!(
+                executor.update_chain_info("Unknown".to_string(), vec![], String::new()),
+                Err(Error::InvalidEndpoints)
+            );
+            assert_eq!(
+                executor.unregister_asset("Polkadot".to_string(), vec![]),
+                Err(Error::AssetNotFound)
+            );
+            assert_eq!(
+                executor.remove_action("Polkadot".to_string(), "unknown".to_string()),
+                Err(Error::ActionNotFound)
+            );
+            let mut action = executor
+                .registry
+                .actions
+                .iter()
+                .find(|action| action.id == "polkadot_native_transactor")
+                .cloned()
+                .unwrap();
+            action.kind = crate::actions::ActionKind::UniswapV2 { router: [0; 20] };
+            assert_eq!(executor.set_action(action), Err(Error::InvalidActionInfo));
+            assert_eq!(executor.remove_chain("Polkadot".to_string()), Ok(()));
+            assert!(executor
+                .registry
+                .actions
+                .iter()
+                .all(|action| action.chain != "Polkadot"));
+            assert!(executor
+                .registry
+                .assets
+                .iter()
+                .all(|asset| asset.chain != "Polkadot"));
+            assert_eq!(executor.add_chain(polkadot), Ok(()));
+        }
+
         #[ink::test]
         fn dump_location() {
             println!(
@@ -3,10 +3,30 @@
 ///
 /// The key is the asset sygmbol, and the value is the price. Each asset only have one price
 /// related. That means we treat xcPHA and PHA as PHA, because they are the same asset essentially
+use crate::constants::{ASSETHUB_ASSETS_PALLET_ID, ASSETHUB_USDC_ID, ASSETHUB_USDT_ID};
+use crate::registry::Registry;
 use alloc::vec::Vec;
+use scale::Encode;
+use xcm::v3::{prelude::*, MultiLocation};
 
 // TODO: Get price from local cache
 pub fn get_price(chain: &str, asset: &Vec<u8>) -> Option<u32> {
+    // TODO
+    // 0.1 USD
+    known_price(chain, asset).or(Some(1000))
+}
+
+/// Price of the asset, representations of the same asset on other chains share the price.
+/// e.g. xcGLMR on AstarEvm is priced as GLMR on Moonbeam
+pub fn get_asset_price(registry: &Registry, chain: &str, asset: &Vec<u8>) -> Option<u32> {
+    registry
+        .get_representations(&chain.into(), asset)
+        .iter()
+        .find_map(|a| known_price(&a.chain, &a.location))
+        .or_else(|| get_price(chain, asset))
+}
+
+fn known_price(chain: &str, asset: &Vec<u8>) -> Option<u32> {
     // ETH
     if chain == "Ethereum" && asset == &[0; 20] {
         // 2000 USD
@@ -23,13 +43,27 @@ pub fn get_price(chain: &str, asset: &Vec<u8>) -> Option<u32> {
     {
         // 0.3 USD
         Some(3000)
+    }
+    // USDT, USDC
+    else if chain == "AssetHub" && is_assethub_stablecoin(asset) {
+        // 1 USD
+        Some(10000)
     } else {
-        // TODO
-        // 0.1 USD
-        Some(1000)
+        None
     }
 }
 
+fn is_assethub_stablecoin(asset: &Vec<u8>) -> bool {
+    [ASSETHUB_USDT_ID, ASSETHUB_USDC_ID].iter().any(|id| {
+        &MultiLocation::new(
+            0,
+            X2(PalletInstance(ASSETHUB_ASSETS_PALLET_ID), GeneralIndex(*id)),
+        )
+        .encode()
+            == asset
+    })
+}
+
 // TODO: Fetch asset price from internet and save to local cache
 #[allow(dead_code, unused_variables)]
 pub fn update_price(assets: Vec<&str>) {}
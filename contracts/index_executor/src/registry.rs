@@ -1,10 +1,28 @@
 //#[allow(clippy::large_enum_variant)]
-use crate::actions::ActionExtraInfo;
+use crate::account::AccountType;
+use crate::actions::{
+    self,
+    acala::{asset::TokenSymbol, dex::TaigaPool},
+    base::xcm::{XcmConfig, XcmDestWeights},
+    ethereum::sygma::SygmaResource,
+    ActionExtraInfo, ActionKind,
+};
+use crate::constants::*;
+use crate::utils::slice_to_generalkey;
 use crate::{
     call::CallBuilder,
     chain::{Chain, ChainType, ForeignAssetModule},
+    rpc::{RpcEndpoint, RpcHealth},
+    tx::{DepositEvent, XcmEvents, XcmQueue, XcmSender},
 };
 use ink::storage::traits::StorageLayout;
+use scale::Encode;
+use sp_runtime::Permill;
+use xcm::v3::{
+    Junction::{GeneralIndex, GlobalConsensus, PalletInstance, Parachain},
+    Junctions::{X1, X2, X3},
+    MultiLocation, NetworkId,
+};
 
 use alloc::{
     boxed::Box,
@@ -17,9 +35,36 @@ use alloc::{
 #[cfg_attr(feature = "std", derive(scale_info::TypeInfo, StorageLayout,))]
 pub struct Asset {
     pub chain: String,
+    pub name: String,
     pub symbol: String,
     pub location: Vec<u8>,
     pub decimals: u8,
+    /// SCALE encoded MultiLocation of the asset on its reserve chain, as seen from a
+    /// parachain. Assets with the same canonical id are representations of the same asset
+    /// on different chains, e.g. PHA on Phala, ERC20 PHA on Ethereum and xcPHA on Moonbeam
+    pub canonical_id: Option<Vec<u8>>,
+}
+
+/// An action available on a chain, its `CallBuilder` is built from `kind`
+#[derive(Clone, scale::Encode, scale::Decode, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+pub struct ActionInfo {
+    pub chain: String,
+    // Same as the `exe` of steps that use the action
+    pub id: String,
+    pub kind: ActionKind,
+    pub extra_info: ActionExtraInfo,
+}
+
+impl ActionInfo {
+    pub fn new(chain: &str, id: &str, kind: ActionKind, extra_info: ActionExtraInfo) -> Self {
+        Self {
+            chain: chain.into(),
+            id: id.into(),
+            kind,
+            extra_info,
+        }
+    }
 }
 
 #[derive(Clone, scale::Encode, scale::Decode, Debug)]
@@ -27,6 +72,7 @@ pub struct Asset {
 pub struct Registry {
     pub chains: Vec<Chain>,
     pub assets: Vec<Asset>,
+    pub actions: Vec<ActionInfo>,
 }
 
 impl Default for Registry {
@@ -42,7 +88,10 @@ impl Registry {
                 Chain {
                     id: 0,
                     name: "Ethereum".to_string(),
-                    endpoint: "https://rpc.ankr.com/eth".to_string(),
+                    endpoints: vec![
+                        RpcEndpoint::new("https://rpc.ankr.com/eth", 0),
+                        RpcEndpoint::new("https://ethereum.publicnode.com", 1),
+                    ],
                     chain_type: ChainType::Evm,
                     native_asset: hex_literal::hex!("0000000000000000000000000000000000000000")
                         .to_vec(),
@@ -50,11 +99,18 @@ impl Registry {
                     handler_contract: hex::decode("d693bDC5cb0cF2a31F08744A0Ec135a68C26FE1c")
                         .expect("InvalidLocation"),
                     tx_indexer_url: "https://squid.subsquid.io/graph-ethereum/graphql".to_string(),
+                    fee_strategy: Default::default(),
+                    finality: Default::default(),
+                    xcm_events: default_xcm_events("Ethereum"),
+                    xcm_dest_weight: None,
                 },
                 Chain {
                     id: 1,
                     name: "Moonbeam".to_string(),
-                    endpoint: "https://rpc.api.moonbeam.network".to_string(),
+                    endpoints: vec![
+                        RpcEndpoint::new("https://rpc.api.moonbeam.network", 0),
+                        RpcEndpoint::new("https://moonbeam.public.blastapi.io", 1),
+                    ],
                     chain_type: ChainType::Evm,
                     native_asset: hex::decode("0000000000000000000000000000000000000802")
                         .expect("InvalidLocation"),
@@ -62,11 +118,18 @@ impl Registry {
                     handler_contract: hex::decode("8351BAE38E3D590063544A99A95BF4fe5379110b")
                         .expect("InvalidLocation"),
                     tx_indexer_url: "https://squid.subsquid.io/graph-moonbeam/graphql".to_string(),
+                    fee_strategy: Default::default(),
+                    finality: Default::default(),
+                    xcm_events: default_xcm_events("Moonbeam"),
+                    xcm_dest_weight: None,
                 },
                 Chain {
                     id: 2,
                     name: "AstarEvm".to_string(),
-                    endpoint: "https://astar.public.blastapi.io".to_string(),
+                    endpoints: vec![
+                        RpcEndpoint::new("https://astar.public.blastapi.io", 0),
+                        RpcEndpoint::new("https://evm.astar.network", 1),
+                    ],
                     chain_type: ChainType::Evm,
                     native_asset: hex_literal::hex!("0000000000000000000000000000000000000000")
                         .to_vec(),
@@ -74,115 +137,332 @@ impl Registry {
                     handler_contract: hex::decode("AE1Ab0a83de66a545229d39E874237fbaFe05714")
                         .expect("InvalidLocation"),
                     tx_indexer_url: "https://squid.subsquid.io/graph-astar/graphql".to_string(),
+                    fee_strategy: Default::default(),
+                    finality: Default::default(),
+                    xcm_events: default_xcm_events("AstarEvm"),
+                    xcm_dest_weight: None,
                 },
                 Chain {
                     id: 3,
                     name: "Astar".to_string(),
-                    endpoint: "https://astar.public.blastapi.io".to_string(),
+                    endpoints: vec![RpcEndpoint::new("https://astar.public.blastapi.io", 0)],
                     chain_type: ChainType::Sub,
                     native_asset: hex::decode("010100591f").expect("InvalidLocation"),
                     foreign_asset: Some(ForeignAssetModule::PalletAsset),
                     // FIXME: Handle contract on AStar Sub
                     handler_contract: hex::decode("00").expect("InvalidLocation"),
                     tx_indexer_url: "https://squid.subsquid.io/graph-astar/graphql".to_string(),
+                    fee_strategy: Default::default(),
+                    finality: Default::default(),
+                    xcm_events: default_xcm_events("Astar"),
+                    xcm_dest_weight: None,
                 },
                 Chain {
                     id: 4,
                     name: "Khala".to_string(),
-                    endpoint: "https://khala-api.phala.network/rpc".to_string(),
+                    endpoints: vec![RpcEndpoint::new("https://khala-api.phala.network/rpc", 0)],
                     chain_type: ChainType::Sub,
                     native_asset: hex::decode("0000").expect("InvalidLocation"),
                     foreign_asset: Some(ForeignAssetModule::PalletAsset),
                     handler_contract: hex::decode("79").expect("InvalidLocation"),
                     tx_indexer_url: "https://squid.subsquid.io/graph-khala/graphql".to_string(),
+                    fee_strategy: Default::default(),
+                    finality: Default::default(),
+                    xcm_events: default_xcm_events("Khala"),
+                    xcm_dest_weight: Some(PHALA_XCM_DEST_WEIGHT),
                 },
                 Chain {
                     id: 5,
                     name: "Phala".to_string(),
-                    endpoint: "https://api.phala.network/rpc".to_string(),
+                    endpoints: vec![RpcEndpoint::new("https://api.phala.network/rpc", 0)],
                     chain_type: ChainType::Sub,
                     native_asset: hex::decode("0000").expect("InvalidLocation"),
                     foreign_asset: Some(ForeignAssetModule::PalletAsset),
                     handler_contract: hex::decode("79").expect("InvalidLocation"),
                     tx_indexer_url: "https://squid.subsquid.io/graph-phala/graphql".to_string(),
+                    fee_strategy: Default::default(),
+                    finality: Default::default(),
+                    xcm_events: default_xcm_events("Phala"),
+                    xcm_dest_weight: Some(PHALA_XCM_DEST_WEIGHT),
                 },
                 Chain {
                     id: 6,
                     name: "Acala".to_string(),
-                    endpoint: "https://acala-rpc.dwellir.com".to_string(),
+                    endpoints: vec![RpcEndpoint::new("https://acala-rpc.dwellir.com", 0)],
                     chain_type: ChainType::Sub,
                     native_asset: hex::decode("010200411f06080000").expect("InvalidLocation"),
                     foreign_asset: Some(ForeignAssetModule::OrmlToken),
                     // FIXME: No Handler pallet in Acala
                     handler_contract: hex::decode("00").expect("InvalidLocation"),
                     tx_indexer_url: "https://squid.subsquid.io/graph-acala/graphql".to_string(),
+                    fee_strategy: Default::default(),
+                    finality: Default::default(),
+                    xcm_events: default_xcm_events("Acala"),
+                    xcm_dest_weight: None,
                 },
                 Chain {
                     id: 7,
                     name: "Polkadot".to_string(),
-                    endpoint: "https://polkadot.api.onfinality.io/public".to_string(),
+                    endpoints: vec![
+                        RpcEndpoint::new("https://polkadot.api.onfinality.io/public", 0),
+                        RpcEndpoint::new("https://rpc.polkadot.io", 1),
+                    ],
                     chain_type: ChainType::Sub,
                     native_asset: hex::decode("0000").expect("InvalidLocation"),
                     foreign_asset: Some(ForeignAssetModule::PalletAsset),
                     // FIXME: No Handler pallet in Polkadot
                     handler_contract: hex::decode("00").expect("InvalidLocation"),
                     tx_indexer_url: "https://squid.subsquid.io/graph-polkadot/graphql".to_string(),
+                    fee_strategy: Default::default(),
+                    finality: Default::default(),
+                    xcm_events: default_xcm_events("Polkadot"),
+                    xcm_dest_weight: None,
                 },
-            ],
-            assets: vec![
-                Asset {
-                    chain: "Ethereum".to_string(),
-                    symbol: "ETH".to_string(),
-                    location: hex::decode("0000000000000000000000000000000000000000")
-                        .expect("InvalidLocation"),
-                    decimals: 18,
+                Chain {
+                    id: 8,
+                    name: "Karura".to_string(),
+                    endpoints: vec![
+                        RpcEndpoint::new("https://karura-rpc.dwellir.com", 0),
+                        RpcEndpoint::new("https://karura-rpc-0.aca-api.network", 1),
+                    ],
+                    chain_type: ChainType::Sub,
+                    native_asset: hex::decode("010200411f06080080").expect("InvalidLocation"),
+                    foreign_asset: Some(ForeignAssetModule::OrmlToken),
+                    // FIXME: No Handler pallet in Karura
+                    handler_contract: hex::decode("00").expect("InvalidLocation"),
+                    tx_indexer_url: "https://squid.subsquid.io/graph-karura/graphql".to_string(),
+                    fee_strategy: Default::default(),
+                    finality: Default::default(),
+                    xcm_events: default_xcm_events("Karura"),
+                    xcm_dest_weight: None,
                 },
-                Asset {
-                    chain: "Ethereum".to_string(),
-                    symbol: "PHA".to_string(),
-                    location: hex::decode("6c5bA91642F10282b576d91922Ae6448C9d52f4E")
+                Chain {
+                    id: 9,
+                    name: "Moonriver".to_string(),
+                    endpoints: vec![
+                        RpcEndpoint::new("https://rpc.api.moonriver.moonbeam.network", 0),
+                        RpcEndpoint::new("https://moonriver.public.blastapi.io", 1),
+                    ],
+                    chain_type: ChainType::Evm,
+                    native_asset: hex::decode("0000000000000000000000000000000000000802")
                         .expect("InvalidLocation"),
-                    decimals: 18,
-                },
-                Asset {
-                    chain: "Phala".to_string(),
-                    symbol: "PHA".to_string(),
-                    location: hex::decode("0000").expect("InvalidLocation"),
-                    decimals: 12,
-                },
-                Asset {
-                    chain: "Khala".to_string(),
-                    symbol: "PHA".to_string(),
-                    location: hex::decode("0000").expect("InvalidLocation"),
-                    decimals: 12,
+                    foreign_asset: None,
+                    // FIXME: Handler contract not deployed on Moonriver yet
+                    handler_contract: hex_literal::hex!("0000000000000000000000000000000000000000")
+                        .to_vec(),
+                    tx_indexer_url: "https://squid.subsquid.io/graph-moonriver/graphql".to_string(),
+                    fee_strategy: Default::default(),
+                    finality: Default::default(),
+                    xcm_events: default_xcm_events("Moonriver"),
+                    xcm_dest_weight: None,
                 },
-                Asset {
-                    chain: "Moonbeam".to_string(),
-                    symbol: "GLMR".to_string(),
-                    location: hex::decode("0000000000000000000000000000000000000802")
-                        .expect("InvalidLocation"),
-                    decimals: 18,
+                Chain {
+                    id: 10,
+                    name: "Shiden".to_string(),
+                    endpoints: vec![RpcEndpoint::new("https://shiden.public.blastapi.io", 0)],
+                    chain_type: ChainType::Sub,
+                    native_asset: hex::decode("0101005d1f").expect("InvalidLocation"),
+                    foreign_asset: Some(ForeignAssetModule::PalletAsset),
+                    // FIXME: Handle contract on Shiden
+                    handler_contract: hex::decode("00").expect("InvalidLocation"),
+                    tx_indexer_url: "https://squid.subsquid.io/graph-shiden/graphql".to_string(),
+                    fee_strategy: Default::default(),
+                    finality: Default::default(),
+                    xcm_events: default_xcm_events("Shiden"),
+                    xcm_dest_weight: None,
                 },
-                Asset {
-                    chain: "AstarEvm".to_string(),
-                    symbol: "ASTR".to_string(),
-                    location: [0; 20].to_vec(),
-                    decimals: 18,
+                Chain {
+                    id: 11,
+                    name: "Kusama".to_string(),
+                    endpoints: vec![
+                        RpcEndpoint::new("https://kusama.api.onfinality.io/public", 0),
+                        RpcEndpoint::new("https://kusama-rpc.polkadot.io", 1),
+                    ],
+                    chain_type: ChainType::Sub,
+                    native_asset: hex::decode("0000").expect("InvalidLocation"),
+                    foreign_asset: Some(ForeignAssetModule::PalletAsset),
+                    // FIXME: No Handler pallet in Kusama
+                    handler_contract: hex::decode("00").expect("InvalidLocation"),
+                    tx_indexer_url: "https://squid.subsquid.io/graph-kusama/graphql".to_string(),
+                    fee_strategy: Default::default(),
+                    finality: Default::default(),
+                    xcm_events: default_xcm_events("Kusama"),
+                    xcm_dest_weight: None,
                 },
-                Asset {
-                    chain: "AstarEvm".to_string(),
-                    symbol: "GLMR".to_string(),
-                    location: hex::decode("FFFFFFFF00000000000000010000000000000003")
-                        .expect("InvalidLocation"),
-                    decimals: 18,
+                Chain {
+                    id: 12,
+                    name: "AssetHub".to_string(),
+                    endpoints: vec![
+                        RpcEndpoint::new("https://polkadot-asset-hub-rpc.polkadot.io", 0),
+                        RpcEndpoint::new("https://statemint.api.onfinality.io/public", 1),
+                    ],
+                    chain_type: ChainType::Sub,
+                    native_asset: hex::decode("0000").expect("InvalidLocation"),
+                    foreign_asset: Some(ForeignAssetModule::PalletAsset),
+                    // FIXME: No Handler pallet in AssetHub
+                    handler_contract: hex::decode("00").expect("InvalidLocation"),
+                    tx_indexer_url: "https://squid.subsquid.io/graph-assethub/graphql".to_string(),
+                    fee_strategy: Default::default(),
+                    finality: Default::default(),
+                    xcm_events: default_xcm_events("AssetHub"),
+                    xcm_dest_weight: None,
                 },
-                Asset {
-                    chain: "Astar".to_string(),
-                    symbol: "ASTR".to_string(),
-                    location: hex::decode("010100591f").expect("InvalidLocation"),
-                    decimals: 18,
+                Chain {
+                    id: 13,
+                    name: "HydraDX".to_string(),
+                    endpoints: vec![
+                        RpcEndpoint::new("https://rpc.hydradx.cloud", 0),
+                        RpcEndpoint::new("https://hydradx-rpc.dwellir.com", 1),
+                    ],
+                    chain_type: ChainType::Sub,
+                    native_asset: hex::decode("0000").expect("InvalidLocation"),
+                    foreign_asset: Some(ForeignAssetModule::OrmlToken),
+                    // FIXME: No Handler pallet in HydraDX
+                    handler_contract: hex::decode("00").expect("InvalidLocation"),
+                    tx_indexer_url: "https://squid.subsquid.io/graph-hydradx/graphql".to_string(),
+                    fee_strategy: Default::default(),
+                    finality: Default::default(),
+                    xcm_events: default_xcm_events("HydraDX"),
+                    xcm_dest_weight: None,
                 },
             ],
+            assets: [
+                vec![
+                    Asset {
+                        chain: "Ethereum".to_string(),
+                        name: "Ether".to_string(),
+                        symbol: "ETH".to_string(),
+                        location: hex::decode("0000000000000000000000000000000000000000")
+                            .expect("InvalidLocation"),
+                        decimals: 18,
+                        canonical_id: Some(ether_location().encode()),
+                    },
+                    Asset {
+                        chain: "Ethereum".to_string(),
+                        name: "Phala Token".to_string(),
+                        symbol: "PHA".to_string(),
+                        location: hex::decode("6c5bA91642F10282b576d91922Ae6448C9d52f4E")
+                            .expect("InvalidLocation"),
+                        decimals: 18,
+                        canonical_id: Some(pha_location().encode()),
+                    },
+                    Asset {
+                        chain: "Phala".to_string(),
+                        name: "Phala Token".to_string(),
+                        symbol: "PHA".to_string(),
+                        location: hex::decode("0000").expect("InvalidLocation"),
+                        decimals: 12,
+                        canonical_id: Some(pha_location().encode()),
+                    },
+                    Asset {
+                        chain: "Khala".to_string(),
+                        name: "Phala Token".to_string(),
+                        symbol: "PHA".to_string(),
+                        location: hex::decode("0000").expect("InvalidLocation"),
+                        decimals: 12,
+                        canonical_id: Some(pha_location().encode()),
+                    },
+                    Asset {
+                        chain: "Moonbeam".to_string(),
+                        name: "Moonbeam".to_string(),
+                        symbol: "GLMR".to_string(),
+                        location: hex::decode("0000000000000000000000000000000000000802")
+                            .expect("InvalidLocation"),
+                        decimals: 18,
+                        canonical_id: Some(glmr_location().encode()),
+                    },
+                    Asset {
+                        chain: "AstarEvm".to_string(),
+                        name: "Astar".to_string(),
+                        symbol: "ASTR".to_string(),
+                        location: [0; 20].to_vec(),
+                        decimals: 18,
+                        canonical_id: Some(astr_location().encode()),
+                    },
+                    Asset {
+                        chain: "AstarEvm".to_string(),
+                        name: "Moonbeam".to_string(),
+                        symbol: "GLMR".to_string(),
+                        location: hex::decode("FFFFFFFF00000000000000010000000000000003")
+                            .expect("InvalidLocation"),
+                        decimals: 18,
+                        canonical_id: Some(glmr_location().encode()),
+                    },
+                    Asset {
+                        chain: "Astar".to_string(),
+                        name: "Astar".to_string(),
+                        symbol: "ASTR".to_string(),
+                        location: hex::decode("010100591f").expect("InvalidLocation"),
+                        decimals: 18,
+                        canonical_id: Some(astr_location().encode()),
+                    },
+                    Asset {
+                        chain: "Karura".to_string(),
+                        name: "Karura".to_string(),
+                        symbol: "KAR".to_string(),
+                        location: hex::decode("010200411f06080080").expect("InvalidLocation"),
+                        decimals: 12,
+                        canonical_id: Some(kar_location().encode()),
+                    },
+                    Asset {
+                        chain: "Moonriver".to_string(),
+                        name: "Moonriver".to_string(),
+                        symbol: "MOVR".to_string(),
+                        location: hex::decode("0000000000000000000000000000000000000802")
+                            .expect("InvalidLocation"),
+                        decimals: 18,
+                        canonical_id: Some(movr_location().encode()),
+                    },
+                    Asset {
+                        chain: "Shiden".to_string(),
+                        name: "Shiden".to_string(),
+                        symbol: "SDN".to_string(),
+                        location: hex::decode("0101005d1f").expect("InvalidLocation"),
+                        decimals: 18,
+                        canonical_id: Some(sdn_location().encode()),
+                    },
+                    Asset {
+                        chain: "Kusama".to_string(),
+                        name: "Kusama".to_string(),
+                        symbol: "KSM".to_string(),
+                        location: hex::decode("0000").expect("InvalidLocation"),
+                        decimals: 12,
+                        canonical_id: Some(ksm_location().encode()),
+                    },
+                    Asset {
+                        chain: "HydraDX".to_string(),
+                        name: "HydraDX".to_string(),
+                        symbol: "HDX".to_string(),
+                        location: hex::decode("0000").expect("InvalidLocation"),
+                        decimals: 12,
+                        canonical_id: Some(hdx_location().encode()),
+                    },
+                ],
+                stablecoin_assets(),
+            ]
+            .concat(),
+            actions: default_actions(),
+        }
+    }
+
+    /// The registry without secrets of the actions, returned to callers
+    pub fn redacted(&self) -> Registry {
+        let mut registry = self.clone();
+        for action in registry.actions.iter_mut() {
+            action.kind = action.kind.redacted();
+        }
+        registry
+    }
+
+    /// Keep secrets of the actions in `existing` that the same actions of the registry don't
+    /// carry, see `ActionKind::keep_secrets`
+    pub fn keep_secrets(&mut self, existing: &Registry) {
+        for action in self.actions.iter_mut() {
+            if let Some(existing) = existing.actions.iter().find(|a| {
+                a.chain == action.chain && a.id.to_lowercase() == action.id.to_lowercase()
+            }) {
+                action.kind.keep_secrets(&existing.kind);
+            }
         }
     }
 
@@ -202,30 +482,1534 @@ impl Registry {
             .map(|idx| assets[idx].clone())
     }
 
-    pub fn create_actions(&self, chain: &String) -> Vec<(String, Box<dyn CallBuilder>)> {
-        let chain = self.get_chain(chain).expect("ChainNotFound");
+    /// Return assets with the symbol on all chains, case insensitive
+    pub fn get_assets_by_symbol(&self, symbol: &str) -> Vec<Asset> {
+        self.assets
+            .iter()
+            .filter(|a| a.symbol.to_lowercase() == symbol.to_lowercase())
+            .cloned()
+            .collect()
+    }
+
+    /// Return all representations of the asset with the canonical id
+    pub fn get_assets_by_canonical_id(&self, canonical_id: &[u8]) -> Vec<Asset> {
+        self.assets
+            .iter()
+            .filter(|a| a.canonical_id.as_deref() == Some(canonical_id))
+            .cloned()
+            .collect()
+    }
+
+    /// Return all representations of the asset, including itself which is always the first.
+    /// Asset without canonical id is only represented by itself
+    pub fn get_representations(&self, chain: &String, location: &Vec<u8>) -> Vec<Asset> {
+        let Some(asset) = self.get_asset(chain, location) else {
+            return vec![];
+        };
+        let mut representations = vec![asset.clone()];
+        if let Some(canonical_id) = &asset.canonical_id {
+            representations.extend(
+                self.get_assets_by_canonical_id(canonical_id)
+                    .into_iter()
+                    .filter(|a| !(&a.chain == chain && &a.location == location)),
+            );
+        }
+        representations
+    }
+
+    /// Whether two chain local assets are representations of the same asset,
+    /// returns `None` if the canonical id of any of them is unknown
+    pub fn is_same_asset(
+        &self,
+        chain0: &String,
+        location0: &Vec<u8>,
+        chain1: &String,
+        location1: &Vec<u8>,
+    ) -> Option<bool> {
+        if chain0 == chain1 && location0 == location1 {
+            return Some(true);
+        }
+        let canonical_id0 = self.get_asset(chain0, location0)?.canonical_id?;
+        let canonical_id1 = self.get_asset(chain1, location1)?.canonical_id?;
+        Some(canonical_id0 == canonical_id1)
+    }
+
+    /// Add a new chain, name and id of the chain must not be used by existing chains
+    pub fn add_chain(&mut self, chain: Chain) -> Result<(), &'static str> {
+        validate_chain(&chain)?;
+        if self
+            .chains
+            .iter()
+            .any(|c| c.name == chain.name || c.id == chain.id)
+        {
+            return Err("ChainAlreadyExist");
+        }
+        self.chains.push(chain);
+        Ok(())
+    }
 
-        match chain.name.as_str() {
-            "Acala" => crate::actions::acala::create_actions(&chain),
-            "AstarEvm" => crate::actions::astar::evm_create_actions(&chain),
-            "Astar" => crate::actions::astar::sub_create_actions(&chain),
-            "Ethereum" => crate::actions::ethereum::create_actions(&chain),
-            "Moonbeam" => crate::actions::moonbeam::create_actions(&chain),
-            "Phala" | "Khala" => crate::actions::phala::create_actions(&chain),
-            "Polkadot" => crate::actions::polkadot::create_actions(&chain),
-            _ => vec![],
+    /// Replace all fields of the chain with the same name, the chain type of a chain with
+    /// actions can not be changed and its handler can not be reset to the zero address
+    pub fn update_chain(&mut self, chain: Chain) -> Result<(), &'static str> {
+        validate_chain(&chain)?;
+        let index = self
+            .chains
+            .iter()
+            .position(|c| c.name == chain.name)
+            .ok_or("ChainNotFound")?;
+        if self.actions.iter().any(|a| a.chain == chain.name) {
+            // Actions were checked against the chain type when they were set
+            if chain.chain_type != self.chains[index].chain_type {
+                return Err("InvalidChain");
+            }
+            if !chain.has_handler() {
+                return Err("HandlerNotDeployed");
+            }
+        }
+        if self
+            .chains
+            .iter()
+            .enumerate()
+            .any(|(i, c)| i != index && c.id == chain.id)
+        {
+            return Err("ChainAlreadyExist");
         }
+        self.chains[index] = chain;
+        Ok(())
+    }
+
+    /// Remove the chain along with all assets and actions registered on it
+    pub fn remove_chain(&mut self, name: &String) -> Result<(), &'static str> {
+        let index = self
+            .chains
+            .iter()
+            .position(|c| &c.name == name)
+            .ok_or("ChainNotFound")?;
+        self.chains.remove(index);
+        self.assets.retain(|a| &a.chain != name);
+        self.actions.retain(|a| &a.chain != name);
+        Ok(())
+    }
+
+    pub fn register_asset(&mut self, asset: Asset) -> Result<(), &'static str> {
+        self.validate_asset(&asset)?;
+        if self.get_asset(&asset.chain, &asset.location).is_some() {
+            return Err("AssetAlreadyExist");
+        }
+        self.assets.push(asset);
+        Ok(())
+    }
+
+    /// Replace the asset with the same chain and location, all fields but the chain and
+    /// location can be changed
+    pub fn update_asset(&mut self, asset: Asset) -> Result<(), &'static str> {
+        self.validate_asset(&asset)?;
+        let index = self
+            .assets
+            .iter()
+            .position(|a| a.chain == asset.chain && a.location == asset.location)
+            .ok_or("AssetNotFound")?;
+        self.assets[index] = asset;
+        Ok(())
+    }
+
+    pub fn unregister_asset(
+        &mut self,
+        chain: &String,
+        location: &Vec<u8>,
+    ) -> Result<(), &'static str> {
+        let index = self
+            .assets
+            .iter()
+            .position(|a| &a.chain == chain && &a.location == location)
+            .ok_or("AssetNotFound")?;
+        self.assets.remove(index);
+        Ok(())
+    }
+
+    /// Add the action, or replace the existing one with the same chain and id
+    pub fn set_action(&mut self, action: ActionInfo) -> Result<(), &'static str> {
+        let chain = self.get_chain(&action.chain).ok_or("ChainNotFound")?;
+        if action.id.is_empty() || action.kind.is_evm() != chain.is_evm_chain() {
+            return Err("InvalidAction");
+        }
+        if !chain.has_handler() {
+            return Err("HandlerNotDeployed");
+        }
+        match self.actions.iter().position(|a| {
+            a.chain == action.chain && a.id.to_lowercase() == action.id.to_lowercase()
+        }) {
+            Some(index) => self.actions[index] = action,
+            None => self.actions.push(action),
+        }
+        Ok(())
+    }
+
+    /// Merge chains, assets and actions of `other` into the registry, existing ones are updated.
+    /// Fee strategy and finality of existing chains are kept
+    pub fn merge(&mut self, other: Registry) -> Result<(), &'static str> {
+        for mut chain in other.chains {
+            match self.get_chain(&chain.name) {
+                Some(existing) => {
+                    chain.fee_strategy = existing.fee_strategy;
+                    chain.finality = existing.finality;
+                    self.update_chain(chain)?;
+                }
+                None => self.add_chain(chain)?,
+            }
+        }
+        for asset in other.assets {
+            match self.get_asset(&asset.chain, &asset.location) {
+                Some(_) => self.update_asset(asset)?,
+                None => self.register_asset(asset)?,
+            }
+        }
+        for action in other.actions {
+            self.set_action(action)?;
+        }
+        Ok(())
+    }
+
+    /// Remove the action with the same chain and id, ids are matched case-insensitively
+    /// like `set_action`
+    pub fn remove_action(&mut self, chain: &String, id: &String) -> Result<(), &'static str> {
+        let index = self
+            .actions
+            .iter()
+            .position(|a| &a.chain == chain && a.id.to_lowercase() == id.to_lowercase())
+            .ok_or("ActionNotFound")?;
+        self.actions.remove(index);
+        Ok(())
+    }
+
+    fn validate_asset(&self, asset: &Asset) -> Result<(), &'static str> {
+        let chain = self.get_chain(&asset.chain).ok_or("ChainNotFound")?;
+        if asset.name.is_empty() || asset.symbol.is_empty() {
+            return Err("InvalidAsset");
+        }
+        let valid_location = match chain.chain_type {
+            ChainType::Evm => asset.location.len() == 20,
+            ChainType::Sub => !asset.location.is_empty(),
+        };
+        if !valid_location {
+            return Err("InvalidAsset");
+        }
+        Ok(())
+    }
+
+    pub fn create_actions(
+        &self,
+        chain: &String,
+        health: &RpcHealth,
+    ) -> Vec<(String, Box<dyn CallBuilder>)> {
+        let chain = match self.get_chain(chain) {
+            Some(chain) => chain,
+            None => return vec![],
+        };
+        let dest_weights = XcmDestWeights::new(&self.chains);
+        self.actions
+            .iter()
+            .filter(|a| a.chain == chain.name)
+            .filter_map(
+                |a| match actions::create_action(&chain, &a.kind, health, &dest_weights) {
+                    Ok(action) => Some((a.id.clone(), action)),
+                    Err(err) => {
+                        pink_extension::error!("Failed to build action {:?}: {:?}", &a.id, err);
+                        None
+                    }
+                },
+            )
+            .collect()
+    }
+
+    /// Build the action with the given id on the chain
+    pub fn create_action(
+        &self,
+        chain: &String,
+        id: &str,
+        health: &RpcHealth,
+    ) -> Result<Box<dyn CallBuilder>, &'static str> {
+        let chain = self.get_chain(chain).ok_or("ChainNotFound")?;
+        let action = self
+            .actions
+            .iter()
+            .find(|a| a.chain == chain.name && a.id.to_lowercase() == id.to_lowercase())
+            .ok_or("NoActionFound")?;
+        actions::create_action(
+            &chain,
+            &action.kind,
+            health,
+            &XcmDestWeights::new(&self.chains),
+        )
     }
 
     pub fn get_action_extra_info(&self, chain: &str, action: &str) -> Option<ActionExtraInfo> {
-        match chain {
-            "Acala" => crate::actions::acala::get_extra_info(chain, action),
-            "AstarEvm" => crate::actions::astar::get_extra_info(chain, action),
-            "Astar" => crate::actions::astar::get_extra_info(chain, action),
-            "Ethereum" => crate::actions::ethereum::get_extra_info(chain, action),
-            "Moonbeam" => crate::actions::moonbeam::get_extra_info(chain, action),
-            "Phala" | "Khala" => crate::actions::phala::get_extra_info(chain, action),
-            _ => None,
+        self.actions
+            .iter()
+            .find(|a| a.chain == chain && a.id.to_lowercase() == action.to_lowercase())
+            .map(|a| a.extra_info.clone())
+    }
+}
+
+fn ether_location() -> MultiLocation {
+    MultiLocation::new(2, X1(GlobalConsensus(NetworkId::Ethereum { chain_id: 1 })))
+}
+
+fn pha_location() -> MultiLocation {
+    MultiLocation::new(1, X1(Parachain(PHALA_PARACHAIN_ID)))
+}
+
+fn glmr_location() -> MultiLocation {
+    MultiLocation::new(1, X2(Parachain(MOONBEAM_PARACHAIN_ID), PalletInstance(10)))
+}
+
+fn astr_location() -> MultiLocation {
+    MultiLocation::new(1, X1(Parachain(ASTAR_PARACHAIN_ID)))
+}
+
+fn hdx_location() -> MultiLocation {
+    MultiLocation::new(1, X2(Parachain(HYDRADX_PARACHAIN_ID), GeneralIndex(0)))
+}
+
+/// Location of the stablecoin issued on AssetHub, as seen from parachains
+fn assethub_asset_location(asset_id: u128) -> MultiLocation {
+    MultiLocation::new(
+        1,
+        X3(
+            Parachain(ASSETHUB_PARACHAIN_ID),
+            PalletInstance(ASSETHUB_ASSETS_PALLET_ID),
+            GeneralIndex(asset_id),
+        ),
+    )
+}
+
+/// USDT and USDC on AssetHub and their representations on other parachains
+fn stablecoin_assets() -> Vec<Asset> {
+    let stablecoins = [
+        (
+            "Tether USD",
+            "USDT",
+            ASSETHUB_USDT_ID,
+            hex_literal::hex!("FFFFFFFFea09fb06d082fd1275cd48b191cbcd1d"),
+        ),
+        (
+            "USD Coin",
+            "USDC",
+            ASSETHUB_USDC_ID,
+            hex_literal::hex!("FFfffffF7D2B0B761Af01Ca8e25242976ac0aD7D"),
+        ),
+    ];
+    let mut assets = vec![];
+    for (name, symbol, asset_id, moonbeam_address) in stablecoins {
+        let canonical_id = assethub_asset_location(asset_id).encode();
+        let local_location = MultiLocation::new(
+            0,
+            X2(
+                PalletInstance(ASSETHUB_ASSETS_PALLET_ID),
+                GeneralIndex(asset_id),
+            ),
+        );
+        for (chain, location) in [
+            ("AssetHub", local_location.encode()),
+            ("Phala", canonical_id.clone()),
+            ("Astar", canonical_id.clone()),
+            ("Acala", canonical_id.clone()),
+            ("Moonbeam", moonbeam_address.to_vec()),
+        ] {
+            assets.push(Asset {
+                chain: chain.to_string(),
+                name: name.to_string(),
+                symbol: symbol.to_string(),
+                location,
+                decimals: 6,
+                canonical_id: Some(canonical_id.clone()),
+            });
+        }
+    }
+    assets
+}
+
+// Assets of Kusama ecosystem are identified with their global consensus, since parachain
+// locations as seen from Kusama parachains conflict with the ones of Polkadot
+fn ksm_location() -> MultiLocation {
+    MultiLocation::new(2, X1(GlobalConsensus(NetworkId::Kusama)))
+}
+
+fn kar_location() -> MultiLocation {
+    MultiLocation::new(
+        2,
+        X3(
+            GlobalConsensus(NetworkId::Kusama),
+            Parachain(KARURA_PARACHAIN_ID),
+            slice_to_generalkey(&[0x00, 0x80]),
+        ),
+    )
+}
+
+fn movr_location() -> MultiLocation {
+    MultiLocation::new(
+        2,
+        X3(
+            GlobalConsensus(NetworkId::Kusama),
+            Parachain(MOONRIVER_PARACHAIN_ID),
+            PalletInstance(10),
+        ),
+    )
+}
+
+fn sdn_location() -> MultiLocation {
+    MultiLocation::new(
+        2,
+        X2(
+            GlobalConsensus(NetworkId::Kusama),
+            Parachain(SHIDEN_PARACHAIN_ID),
+        ),
+    )
+}
+
+/// Fee information of an action, USD amounts are the value / 10000
+fn fee_info(
+    extra_proto_fee_in_usd: u32,
+    const_proto_fee_in_usd: u32,
+    percentage_proto_fee: Permill,
+    confirm_time_in_sec: u16,
+) -> ActionExtraInfo {
+    ActionExtraInfo {
+        extra_proto_fee_in_usd,
+        const_proto_fee_in_usd,
+        percentage_proto_fee,
+        confirm_time_in_sec,
+    }
+}
+
+// Variant index of `Balances.Deposit`, `Balances.Minted`, `Assets.Issued` and `Tokens.Deposited`
+const BALANCES_DEPOSIT: u8 = 7;
+const BALANCES_MINTED: u8 = 10;
+const ASSETS_ISSUED: u8 = 1;
+const TOKENS_DEPOSITED: u8 = 10;
+
+/// Pallets and events reporting XCM messages of the chain in its current runtime, delivery of
+/// XCM sent from or to other chains is checked by balance change of the recipient
+fn default_xcm_events(chain: &str) -> Option<XcmEvents> {
+    let xcmp_queue = |pallet_id: u8| {
+        Some(XcmSender::XcmpQueue {
+            pallet_id,
+            event_id: 4,
+            optional_hash: true,
+        })
+    };
+    let deposit = |pallet_id: u8, event_id: u8, asset_id_len: u8| DepositEvent {
+        pallet_id,
+        event_id,
+        asset_id_len,
+    };
+    let (sender, queues, deposits) = match chain {
+        "Phala" | "Khala" => (
+            xcmp_queue(30),
+            vec![XcmQueue::XcmpQueue(30), XcmQueue::DmpQueue(32)],
+            vec![
+                deposit(40, BALANCES_DEPOSIT, 0),
+                deposit(40, BALANCES_MINTED, 0),
+                deposit(90, ASSETS_ISSUED, 4),
+            ],
+        ),
+        "Astar" | "Shiden" => (
+            xcmp_queue(50),
+            vec![XcmQueue::XcmpQueue(50), XcmQueue::DmpQueue(53)],
+            vec![
+                deposit(31, BALANCES_DEPOSIT, 0),
+                deposit(31, BALANCES_MINTED, 0),
+                deposit(36, ASSETS_ISSUED, 16),
+            ],
+        ),
+        // `CurrencyId` of tokens, foreign assets, stable asset pool tokens and ERC20 tokens
+        "Acala" | "Karura" => (
+            xcmp_queue(50),
+            vec![XcmQueue::XcmpQueue(50), XcmQueue::DmpQueue(53)],
+            vec![
+                deposit(10, BALANCES_DEPOSIT, 0),
+                deposit(11, TOKENS_DEPOSITED, 2),
+                deposit(11, TOKENS_DEPOSITED, 3),
+                deposit(11, TOKENS_DEPOSITED, 5),
+                deposit(11, TOKENS_DEPOSITED, 21),
+            ],
+        ),
+        "AssetHub" => (
+            xcmp_queue(30),
+            vec![
+                XcmQueue::XcmpQueue(30),
+                XcmQueue::DmpQueue(33),
+                XcmQueue::MessageQueue(35),
+            ],
+            vec![
+                deposit(10, BALANCES_DEPOSIT, 0),
+                deposit(10, BALANCES_MINTED, 0),
+                deposit(50, ASSETS_ISSUED, 4),
+            ],
+        ),
+        "HydraDX" => (
+            xcmp_queue(50),
+            vec![XcmQueue::XcmpQueue(50), XcmQueue::DmpQueue(53)],
+            vec![
+                deposit(7, BALANCES_DEPOSIT, 0),
+                deposit(77, TOKENS_DEPOSITED, 4),
+            ],
+        ),
+        "Polkadot" | "Kusama" => (Some(XcmSender::Dmp), vec![], vec![]),
+        // Accounts of Moonbeam and Moonriver are 20 bytes, which are matched the same way.
+        // Messages sent through the xtokens precompile are not tracked
+        "Moonbeam" | "Moonriver" => (
+            None,
+            vec![
+                XcmQueue::XcmpQueue(100),
+                XcmQueue::DmpQueue(102),
+                XcmQueue::MessageQueue(111),
+            ],
+            vec![
+                deposit(10, BALANCES_DEPOSIT, 0),
+                deposit(104, ASSETS_ISSUED, 16),
+            ],
+        ),
+        _ => return None,
+    };
+    Some(XcmEvents {
+        sender,
+        queues,
+        deposits,
+    })
+}
+
+/// Actions deployed along with the contract, more can be added with `Registry::set_action`
+fn default_actions() -> Vec<ActionInfo> {
+    let sygma_bridge = |to_domain: u8| ActionKind::SygmaBridge {
+        bridge: hex_literal::hex!("4D878E8Fb90178588Cda4cf1DCcdC9a6d2757089"),
+        erc20_handler: hex_literal::hex!("C832588193cd5ED2185daDA4A531e0B26eC5B830"),
+        fee_handler: hex_literal::hex!("e43F8245249d7fAF46408723Ab36D071dD85D7BB"),
+        from_domain: SYGMA_ETHEREUM_DOMAIN_ID,
+        to_domain,
+        dest_parachain: None,
+        resources: vec![SygmaResource {
+            // PHA
+            asset: hex_literal::hex!("6c5bA91642F10282b576d91922Ae6448C9d52f4E"),
+            resource_id: hex_literal::hex!(
+                "0000000000000000000000000000000000000000000000000000000000000001"
+            ),
+        }],
+    };
+    let moonbeam_xtoken = |dest_parachain: Option<u32>, xcm: XcmConfig| ActionKind::XTokenBridge {
+        xtoken: hex_literal::hex!("0000000000000000000000000000000000000804"),
+        dest_parachain,
+        xcm,
+    };
+    let phala_xcm = |dest_parachain: Option<u32>, account_type: AccountType, xcm: XcmConfig| {
+        ActionKind::XTransferXcm {
+            dest_parachain,
+            account_type,
+            xcm,
+        }
+    };
+    let polkadot_xcm =
+        |dest_parachain: u32, is_evm: bool, xcm: XcmConfig| ActionKind::PolkadotXcmV3 {
+            dest_parachain,
+            account_type: AccountType::Account32,
+            is_evm,
+            xcm,
+        };
+    let karura_xtokens = |dest_parachain: u32, xcm: XcmConfig| ActionKind::OrmlXtokens {
+        pallet_id: 0x36,
+        dest_parachain: Some(dest_parachain),
+        xcm,
+    };
+    let hydradx_xtokens = |dest_parachain: Option<u32>, xcm: XcmConfig| ActionKind::OrmlXtokens {
+        pallet_id: 0x89,
+        dest_parachain,
+        xcm,
+    };
+    let assethub_xcm =
+        |dest_parachain: u32, account_type: AccountType, xcm: XcmConfig| ActionKind::AssetHubXcm {
+            dest_parachain,
+            account_type,
+            xcm,
+        };
+    // 0.0005 USD
+    let xcm_fee = fee_info(0, 5, Permill::zero(), PARACHAIN_BLOCK_TIME * 2);
+
+    vec![
+        // Ethereum
+        ActionInfo::new(
+            "Ethereum",
+            "ethereum_nativewrapper",
+            ActionKind::NativeWrapper {
+                weth: hex_literal::hex!("C02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2"),
+                native: [0; 20],
+            },
+            fee_info(0, 0, Permill::zero(), ETHEREUM_BLOCK_TIME),
+        ),
+        ActionInfo::new(
+            "Ethereum",
+            "ethereum_transactor",
+            ActionKind::EvmTransactor,
+            fee_info(0, 0, Permill::zero(), ETHEREUM_BLOCK_TIME),
+        ),
+        ActionInfo::new(
+            "Ethereum",
+            "ethereum_uniswapv2",
+            // Route through WETH and USDC
+            ActionKind::UniswapV2Multihop {
+                router: hex_literal::hex!("7a250d5630B4cF539739dF2C5dAcb4c659F2488D"),
+                intermediates: vec![
+                    hex_literal::hex!("C02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2"),
+                    hex_literal::hex!("A0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48"),
+                ],
+                fee_on_transfer_tokens: vec![],
+            },
+            fee_info(0, 0, Permill::from_perthousand(3), ETHEREUM_BLOCK_TIME),
+        ),
+        ActionInfo::new(
+            "Ethereum",
+            "ethereum_uniswapv3",
+            ActionKind::UniswapV3 {
+                router: hex_literal::hex!("E592427A0AEce92De3Edee1F18E0157C05861564"),
+                factory: hex_literal::hex!("1F98431c8aD98523631AE4a59f267346ea31F984"),
+                quoter: hex_literal::hex!("b27308f9F90D607463bb33eA1BeBb41C27CE5AB6"),
+                weth: hex_literal::hex!("C02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2"),
+            },
+            fee_info(0, 0, Permill::zero(), ETHEREUM_BLOCK_TIME),
+        ),
+        ActionInfo::new(
+            "Ethereum",
+            "ethereum_1inch",
+            // API key is set by the admin with `set_action`
+            ActionKind::Aggregator {
+                api: actions::ethereum::aggregator::AggregatorApi::OneInch,
+                api_url: "https://api.1inch.dev/swap/v5.2/1".to_string(),
+                // AggregationRouterV5
+                allowlist: vec![hex_literal::hex!(
+                    "1111111254EEB25477B68fb85Ed929f73A960582"
+                )],
+                api_key: None,
+            },
+            fee_info(0, 0, Permill::zero(), ETHEREUM_BLOCK_TIME),
+        ),
+        // Fee of the pool is quoted from the pool with the swap
+        ActionInfo::new(
+            "Ethereum",
+            "ethereum_curve_3pool",
+            ActionKind::CurveStableSwap {
+                pool: hex_literal::hex!("bEbc44782C7dB0a1A60Cb6fe97d0b483032FF1C7"),
+                underlying: false,
+            },
+            fee_info(0, 0, Permill::zero(), ETHEREUM_BLOCK_TIME),
+        ),
+        // Sygma relayer wait 15 blocks to forward and 1 block on Phala/Khala to confirm, 0.2 USD
+        ActionInfo::new(
+            "Ethereum",
+            "ethereum_sygmabridge_to_phala",
+            sygma_bridge(SYGMA_PHALA_DOMAIN_ID),
+            fee_info(
+                2000,
+                0,
+                Permill::zero(),
+                ETHEREUM_BLOCK_TIME * 15 + PARACHAIN_BLOCK_TIME,
+            ),
+        ),
+        ActionInfo::new(
+            "Ethereum",
+            "ethereum_sygmabridge_to_khala",
+            sygma_bridge(SYGMA_KHALA_DOMAIN_ID),
+            fee_info(
+                2000,
+                0,
+                Permill::zero(),
+                ETHEREUM_BLOCK_TIME * 15 + PARACHAIN_BLOCK_TIME,
+            ),
+        ),
+        // Moonbeam
+        ActionInfo::new(
+            "Moonbeam",
+            "moonbeam_nativewrapper",
+            ActionKind::NativeWrapper {
+                weth: hex_literal::hex!("Acc15dC74880C9944775448304B263D191c6077F"),
+                native: hex_literal::hex!("0000000000000000000000000000000000000802"),
+            },
+            fee_info(0, 0, Permill::zero(), PARACHAIN_BLOCK_TIME),
+        ),
+        ActionInfo::new(
+            "Moonbeam",
+            "moonbeam_transactor",
+            ActionKind::EvmTransactor,
+            fee_info(0, 0, Permill::zero(), PARACHAIN_BLOCK_TIME),
+        ),
+        ActionInfo::new(
+            "Moonbeam",
+            "moonbeam_stellaswap",
+            ActionKind::AlgebraV3 {
+                router: hex_literal::hex!("e6d0ED3759709b743707DcfeCAe39BC180C981fe"),
+            },
+            fee_info(0, 0, Permill::from_perthousand(3), PARACHAIN_BLOCK_TIME),
+        ),
+        ActionInfo::new(
+            "Moonbeam",
+            "moonbeam_bridge_to_acala",
+            moonbeam_xtoken(Some(ACALA_PARACHAIN_ID), XcmConfig::default()),
+            xcm_fee.clone(),
+        ),
+        ActionInfo::new(
+            "Moonbeam",
+            "moonbeam_bridge_to_astar",
+            moonbeam_xtoken(Some(ASTAR_PARACHAIN_ID), XcmConfig::default()),
+            xcm_fee.clone(),
+        ),
+        ActionInfo::new(
+            "Moonbeam",
+            "moonbeam_bridge_to_phala",
+            moonbeam_xtoken(Some(PHALA_PARACHAIN_ID), XcmConfig::default()),
+            xcm_fee.clone(),
+        ),
+        ActionInfo::new(
+            "Moonbeam",
+            "moonbeam_bridge_to_polkadot",
+            moonbeam_xtoken(None, XcmConfig::default()),
+            xcm_fee.clone(),
+        ),
+        // AstarEvm
+        ActionInfo::new(
+            "AstarEvm",
+            "astar_evm_nativewrapper",
+            ActionKind::NativeWrapper {
+                weth: hex_literal::hex!("Aeaaf0e2c81Af264101B9129C00F4440cCF0F720"),
+                native: [0; 20],
+            },
+            fee_info(0, 0, Permill::zero(), PARACHAIN_BLOCK_TIME),
+        ),
+        ActionInfo::new(
+            "AstarEvm",
+            "astar_evm_transactor",
+            ActionKind::EvmTransactor,
+            fee_info(0, 0, Permill::zero(), PARACHAIN_BLOCK_TIME),
+        ),
+        ActionInfo::new(
+            "AstarEvm",
+            "astar_evm_arthswap",
+            ActionKind::UniswapV2 {
+                router: hex_literal::hex!("E915D2393a08a00c5A463053edD31bAe2199b9e7"),
+            },
+            fee_info(0, 0, Permill::from_perthousand(3), PARACHAIN_BLOCK_TIME),
+        ),
+        // Astar
+        ActionInfo::new(
+            "Astar",
+            "astar_transactor",
+            ActionKind::AstarTransactor,
+            fee_info(0, 0, Permill::zero(), PARACHAIN_BLOCK_TIME),
+        ),
+        ActionInfo::new(
+            "Astar",
+            "astar_bridge_to_astarevm",
+            ActionKind::AstarSubToEvm,
+            fee_info(0, 0, Permill::zero(), PARACHAIN_BLOCK_TIME),
+        ),
+        ActionInfo::new(
+            "Astar",
+            "astar_bridge_to_phala",
+            ActionKind::AstarXtokens {
+                dest_parachain: PHALA_PARACHAIN_ID,
+                xcm: XcmConfig::default(),
+            },
+            xcm_fee.clone(),
+        ),
+        // Acala
+        ActionInfo::new(
+            "Acala",
+            "acala_dex",
+            ActionKind::AcalaSwap {
+                hub_token: TokenSymbol::AUSD,
+                stable_pool: Some(TaigaPool {
+                    pool_id: 0,
+                    token: TokenSymbol::DOT,
+                    liquid_token: TokenSymbol::LDOT,
+                }),
+            },
+            fee_info(0, 0, Permill::from_perthousand(3), PARACHAIN_BLOCK_TIME),
+        ),
+        ActionInfo::new(
+            "Acala",
+            "acala_transactor",
+            ActionKind::AcalaTransactor,
+            fee_info(0, 0, Permill::zero(), PARACHAIN_BLOCK_TIME),
+        ),
+        // Phala
+        ActionInfo::new(
+            "Phala",
+            "phala_bridge_to_acala",
+            phala_xcm(
+                Some(ACALA_PARACHAIN_ID),
+                AccountType::Account32,
+                XcmConfig::default(),
+            ),
+            xcm_fee.clone(),
+        ),
+        ActionInfo::new(
+            "Phala",
+            "phala_bridge_to_astar",
+            phala_xcm(
+                Some(ASTAR_PARACHAIN_ID),
+                AccountType::Account32,
+                XcmConfig::default(),
+            ),
+            xcm_fee.clone(),
+        ),
+        ActionInfo::new(
+            "Phala",
+            "phala_bridge_to_moonbeam",
+            phala_xcm(
+                Some(MOONBEAM_PARACHAIN_ID),
+                AccountType::Account20,
+                XcmConfig::default(),
+            ),
+            xcm_fee.clone(),
+        ),
+        ActionInfo::new(
+            "Phala",
+            "phala_bridge_to_polkadot",
+            phala_xcm(None, AccountType::Account20, XcmConfig::default()),
+            fee_info(0, 5, Permill::zero(), PARACHAIN_BLOCK_TIME),
+        ),
+        // Sygma relayer wait 2 blocks to finialize and 1 block on Ethereum to confirm, 5 USD
+        ActionInfo::new(
+            "Phala",
+            "phala_bridge_to_ethereum",
+            ActionKind::XTransferSygma {
+                dest_domain: SYGMA_ETHEREUM_DOMAIN_ID,
+            },
+            fee_info(
+                0,
+                50000,
+                Permill::zero(),
+                PARACHAIN_BLOCK_TIME * 2 + ETHEREUM_BLOCK_TIME,
+            ),
+        ),
+        ActionInfo::new(
+            "Phala",
+            "phala_native_transactor",
+            ActionKind::SubTransactor {
+                pallet_id: 0x28,
+                call_index: 0x07,
+            },
+            fee_info(0, 0, Permill::zero(), PARACHAIN_BLOCK_TIME),
+        ),
+        // Khala, 0.5 USD for bridging to Ethereum
+        ActionInfo::new(
+            "Khala",
+            "khala_bridge_to_ethereum",
+            ActionKind::XTransferSygma {
+                dest_domain: SYGMA_ETHEREUM_DOMAIN_ID,
+            },
+            fee_info(
+                0,
+                5000,
+                Permill::zero(),
+                PARACHAIN_BLOCK_TIME * 2 + ETHEREUM_BLOCK_TIME,
+            ),
+        ),
+        ActionInfo::new(
+            "Khala",
+            "khala_native_transactor",
+            ActionKind::SubTransactor {
+                pallet_id: 0x28,
+                call_index: 0x07,
+            },
+            fee_info(0, 0, Permill::zero(), PARACHAIN_BLOCK_TIME),
+        ),
+        // Polkadot
+        ActionInfo::new(
+            "Polkadot",
+            "polkadot_bridge_to_phala",
+            polkadot_xcm(PHALA_PARACHAIN_ID, false, XcmConfig::default()),
+            xcm_fee.clone(),
+        ),
+        ActionInfo::new(
+            "Polkadot",
+            "polkadot_bridge_to_moonbeam",
+            ActionKind::PolkadotXcmV2 {
+                dest_parachain: MOONBEAM_PARACHAIN_ID,
+                account_type: AccountType::Account20,
+                xcm: XcmConfig::default(),
+            },
+            xcm_fee.clone(),
+        ),
+        ActionInfo::new(
+            "Polkadot",
+            "polkadot_bridge_to_astar_evm",
+            polkadot_xcm(ASTAR_PARACHAIN_ID, true, XcmConfig::default()),
+            xcm_fee.clone(),
+        ),
+        ActionInfo::new(
+            "Polkadot",
+            "polkadot_bridge_to_astar",
+            polkadot_xcm(ASTAR_PARACHAIN_ID, false, XcmConfig::default()),
+            xcm_fee.clone(),
+        ),
+        ActionInfo::new(
+            "Polkadot",
+            "polkadot_native_transactor",
+            ActionKind::SubTransactor {
+                pallet_id: 0x05,
+                call_index: 0x07,
+            },
+            fee_info(0, 0, Permill::zero(), PARACHAIN_BLOCK_TIME),
+        ),
+        // Khala
+        ActionInfo::new(
+            "Khala",
+            "khala_bridge_to_karura",
+            phala_xcm(
+                Some(KARURA_PARACHAIN_ID),
+                AccountType::Account32,
+                XcmConfig::default(),
+            ),
+            xcm_fee.clone(),
+        ),
+        ActionInfo::new(
+            "Khala",
+            "khala_bridge_to_moonriver",
+            phala_xcm(
+                Some(MOONRIVER_PARACHAIN_ID),
+                AccountType::Account20,
+                XcmConfig::default(),
+            ),
+            xcm_fee.clone(),
+        ),
+        ActionInfo::new(
+            "Khala",
+            "khala_bridge_to_shiden",
+            phala_xcm(
+                Some(SHIDEN_PARACHAIN_ID),
+                AccountType::Account32,
+                XcmConfig::default(),
+            ),
+            xcm_fee.clone(),
+        ),
+        ActionInfo::new(
+            "Khala",
+            "khala_bridge_to_kusama",
+            phala_xcm(None, AccountType::Account32, XcmConfig::default()),
+            fee_info(0, 5, Permill::zero(), PARACHAIN_BLOCK_TIME),
+        ),
+        // Karura
+        ActionInfo::new(
+            "Karura",
+            "karura_dex",
+            ActionKind::AcalaSwap {
+                hub_token: TokenSymbol::KUSD,
+                stable_pool: Some(TaigaPool {
+                    pool_id: 0,
+                    token: TokenSymbol::KSM,
+                    liquid_token: TokenSymbol::LKSM,
+                }),
+            },
+            fee_info(0, 0, Permill::from_perthousand(3), PARACHAIN_BLOCK_TIME),
+        ),
+        ActionInfo::new(
+            "Karura",
+            "karura_transactor",
+            ActionKind::AcalaTransactor,
+            fee_info(0, 0, Permill::zero(), PARACHAIN_BLOCK_TIME),
+        ),
+        ActionInfo::new(
+            "Karura",
+            "karura_bridge_to_khala",
+            karura_xtokens(KHALA_PARACHAIN_ID, XcmConfig::default()),
+            xcm_fee.clone(),
+        ),
+        ActionInfo::new(
+            "Karura",
+            "karura_bridge_to_moonriver",
+            karura_xtokens(MOONRIVER_PARACHAIN_ID, XcmConfig::default()),
+            xcm_fee.clone(),
+        ),
+        ActionInfo::new(
+            "Karura",
+            "karura_bridge_to_shiden",
+            karura_xtokens(SHIDEN_PARACHAIN_ID, XcmConfig::default()),
+            xcm_fee.clone(),
+        ),
+        // Moonriver: actions are registered once the handler is deployed, until then
+        // Moonriver is only a destination
+        // Shiden
+        ActionInfo::new(
+            "Shiden",
+            "shiden_transactor",
+            ActionKind::AstarTransactor,
+            fee_info(0, 0, Permill::zero(), PARACHAIN_BLOCK_TIME),
+        ),
+        ActionInfo::new(
+            "Shiden",
+            "shiden_bridge_to_khala",
+            ActionKind::AstarXtokens {
+                dest_parachain: KHALA_PARACHAIN_ID,
+                xcm: XcmConfig::default(),
+            },
+            xcm_fee.clone(),
+        ),
+        ActionInfo::new(
+            "Shiden",
+            "shiden_bridge_to_karura",
+            ActionKind::AstarXtokens {
+                dest_parachain: KARURA_PARACHAIN_ID,
+                xcm: XcmConfig::default(),
+            },
+            xcm_fee.clone(),
+        ),
+        ActionInfo::new(
+            "Shiden",
+            "shiden_bridge_to_moonriver",
+            ActionKind::AstarXtokens {
+                dest_parachain: MOONRIVER_PARACHAIN_ID,
+                xcm: XcmConfig::default(),
+            },
+            xcm_fee.clone(),
+        ),
+        // Kusama
+        ActionInfo::new(
+            "Kusama",
+            "kusama_bridge_to_khala",
+            polkadot_xcm(KHALA_PARACHAIN_ID, false, XcmConfig::default()),
+            xcm_fee.clone(),
+        ),
+        ActionInfo::new(
+            "Kusama",
+            "kusama_bridge_to_karura",
+            polkadot_xcm(KARURA_PARACHAIN_ID, false, XcmConfig::default()),
+            xcm_fee.clone(),
+        ),
+        ActionInfo::new(
+            "Kusama",
+            "kusama_bridge_to_moonriver",
+            ActionKind::PolkadotXcmV3 {
+                dest_parachain: MOONRIVER_PARACHAIN_ID,
+                account_type: AccountType::Account20,
+                is_evm: false,
+                xcm: XcmConfig::default(),
+            },
+            xcm_fee.clone(),
+        ),
+        ActionInfo::new(
+            "Kusama",
+            "kusama_bridge_to_shiden",
+            polkadot_xcm(SHIDEN_PARACHAIN_ID, false, XcmConfig::default()),
+            xcm_fee.clone(),
+        ),
+        ActionInfo::new(
+            "Kusama",
+            "kusama_native_transactor",
+            ActionKind::SubTransactor {
+                pallet_id: 0x04,
+                call_index: 0x07,
+            },
+            fee_info(0, 0, Permill::zero(), PARACHAIN_BLOCK_TIME),
+        ),
+        // AssetHub
+        ActionInfo::new(
+            "AssetHub",
+            "assethub_bridge_to_phala",
+            assethub_xcm(
+                PHALA_PARACHAIN_ID,
+                AccountType::Account32,
+                XcmConfig::default(),
+            ),
+            xcm_fee.clone(),
+        ),
+        ActionInfo::new(
+            "AssetHub",
+            "assethub_bridge_to_moonbeam",
+            assethub_xcm(
+                MOONBEAM_PARACHAIN_ID,
+                AccountType::Account20,
+                XcmConfig::default(),
+            ),
+            xcm_fee.clone(),
+        ),
+        ActionInfo::new(
+            "AssetHub",
+            "assethub_bridge_to_astar",
+            assethub_xcm(
+                ASTAR_PARACHAIN_ID,
+                AccountType::Account32,
+                XcmConfig::default(),
+            ),
+            xcm_fee.clone(),
+        ),
+        ActionInfo::new(
+            "AssetHub",
+            "assethub_bridge_to_acala",
+            assethub_xcm(
+                ACALA_PARACHAIN_ID,
+                AccountType::Account32,
+                XcmConfig::default(),
+            ),
+            xcm_fee.clone(),
+        ),
+        ActionInfo::new(
+            "Phala",
+            "phala_bridge_to_assethub",
+            phala_xcm(
+                Some(ASSETHUB_PARACHAIN_ID),
+                AccountType::Account32,
+                XcmConfig::default(),
+            ),
+            xcm_fee.clone(),
+        ),
+        ActionInfo::new(
+            "Moonbeam",
+            "moonbeam_bridge_to_assethub",
+            moonbeam_xtoken(Some(ASSETHUB_PARACHAIN_ID), XcmConfig::default()),
+            xcm_fee.clone(),
+        ),
+        ActionInfo::new(
+            "Astar",
+            "astar_bridge_to_assethub",
+            ActionKind::AstarXtokens {
+                dest_parachain: ASSETHUB_PARACHAIN_ID,
+                xcm: XcmConfig::default(),
+            },
+            xcm_fee.clone(),
+        ),
+        ActionInfo::new(
+            "Acala",
+            "acala_bridge_to_assethub",
+            ActionKind::OrmlXtokens {
+                pallet_id: 0x36,
+                dest_parachain: Some(ASSETHUB_PARACHAIN_ID),
+                xcm: XcmConfig::default(),
+            },
+            xcm_fee.clone(),
+        ),
+        // HydraDX
+        ActionInfo::new(
+            "HydraDX",
+            "hydradx_omnipool",
+            ActionKind::HydraDxSwap,
+            fee_info(0, 0, Permill::from_perthousand(3), PARACHAIN_BLOCK_TIME),
+        ),
+        ActionInfo::new(
+            "HydraDX",
+            "hydradx_native_transactor",
+            ActionKind::SubTransactor {
+                pallet_id: 0x07,
+                call_index: 0x07,
+            },
+            fee_info(0, 0, Permill::zero(), PARACHAIN_BLOCK_TIME),
+        ),
+        ActionInfo::new(
+            "HydraDX",
+            "hydradx_bridge_to_phala",
+            hydradx_xtokens(Some(PHALA_PARACHAIN_ID), XcmConfig::default()),
+            xcm_fee.clone(),
+        ),
+        ActionInfo::new(
+            "HydraDX",
+            "hydradx_bridge_to_moonbeam",
+            hydradx_xtokens(Some(MOONBEAM_PARACHAIN_ID), XcmConfig::default()),
+            xcm_fee.clone(),
+        ),
+        ActionInfo::new(
+            "HydraDX",
+            "hydradx_bridge_to_polkadot",
+            hydradx_xtokens(None, XcmConfig::default()),
+            xcm_fee.clone(),
+        ),
+        ActionInfo::new(
+            "Phala",
+            "phala_bridge_to_hydradx",
+            phala_xcm(
+                Some(HYDRADX_PARACHAIN_ID),
+                AccountType::Account32,
+                XcmConfig::default(),
+            ),
+            xcm_fee.clone(),
+        ),
+        ActionInfo::new(
+            "Moonbeam",
+            "moonbeam_bridge_to_hydradx",
+            moonbeam_xtoken(Some(HYDRADX_PARACHAIN_ID), XcmConfig::default()),
+            xcm_fee.clone(),
+        ),
+        ActionInfo::new(
+            "Polkadot",
+            "polkadot_bridge_to_hydradx",
+            polkadot_xcm(HYDRADX_PARACHAIN_ID, false, XcmConfig::default()),
+            xcm_fee,
+        ),
+    ]
+}
+
+/// Check fields of the chain are consistent with its chain type
+fn validate_chain(chain: &Chain) -> Result<(), &'static str> {
+    if chain.name.is_empty()
+        || chain.endpoints.is_empty()
+        || chain
+            .endpoints
+            .iter()
+            .any(|endpoint| endpoint.url.is_empty())
+    {
+        return Err("InvalidChain");
+    }
+    let valid = match chain.chain_type {
+        // Handler is the contract address, native asset is the zero address or a precompile
+        ChainType::Evm => chain.handler_contract.len() == 20 && chain.native_asset.len() == 20,
+        // Handler is the pallet index, native asset is an encoded location
+        ChainType::Sub => {
+            chain.handler_contract.len() == 1
+                && chain.foreign_asset.is_some()
+                && !chain.native_asset.is_empty()
         }
+    };
+    if !valid {
+        return Err("InvalidChain");
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_manage_chains_and_assets() {
+        let mut registry = Registry::new();
+        let moonbase = Chain {
+            id: 100,
+            name: "Moonbase".to_string(),
+            endpoints: vec![RpcEndpoint::new(
+                "https://rpc.api.moonbase.moonbeam.network",
+                0,
+            )],
+            chain_type: ChainType::Evm,
+            native_asset: hex_literal::hex!("0000000000000000000000000000000000000802").to_vec(),
+            handler_contract: hex_literal::hex!("0000000000000000000000000000000000000001")
+                .to_vec(),
+            ..Default::default()
+        };
+
+        // Chain must be consistent with its type
+        assert_eq!(
+            registry.add_chain(Chain {
+                handler_contract: vec![0x79],
+                ..moonbase.clone()
+            }),
+            Err("InvalidChain")
+        );
+        assert_eq!(
+            registry.add_chain(Chain {
+                endpoints: vec![],
+                ..moonbase.clone()
+            }),
+            Err("InvalidChain")
+        );
+        assert_eq!(registry.add_chain(moonbase.clone()), Ok(()));
+        assert_eq!(
+            registry.add_chain(moonbase.clone()),
+            Err("ChainAlreadyExist")
+        );
+        // Chain id is unique as well
+        assert_eq!(
+            registry.update_chain(Chain {
+                id: 0,
+                ..moonbase.clone()
+            }),
+            Err("ChainAlreadyExist")
+        );
+        assert_eq!(
+            registry.update_chain(Chain {
+                tx_indexer_url: "https://indexer".to_string(),
+                ..moonbase.clone()
+            }),
+            Ok(())
+        );
+        assert_eq!(
+            registry
+                .get_chain(&"Moonbase".to_string())
+                .unwrap()
+                .tx_indexer_url,
+            "https://indexer"
+        );
+
+        let dev = Asset {
+            chain: "Moonbase".to_string(),
+            name: "Moonbase".to_string(),
+            symbol: "DEV".to_string(),
+            location: moonbase.native_asset.clone(),
+            decimals: 18,
+            canonical_id: None,
+        };
+        assert_eq!(
+            registry.register_asset(Asset {
+                chain: "Unknown".to_string(),
+                ..dev.clone()
+            }),
+            Err("ChainNotFound")
+        );
+        assert_eq!(
+            registry.register_asset(Asset {
+                location: vec![0],
+                ..dev.clone()
+            }),
+            Err("InvalidAsset")
+        );
+        assert_eq!(registry.register_asset(dev.clone()), Ok(()));
+        assert_eq!(
+            registry.register_asset(dev.clone()),
+            Err("AssetAlreadyExist")
+        );
+        assert_eq!(
+            registry.update_asset(Asset {
+                decimals: 12,
+                ..dev.clone()
+            }),
+            Ok(())
+        );
+        assert_eq!(
+            registry
+                .get_asset(&dev.chain, &dev.location)
+                .unwrap()
+                .decimals,
+            12
+        );
+
+        // Assets are removed along with the chain
+        assert_eq!(registry.remove_chain(&"Moonbase".to_string()), Ok(()));
+        assert!(registry.get_asset(&dev.chain, &dev.location).is_none());
+        assert_eq!(
+            registry.remove_chain(&"Moonbase".to_string()),
+            Err("ChainNotFound")
+        );
+        assert_eq!(
+            registry.unregister_asset(&dev.chain, &dev.location),
+            Err("AssetNotFound")
+        );
+    }
+
+    #[test]
+    fn test_manage_actions() {
+        let mut registry = Registry::new();
+        let actions = registry.create_actions(&"Moonbeam".to_string(), &RpcHealth::default());
+        assert!(actions.iter().any(|(id, _)| id == "moonbeam_stellaswap"));
+        assert!(registry
+            .create_actions(&"Unknown".to_string(), &RpcHealth::default())
+            .is_empty());
+        assert!(registry
+            .create_action(
+                &"Moonbeam".to_string(),
+                "MOONBEAM_STELLASWAP",
+                &RpcHealth::default()
+            )
+            .is_ok());
+        assert_eq!(
+            registry
+                .create_action(&"Moonbeam".to_string(), "acala_dex", &RpcHealth::default())
+                .err(),
+            Some("NoActionFound")
+        );
+
+        let arthswap = ActionInfo::new(
+            "AstarEvm",
+            "astar_evm_arthswap",
+            ActionKind::UniswapV2 {
+                router: hex_literal::hex!("E915D2393a08a00c5A463053edD31bAe2199b9e7"),
+            },
+            fee_info(0, 0, Permill::from_perthousand(5), PARACHAIN_BLOCK_TIME),
+        );
+        // Kind of the action must match the chain type
+        assert_eq!(
+            registry.set_action(ActionInfo {
+                chain: "Astar".to_string(),
+                ..arthswap.clone()
+            }),
+            Err("InvalidAction")
+        );
+        assert_eq!(
+            registry.set_action(ActionInfo {
+                chain: "Unknown".to_string(),
+                ..arthswap.clone()
+            }),
+            Err("ChainNotFound")
+        );
+        let count = registry.actions.len();
+        assert_eq!(registry.set_action(arthswap.clone()), Ok(()));
+        assert_eq!(registry.actions.len(), count);
+        assert_eq!(
+            registry
+                .get_action_extra_info("AstarEvm", "astar_evm_arthswap")
+                .unwrap()
+                .percentage_proto_fee,
+            Permill::from_perthousand(5)
+        );
+
+        // Ids are matched case-insensitively
+        assert_eq!(
+            registry.remove_action(&arthswap.chain, &arthswap.id.to_uppercase()),
+            Ok(())
+        );
+        assert!(registry
+            .get_action_extra_info("AstarEvm", "astar_evm_arthswap")
+            .is_none());
+        assert_eq!(
+            registry.remove_action(&arthswap.chain, &arthswap.id),
+            Err("ActionNotFound")
+        );
+    }
+
+    #[test]
+    fn test_asset_representations() {
+        let registry = Registry::new();
+        let phala = "Phala".to_string();
+        let ethereum = "Ethereum".to_string();
+        let pha = hex::decode("0000").unwrap();
+        let erc20_pha = hex::decode("6c5bA91642F10282b576d91922Ae6448C9d52f4E").unwrap();
+
+        let representations = registry.get_representations(&phala, &pha);
+        assert_eq!(representations[0].chain, "Phala");
+        assert!(representations
+            .iter()
+            .any(|a| a.chain == "Ethereum" && a.location == erc20_pha));
+        assert_eq!(
+            registry
+                .get_assets_by_canonical_id(&pha_location().encode())
+                .len(),
+            registry.get_assets_by_symbol("pha").len()
+        );
+        assert_eq!(
+            registry.is_same_asset(&phala, &pha, &ethereum, &erc20_pha),
+            Some(true)
+        );
+        assert_eq!(
+            registry.is_same_asset(&phala, &pha, &ethereum, &[0; 20].to_vec()),
+            Some(false)
+        );
+        assert_eq!(
+            registry.is_same_asset(&phala, &pha, &ethereum, &vec![1]),
+            None
+        );
+        assert!(registry.get_representations(&phala, &vec![1]).is_empty());
+    }
+
+    #[test]
+    fn test_default_registry_is_consistent() {
+        let registry = Registry::new();
+        for chain in registry.chains.iter() {
+            assert_eq!(validate_chain(chain), Ok(()), "{}", chain.name);
+        }
+        for asset in registry.assets.iter() {
+            assert_eq!(registry.validate_asset(asset), Ok(()), "{}", asset.symbol);
+        }
+        let mut actions = registry.clone();
+        for action in registry.actions.iter() {
+            assert_eq!(actions.set_action(action.clone()), Ok(()), "{}", action.id);
+        }
+        assert!(registry
+            .create_actions(&"Kusama".to_string(), &RpcHealth::default())
+            .iter()
+            .any(|(id, _)| id == "kusama_bridge_to_khala"));
+        assert!(registry
+            .create_action(
+                &"HydraDX".to_string(),
+                "hydradx_bridge_to_polkadot",
+                &RpcHealth::default()
+            )
+            .is_ok());
+        for chain in registry
+            .chains
+            .iter()
+            .filter(|chain| chain.is_evm_chain() && chain.has_handler())
+        {
+            assert!(
+                registry
+                    .create_actions(&chain.name, &RpcHealth::default())
+                    .iter()
+                    .any(|(id, _)| id.ends_with("_transactor")),
+                "{}",
+                chain.name
+            );
+        }
+    }
+
+    #[test]
+    fn test_redact_api_key() {
+        let mut registry = Registry::new();
+        let mut action = registry
+            .actions
+            .iter()
+            .find(|action| action.id == "ethereum_1inch")
+            .cloned()
+            .unwrap();
+        if let ActionKind::Aggregator { api_key, .. } = &mut action.kind {
+            *api_key = Some("key".to_string());
+        }
+        registry.set_action(action.clone()).unwrap();
+        assert!(registry.actions.contains(&action));
+        assert!(!registry.redacted().actions.contains(&action));
+        assert_eq!(registry.redacted().actions, Registry::new().actions);
+
+        // Key is imported from JSON but not exported
+        let json = crate::graph::export(&registry).unwrap();
+        assert!(!json.contains("api_key"));
+
+        // Key is kept when the exported registry is imported back
+        let mut imported = crate::graph::import(json.as_bytes()).unwrap();
+        assert!(!imported.actions.contains(&action));
+        imported.keep_secrets(&registry);
+        assert!(imported.actions.contains(&action));
+
+        // Key of the imported action replaces the existing one
+        let mut rotated = action.clone();
+        if let ActionKind::Aggregator { api_key, .. } = &mut rotated.kind {
+            *api_key = Some("new key".to_string());
+        }
+        let mut imported = Registry::new();
+        imported.set_action(rotated.clone()).unwrap();
+        imported.keep_secrets(&registry);
+        assert!(imported.actions.contains(&rotated));
+    }
+
+    #[test]
+    fn test_actions_need_handler() {
+        let mut registry = Registry::new();
+        let moonriver = "Moonriver".to_string();
+        assert!(!registry.get_chain(&moonriver).unwrap().has_handler());
+        assert!(registry
+            .create_actions(&moonriver, &RpcHealth::default())
+            .is_empty());
+        assert_eq!(
+            registry.set_action(ActionInfo::new(
+                "Moonriver",
+                "moonriver_transactor",
+                ActionKind::EvmTransactor,
+                fee_info(0, 0, Permill::zero(), PARACHAIN_BLOCK_TIME),
+            )),
+            Err("HandlerNotDeployed")
+        );
+
+        // Handler of a chain with actions can not be reset
+        let mut ethereum = registry.get_chain(&"Ethereum".to_string()).unwrap();
+        ethereum.handler_contract = [0; 20].to_vec();
+        assert_eq!(registry.update_chain(ethereum), Err("HandlerNotDeployed"));
+    }
+
+    #[test]
+    fn test_actions_keep_chain_type() {
+        let mut registry = Registry::new();
+        // Substrate chain turned into a valid EVM chain, its actions would read the handler
+        // and native asset as EVM addresses
+        let mut acala = registry.get_chain(&"Acala".to_string()).unwrap();
+        acala.chain_type = ChainType::Evm;
+        acala.handler_contract = [1; 20].to_vec();
+        acala.native_asset = [0; 20].to_vec();
+        assert_eq!(validate_chain(&acala), Ok(()));
+        assert_eq!(registry.update_chain(acala.clone()), Err("InvalidChain"));
+
+        // Allowed once the actions of the chain are removed
+        registry.actions.retain(|a| a.chain != acala.name);
+        assert_eq!(registry.update_chain(acala), Ok(()));
     }
 }
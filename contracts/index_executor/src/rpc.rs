@@ -0,0 +1,291 @@
+use crate::storage::StorageClient;
+use alloc::{collections::BTreeMap, rc::Rc, string::String, vec::Vec};
+use core::cell::RefCell;
+use pink_web3::{
+    api::{Eth, Namespace},
+    contract::Contract,
+    transports::PinkHttp,
+};
+use scale::{Decode, Encode};
+
+/// Storage key of the endpoint statistics
+const RPC_HEALTH_KEY: &[u8] = b"rpc_health";
+/// Statistics are halved once requests reach this number, so that recent requests weigh more
+const MAX_TRACKED_REQUESTS: u32 = 1000;
+/// Endpoints with error rate above this permill are tried after the healthy ones
+const UNHEALTHY_ERROR_RATE: u32 = 500;
+
+#[derive(Clone, Debug, PartialEq, Eq, Encode, Decode)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+pub struct RpcEndpoint {
+    pub url: String,
+    /// Endpoints with smaller value are tried first
+    pub priority: u8,
+}
+
+impl RpcEndpoint {
+    pub fn new(url: &str, priority: u8) -> Self {
+        Self {
+            url: url.into(),
+            priority,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Encode, Decode)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+pub struct EndpointStat {
+    pub requests: u32,
+    pub errors: u32,
+}
+
+impl EndpointStat {
+    /// Error rate in permill
+    pub fn error_rate(&self) -> u32 {
+        if self.requests == 0 {
+            return 0;
+        }
+        (self.errors as u64 * 1000 / self.requests as u64) as u32
+    }
+
+    fn record(&mut self, success: bool) {
+        if self.requests >= MAX_TRACKED_REQUESTS {
+            self.requests /= 2;
+            self.errors /= 2;
+        }
+        self.requests += 1;
+        if !success {
+            self.errors += 1;
+        }
+    }
+
+    /// Add requests recorded by another execution
+    fn merge(&mut self, other: &EndpointStat) {
+        self.requests = self.requests.saturating_add(other.requests);
+        self.errors = self.errors.saturating_add(other.errors);
+        while self.requests > MAX_TRACKED_REQUESTS {
+            self.requests /= 2;
+            self.errors /= 2;
+        }
+    }
+}
+
+/// Add statistics recorded since loaded to the ones saved meanwhile by other executions
+fn merge_stats(
+    saved: &mut BTreeMap<String, EndpointStat>,
+    recorded: &BTreeMap<String, EndpointStat>,
+) {
+    for (url, stat) in recorded {
+        saved.entry(url.clone()).or_default().merge(stat);
+    }
+}
+
+/// Error statistics of RPC endpoints, keyed by endpoint url.
+///
+/// Statistics are collected during an execution and saved to storage after it,
+/// they are used to decide which endpoint to try first next time. Clones share the
+/// statistics, so action builders keep a clone to record their requests.
+#[derive(Clone, Debug, Default)]
+pub struct RpcHealth {
+    stats: Rc<RefCell<BTreeMap<String, EndpointStat>>>,
+    // Requests recorded since loaded, merged into the saved statistics on save
+    recorded: Rc<RefCell<BTreeMap<String, EndpointStat>>>,
+}
+
+impl RpcHealth {
+    /// Load statistics saved in storage, start from empty if not exist
+    pub fn load(client: &StorageClient) -> Result<Self, &'static str> {
+        let stats: BTreeMap<String, EndpointStat> =
+            client.read(RPC_HEALTH_KEY)?.unwrap_or_default();
+        Ok(Self {
+            stats: Rc::new(RefCell::new(stats)),
+            recorded: Default::default(),
+        })
+    }
+
+    /// Save statistics to storage if anything recorded since loaded. Requests are added to
+    /// the statistics read right before saving, so the ones saved by executions running
+    /// at the same time are kept
+    pub fn save(&self, client: &StorageClient) -> Result<(), &'static str> {
+        if self.recorded.borrow().is_empty() {
+            return Ok(());
+        }
+        let saved: Option<BTreeMap<String, EndpointStat>> = client.read(RPC_HEALTH_KEY)?;
+        let exists = saved.is_some();
+        let mut stats = saved.unwrap_or_default();
+        merge_stats(&mut stats, &self.recorded.borrow());
+        let data = stats.encode();
+        if exists {
+            client.update(RPC_HEALTH_KEY, &data)?;
+        } else {
+            client.insert(RPC_HEALTH_KEY, &data)?;
+        }
+        *self.stats.borrow_mut() = stats;
+        self.recorded.borrow_mut().clear();
+        Ok(())
+    }
+
+    pub fn stat(&self, url: &str) -> EndpointStat {
+        self.stats.borrow().get(url).cloned().unwrap_or_default()
+    }
+
+    pub fn stats(&self) -> Vec<(String, EndpointStat)> {
+        self.stats
+            .borrow()
+            .iter()
+            .map(|(url, stat)| (url.clone(), stat.clone()))
+            .collect()
+    }
+
+    pub fn record(&self, url: &str, success: bool) {
+        self.stats
+            .borrow_mut()
+            .entry(url.into())
+            .or_default()
+            .record(success);
+        self.recorded
+            .borrow_mut()
+            .entry(url.into())
+            .or_default()
+            .record(success);
+    }
+
+    /// Order endpoints by priority, endpoints that keep failing are moved to the end
+    pub fn rank<'a>(&self, endpoints: &'a [RpcEndpoint]) -> Vec<&'a RpcEndpoint> {
+        let mut ranked: Vec<&RpcEndpoint> = endpoints.iter().collect();
+        ranked.sort_by_key(|endpoint| {
+            let error_rate = self.stat(&endpoint.url).error_rate();
+            (
+                error_rate > UNHEALTHY_ERROR_RATE,
+                endpoint.priority,
+                error_rate,
+            )
+        });
+        ranked
+    }
+}
+
+/// Call `f` with endpoints one by one until it succeeds, return the error of the last
+/// endpoint if all of them failed. Result of every call is recorded to `health`.
+pub fn with_failover<T>(
+    endpoints: &[RpcEndpoint],
+    health: &RpcHealth,
+    mut f: impl FnMut(&str) -> Result<T, &'static str>,
+) -> Result<T, &'static str> {
+    let mut last_err = "NoRpcEndpoint";
+    for endpoint in health.rank(endpoints) {
+        match f(&endpoint.url) {
+            Ok(result) => {
+                health.record(&endpoint.url, true);
+                return Ok(result);
+            }
+            Err(e) => {
+                pink_extension::error!(
+                    "RPC request to {:?} failed with error {:?}, try next endpoint",
+                    &endpoint.url,
+                    e
+                );
+                health.record(&endpoint.url, false);
+                last_err = e;
+            }
+        }
+    }
+    Err(last_err)
+}
+
+/// `contract` bound to `endpoint`, to send its queries to another endpoint of the chain
+pub fn contract_on(contract: &Contract<PinkHttp>, endpoint: &str) -> Contract<PinkHttp> {
+    Contract::new(
+        Eth::new(PinkHttp::new(endpoint)),
+        contract.address(),
+        contract.abi().clone(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+
+    #[test]
+    fn test_failover() {
+        let endpoints = vec![
+            RpcEndpoint::new("https://backup", 1),
+            RpcEndpoint::new("https://primary", 0),
+        ];
+        let health = RpcHealth::default();
+
+        // Primary endpoint goes first
+        let mut tried = vec![];
+        let result = with_failover(&endpoints, &health, |url| {
+            tried.push(String::from(url));
+            if url == "https://primary" {
+                Err("Timeout")
+            } else {
+                Ok(1)
+            }
+        });
+        assert_eq!(result, Ok(1));
+        assert_eq!(tried, vec!["https://primary", "https://backup"]);
+        assert_eq!(
+            health.stat("https://primary"),
+            EndpointStat {
+                requests: 1,
+                errors: 1
+            }
+        );
+
+        // Primary keeps failing, then backup is preferred
+        let mut tried = vec![];
+        let _ = with_failover(&endpoints, &health, |url| -> Result<(), &'static str> {
+            tried.push(String::from(url));
+            Ok(())
+        });
+        assert_eq!(tried, vec!["https://backup"]);
+
+        assert_eq!(
+            with_failover(&endpoints, &health, |_| -> Result<(), &'static str> {
+                Err("Timeout")
+            }),
+            Err("Timeout")
+        );
+        assert_eq!(
+            with_failover(&[], &health, |_| -> Result<(), &'static str> { Ok(()) }),
+            Err("NoRpcEndpoint")
+        );
+
+        // Clones record to the same statistics
+        health.clone().record("https://backup", false);
+        assert_eq!(
+            health.stat("https://backup"),
+            EndpointStat {
+                requests: 4,
+                errors: 2
+            }
+        );
+    }
+
+    #[test]
+    fn test_merge_stats() {
+        let stat = |requests, errors| EndpointStat { requests, errors };
+        let mut saved = BTreeMap::from([
+            (String::from("https://primary"), stat(10, 1)),
+            (String::from("https://backup"), stat(999, 100)),
+        ]);
+        let recorded = BTreeMap::from([
+            (String::from("https://primary"), stat(2, 2)),
+            (String::from("https://backup"), stat(3, 1)),
+            (String::from("https://new"), stat(1, 0)),
+        ]);
+        merge_stats(&mut saved, &recorded);
+        assert_eq!(
+            saved,
+            BTreeMap::from([
+                (String::from("https://primary"), stat(12, 3)),
+                // Halved once over the max
+                (String::from("https://backup"), stat(501, 50)),
+                (String::from("https://new"), stat(1, 0)),
+            ])
+        );
+    }
+}
@@ -2,18 +2,20 @@ use crate::actions::ActionExtraInfo;
 use crate::chain::{BalanceFetcher, Chain, ChainType};
 use crate::utils::ToArray;
 use alloc::vec;
-use alloc::{borrow::ToOwned, boxed::Box, string::String, vec::Vec};
+use alloc::{boxed::Box, string::String, vec::Vec};
 use pink_subrpc::{create_transaction_with_calldata, send_transaction, ExtraParam};
 
 use crate::account::AccountInfo;
 use crate::call::{Call, CallBuilder, CallParams, SubCall};
 use crate::context::Context;
+use crate::gas;
 use crate::storage::StorageClient;
+use crate::task::ExecuteTx;
 use crate::traits::Runner;
-use crate::tx;
+use crate::tx::{self, TxStatus, XcmDelivery};
 use pink_web3::{
     api::{Eth, Namespace},
-    contract::{Contract, Options},
+    contract::{tokens::Tokenize, Contract, Options},
     keys::pink::KeyPair,
     transports::{resolve_ready, PinkHttp},
     types::U256,
@@ -85,9 +87,7 @@ impl TryFrom<StepInput> for Step {
 
 impl Step {
     pub fn get_action(&self, context: &Context) -> Result<Box<dyn CallBuilder>, &'static str> {
-        let action = context
-            .get_actions(&self.source_chain, &self.exe)
-            .ok_or("NoActionFound")?;
+        let action = context.get_actions(&self.source_chain, &self.exe)?;
         pink_extension::debug!("Found action: {:?} on {:?}", &self.exe, &self.source_chain,);
         Ok(action)
     }
@@ -107,8 +107,47 @@ impl Step {
 
         let mut step = self.clone();
         step.sender = Some(sender);
-        let call = action.build_call(step)?;
-        Ok(vec![call])
+        action.build_calls(step)
+    }
+
+    /// Fee the action charges for the step on top of the spent amount, in USD.
+    /// The USD amount is the value / 10000
+    pub fn quote_fee_in_usd(&self, context: &Context) -> Result<u32, &'static str> {
+        let fee = match self.get_action(context)?.quote_fee(self.clone())? {
+            Some(fee) => fee,
+            None => return Ok(0),
+        };
+        pink_extension::debug!("Quoted fee of step {:?}: {:?}", &self.exe, &fee);
+        let price = crate::price::get_asset_price(context.registry, &self.source_chain, &fee.asset)
+            .ok_or("MissingPriceData")?;
+        // Presume 18 decimals like EVM native assets if the asset is not registered
+        let decimals = context
+            .registry
+            .get_asset(&self.source_chain, &fee.asset)
+            .map_or(18, |asset| asset.decimals);
+        (U256::from(fee.amount) * U256::from(price) / U256::from(10).pow(U256::from(decimals)))
+            .try_into()
+            .map_err(|_| "FeeOverflow")
+    }
+
+    /// Bridge step must receive a representation of the asset it spends on source chain,
+    /// assets without canonical id are not checked
+    pub fn check_assets(&self, context: &Context) -> Result<(), &'static str> {
+        if self.is_bridge_step()
+            && context.registry.is_same_asset(
+                &self.source_chain,
+                &self.spend_asset,
+                &self.dest_chain,
+                &self.receive_asset,
+            ) == Some(false)
+        {
+            pink_extension::error!(
+                "Bridge step {:?} receives an asset different from the one it spends",
+                &self.exe
+            );
+            return Err("AssetMismatch");
+        }
+        Ok(())
     }
 
     pub fn is_bridge_step(&self) -> bool {
@@ -176,35 +215,19 @@ impl MultiStep {
             return Err("MissingSpendAmount");
         }
         let calls = match self {
-            MultiStep::Single(step) => {
-                let mut calls = step.derive_calls(context)?;
-                assert!(calls.len() == 1);
-                calls[0].call_index = Some(0);
-                calls[0].input_call = Some(0);
-                calls
-            }
+            MultiStep::Single(step) => Self::index_calls(&[step.clone()], context)?,
             MultiStep::Batch(batch_steps) => {
                 if batch_steps.is_empty() {
                     return Err("BatchStepEmpty");
                 }
-
-                let mut calls: Vec<Call> = vec![];
-                for step in batch_steps.iter() {
-                    let origin_call_count = calls.len();
-                    let mut next_call_index = origin_call_count.try_into().expect("Too many calls");
-                    let mut new_calls = step.derive_calls(context)?;
-                    for call in new_calls.iter_mut() {
-                        call.call_index = Some(next_call_index);
-                        call.input_call = if origin_call_count > 0 {
-                            calls[origin_call_count - 1].call_index
-                        } else {
-                            Some(0)
-                        };
-                        next_call_index += 1;
+                // Only the spend amount of the first step is final, the others are updated with
+                // the output of the calls before them during settlement
+                for step in batch_steps.iter().skip(1) {
+                    if step.get_action(context)?.needs_final_amount() {
+                        return Err("ActionNeedsFinalAmount");
                     }
-                    calls.append(&mut new_calls);
                 }
-                calls
+                Self::index_calls(batch_steps, context)?
             }
         };
         if calls.is_empty() {
@@ -214,6 +237,36 @@ impl MultiStep {
         Ok(calls)
     }
 
+    /// Derive calls of the steps, each call takes the output of the last settled call before
+    /// it as input, calls that don't settle (e.g. approvals) have no output to pass on.
+    /// Calls with no settled call before them take nothing as input, which is represented by
+    /// themselves
+    fn index_calls(steps: &[Step], context: &Context) -> Result<Vec<Call>, &'static str> {
+        let mut calls: Vec<Call> = vec![];
+        let mut input_call: Option<u8> = None;
+        for step in steps.iter() {
+            for mut call in step.derive_calls(context)? {
+                let call_index: u8 = calls.len().try_into().expect("Too many calls");
+                call.call_index = Some(call_index);
+                call.input_call = Some(input_call.unwrap_or(call_index));
+                if !matches!(&call.params, CallParams::Evm(evm_call) if !evm_call.need_settle) {
+                    input_call = Some(call_index);
+                }
+                calls.push(call);
+            }
+        }
+        Ok(calls)
+    }
+
+    pub fn check_assets(&self, context: &Context) -> Result<(), &'static str> {
+        match self {
+            MultiStep::Single(step) => step.check_assets(context),
+            MultiStep::Batch(batch_steps) => batch_steps
+                .iter()
+                .try_for_each(|step| step.check_assets(context)),
+        }
+    }
+
     pub fn as_single_step(&self) -> Step {
         match self {
             MultiStep::Single(step) => step.clone(),
@@ -248,12 +301,23 @@ impl MultiStep {
         }
     }
 
-    pub fn settle(&self, context: &Context) -> Result<u128, &'static str> {
+    /// Amount received by the recipient of the step. For XCM tracked by message hash it is the
+    /// amount deposited by the message, otherwise the balance change of the recipient
+    pub fn settle(&self, sent_txs: &[ExecuteTx], context: &Context) -> Result<u128, &'static str> {
+        if let Some(XcmDelivery::Succeeded {
+            received: Some(received),
+        }) = self.xcm_delivery(sent_txs, context)?
+        {
+            pink_extension::debug!("Settle info of XCM delivery: received {:?}", received);
+            return Ok(received);
+        }
+
         let step = self.as_single_step();
         let dest_chain = step.dest_chain(context).ok_or("MissingDestChain")?;
         let origin_balance = step.origin_balance.ok_or("MissingBalance")?;
         let recipient = step.recipient.clone();
-        let latest_balance = dest_chain.get_balance(step.receive_asset, recipient.clone())?;
+        let latest_balance =
+            dest_chain.get_balance(step.receive_asset, recipient.clone(), context.rpc_health)?;
         pink_extension::debug!(
             "Settle info of account {:?}: origin_balance is {:?}, latest_balance is {:?}",
             &hex::encode(&recipient),
@@ -263,6 +327,82 @@ impl MultiStep {
         Ok(latest_balance.saturating_sub(origin_balance))
     }
 
+    /// Execution result on the destination chain of the XCM message sent by a bridge step,
+    /// `None` if the message can not be tracked, e.g. either chain is not known to send or
+    /// receive XCM
+    /// Result of the transaction sent with `nonce` by the worker on the source chain, fetched
+    /// once for confirming and checking finality
+    fn tx_status(
+        &self,
+        nonce: u64,
+        sent_txs: &[ExecuteTx],
+        context: &Context,
+    ) -> Result<Option<TxStatus>, &'static str> {
+        let source_chain = self
+            .as_single_step()
+            .source_chain(context)
+            .ok_or("MissingSourceChain")?;
+        let worker_account = AccountInfo::from(context.signer);
+        // Query off-chain indexer directly get the execution result
+        let account = match source_chain.chain_type {
+            ChainType::Evm => worker_account.account20.to_vec(),
+            ChainType::Sub => worker_account.account32.to_vec(),
+        };
+        context.get_tx_status(&source_chain.name, nonce, sent_txs, || {
+            tx::get_tx_status(&source_chain, &account, nonce, sent_txs, context.rpc_health)
+        })
+    }
+
+    fn xcm_delivery(
+        &self,
+        sent_txs: &[ExecuteTx],
+        context: &Context,
+    ) -> Result<Option<XcmDelivery>, &'static str> {
+        context.get_xcm_delivery(sent_txs, || self.lookup_xcm_delivery(sent_txs, context))
+    }
+
+    fn lookup_xcm_delivery(
+        &self,
+        sent_txs: &[ExecuteTx],
+        context: &Context,
+    ) -> Result<Option<XcmDelivery>, &'static str> {
+        let step = self.as_single_step();
+        if !step.is_bridge_step() {
+            return Ok(None);
+        }
+        let source_chain = step.source_chain(context).ok_or("MissingSourceChain")?;
+        let dest_chain = step.dest_chain(context).ok_or("MissingDestChain")?;
+        if tx::xcm_receiver(&dest_chain).is_none() {
+            return Ok(None);
+        }
+        // Transactions sent without the block of dest chain recorded fall back to balance check
+        let Some(from) = sent_txs.iter().filter_map(|tx| tx.dest_block).min() else {
+            return Ok(None);
+        };
+        let Some(message) = tx::xcm_message(&source_chain, sent_txs, context.rpc_health)? else {
+            return Ok(None);
+        };
+        tx::xcm_delivery(
+            &dest_chain,
+            &message,
+            &step.recipient,
+            from,
+            context.rpc_health,
+        )
+    }
+
+    /// Best block number of the destination chain of a bridge step whose XCM delivery is
+    /// tracked, recorded along with the transaction about to send
+    pub fn dest_block_number(&self, context: &Context) -> Option<u64> {
+        let step = self.as_single_step();
+        if !step.is_bridge_step() {
+            return None;
+        }
+        let dest_chain = step.dest_chain(context)?;
+        tx::xcm_receiver(&dest_chain)?;
+        tx::sent_block_number(&dest_chain, context.rpc_health)
+    }
+
     pub fn sync_origin_balance(&mut self, context: &Context) -> Result<(), &'static str> {
         let (recipient, receive_asset, dest_chain) = match self {
             MultiStep::Single(step) => (
@@ -284,7 +424,7 @@ impl MultiStep {
             .registry
             .get_chain(&dest_chain)
             .ok_or("MissingDestChain")?;
-        let origin_balance = chain.get_balance(receive_asset, recipient)?;
+        let origin_balance = chain.get_balance(receive_asset, recipient, context.rpc_health)?;
 
         match self {
             MultiStep::Single(step) => {
@@ -320,24 +460,31 @@ impl MultiStep {
             }
         }
     }
-}
 
-impl Runner for MultiStep {
-    // By checking the nonce of the worker account on the chain source chain we can indicate whether
-    // the transaction revalant to the step has been executed.
-    fn can_run(
-        &self,
+    /// Resubmit the step with the same nonce and a bumped fee to replace the pending
+    /// transaction `replaced_tx`. Only EVM transactions can be replaced.
+    pub fn speed_up(
+        &mut self,
         nonce: u64,
+        replaced_tx: &[u8],
         context: &Context,
-        _client: Option<&StorageClient>,
-    ) -> Result<bool, &'static str> {
-        let worker_account = AccountInfo::from(context.signer);
-        let onchain_nonce =
-            worker_account.get_nonce(&self.as_single_step().source_chain, context)?;
-        Ok(onchain_nonce <= nonce)
+    ) -> Result<Vec<u8>, &'static str> {
+        let chain = self
+            .as_single_step()
+            .source_chain(context)
+            .ok_or("MissingSourceChain")?;
+        if chain.chain_type != ChainType::Evm {
+            return Err("UnexpectedChainType");
+        }
+        self.submit(nonce, Some(replaced_tx), context)
     }
 
-    fn run(&mut self, nonce: u64, context: &Context) -> Result<Vec<u8>, &'static str> {
+    fn submit(
+        &mut self,
+        nonce: u64,
+        replaced_tx: Option<&[u8]>,
+        context: &Context,
+    ) -> Result<Vec<u8>, &'static str> {
         let as_single_step = self.as_single_step();
         let spend_amount = as_single_step.spend_amount.ok_or("MissingSpendAmount")?;
         let chain = as_single_step
@@ -353,85 +500,95 @@ impl Runner for MultiStep {
         pink_extension::debug!("Start to execute step with nonce: {}", nonce);
         let tx_id = match chain.chain_type {
             ChainType::Evm => {
-                let handler = Contract::from_json(
-                    Eth::new(PinkHttp::new(&chain.endpoint)),
-                    chain.handler_contract.to_array().into(),
-                    include_bytes!("./abi/handler.json"),
-                )
-                .expect("Bad abi data");
-
-                let is_spend_native = chain.is_native(&as_single_step.spend_asset);
-                // Estiamte gas before submission
-                let gas = resolve_ready(handler.estimate_gas(
-                    "batchCall",
-                    calls.clone(),
-                    worker_account.account20.into(),
-                    Options::with(|opt| {
-                        opt.value = if is_spend_native {
-                            Some(U256::from(spend_amount))
-                        } else {
-                            None
-                        }
-                    }),
-                ))
-                .map_err(|e| {
-                    pink_extension::error!("Failed to estimated step gas cost with error: {:?}", e);
-                    "FailedToEstimateGas"
-                })?;
-                pink_extension::debug!("Estimated step gas: {:?}", gas);
-
-                // Actually submit the tx (no guarantee for success)
-                let tx_id = resolve_ready(handler.signed_call(
-                    "batchCall",
-                    calls,
-                    Options::with(|opt| {
-                        opt.gas = Some(gas);
-                        opt.nonce = Some(U256::from(nonce));
-                        opt.value = if is_spend_native {
-                            Some(U256::from(spend_amount))
-                        } else {
-                            None
-                        }
-                    }),
-                    KeyPair::from(signer),
-                ))
-                .map_err(|e| {
-                    pink_extension::error!(
-                        "Failed to submit step execution tx with error: {:?}",
-                        e
-                    );
-                    "FailedToSubmitTransaction"
-                })?;
-
-                tx_id.as_bytes().to_owned()
-            }
-            ChainType::Sub => match calls[0].params.clone() {
-                CallParams::Sub(SubCall { calldata }) => {
-                    let signed_tx = create_transaction_with_calldata(
-                        &signer,
-                        &chain.name.to_lowercase(),
-                        &chain.endpoint,
-                        &calldata,
-                        ExtraParam {
-                            tip: 0,
-                            nonce: Some(nonce),
-                            era: None,
-                        },
+                let value = if chain.is_native(&as_single_step.spend_asset) {
+                    Some(U256::from(spend_amount))
+                } else {
+                    None
+                };
+                // Sign once, only broadcasting is retried on other endpoints
+                let signed_tx = chain.with_rpc(context.rpc_health, |endpoint| {
+                    let eth = Eth::new(PinkHttp::new(endpoint));
+                    let handler = Contract::from_json(
+                        eth.clone(),
+                        chain.handler_contract.to_array().into(),
+                        include_bytes!("./abi/handler.json"),
                     )
+                    .expect("Bad abi data");
+
+                    // Estiamte gas before submission
+                    let gas = resolve_ready(handler.estimate_gas(
+                        "batchCall",
+                        calls.clone(),
+                        worker_account.account20.into(),
+                        Options::with(|opt| opt.value = value),
+                    ))
                     .map_err(|e| {
                         pink_extension::error!(
-                            "Failed to construct substrate tx with error: {:?}",
+                            "Failed to estimated step gas cost with error: {:?}",
                             e
                         );
-                        "FailedToCreateTransaction"
+                        "FailedToEstimateGas"
                     })?;
-
-                    send_transaction(&chain.endpoint, &signed_tx).map_err(|e| {
-                        pink_extension::error!(
-                            "Failed to submit step execution tx with error: {:?}",
-                            e
-                        );
-                        "FailedToSubmitTransaction"
+                    pink_extension::debug!("Estimated step gas: {:?}", gas);
+
+                    let options = match replaced_tx {
+                        Some(_) => gas::replacement_tx_options(
+                            &chain,
+                            &eth,
+                            gas,
+                            nonce,
+                            value,
+                            replaced_tx,
+                        )?,
+                        None => gas::tx_options(&chain, &eth, gas, Some(nonce), value)?,
+                    };
+                    let data = handler
+                        .abi()
+                        .function("batchCall")
+                        .and_then(|function| function.encode_input(&calls.clone().into_tokens()))
+                        .or(Err("EncodeParamError"))?;
+                    tx::sign_tx(
+                        &eth,
+                        handler.address(),
+                        data,
+                        options,
+                        KeyPair::from(signer),
+                    )
+                })?;
+                // Actually submit the tx (no guarantee for success)
+                tx::send_raw_tx(&chain, &signed_tx, context.rpc_health)?
+            }
+            ChainType::Sub => match calls[0].params.clone() {
+                CallParams::Sub(SubCall { calldata }) => {
+                    let signed_tx = chain.with_rpc(context.rpc_health, |endpoint| {
+                        create_transaction_with_calldata(
+                            &signer,
+                            &chain.name.to_lowercase(),
+                            endpoint,
+                            &calldata,
+                            ExtraParam {
+                                tip: 0,
+                                nonce: Some(nonce),
+                                era: None,
+                            },
+                        )
+                        .map_err(|e| {
+                            pink_extension::error!(
+                                "Failed to construct substrate tx with error: {:?}",
+                                e
+                            );
+                            "FailedToCreateTransaction"
+                        })
+                    })?;
+                    // The same extrinsic is sent to other endpoints on failure
+                    chain.with_rpc(context.rpc_health, |endpoint| {
+                        send_transaction(endpoint, &signed_tx).map_err(|e| {
+                            pink_extension::error!(
+                                "Failed to submit step execution tx with error: {:?}",
+                                e
+                            );
+                            "FailedToSubmitTransaction"
+                        })
                     })?
                 }
                 _ => return Err("UnexpectedCallType"),
@@ -441,10 +598,35 @@ impl Runner for MultiStep {
         pink_extension::info!("Submitted step execution tx: {:?}", hex::encode(&tx_id));
         Ok(tx_id)
     }
+}
+
+impl Runner for MultiStep {
+    // By checking the nonce of the worker account on the chain source chain we can indicate whether
+    // the transaction revalant to the step has been executed.
+    fn can_run(
+        &self,
+        nonce: u64,
+        context: &Context,
+        _client: Option<&StorageClient>,
+    ) -> Result<bool, &'static str> {
+        let worker_account = AccountInfo::from(context.signer);
+        let onchain_nonce =
+            worker_account.get_nonce(&self.as_single_step().source_chain, context)?;
+        Ok(onchain_nonce <= nonce)
+    }
+
+    fn run(&mut self, nonce: u64, context: &Context) -> Result<Vec<u8>, &'static str> {
+        self.submit(nonce, None, context)
+    }
 
     // By checking the nonce we can known whether the transaction has been executed or not,
     // and with help of off-chain indexer, we can get the relevant transaction's execution result.
-    fn has_finished(&self, nonce: u64, context: &Context) -> Result<bool, &'static str> {
+    fn has_finished(
+        &self,
+        nonce: u64,
+        sent_txs: &[ExecuteTx],
+        context: &Context,
+    ) -> Result<bool, &'static str> {
         pink_extension::info!(
             "Trying to check step execution result with nonce: {}",
             nonce
@@ -453,16 +635,27 @@ impl Runner for MultiStep {
         let source_chain = as_single_step
             .source_chain(context)
             .ok_or("MissingSourceChain")?;
-        let worker_account = AccountInfo::from(context.signer);
         let recipient = as_single_step.recipient.clone();
 
-        // Query off-chain indexer directly get the execution result
-        let account = match source_chain.chain_type {
-            ChainType::Evm => worker_account.account20.to_vec(),
-            ChainType::Sub => worker_account.account32.to_vec(),
-        };
-        if tx::has_confirmed(&source_chain.tx_indexer_url, &account, nonce)? {
-            // If is a bridge operation, check balance change on dest chain
+        if self
+            .tx_status(nonce, sent_txs, context)?
+            .map_or(false, |status| status.result)
+        {
+            // If is a bridge operation, check XCM delivery by message hash if it's tracked
+            if let Some(delivery) = self.xcm_delivery(sent_txs, context)? {
+                pink_extension::info!(
+                    "XCM delivery of bridge step {:?}: {:?}",
+                    &as_single_step,
+                    delivery
+                );
+                return match delivery {
+                    XcmDelivery::Pending => Ok(false),
+                    XcmDelivery::Succeeded { .. } => Ok(true),
+                    // Retrying can not recover from it, the task is marked as failed
+                    XcmDelivery::Failed => Err("XcmExecutionFailed"),
+                };
+            }
+            // Otherwise check balance change on dest chain
             if as_single_step.is_bridge_step() {
                 pink_extension::info!(
                     "Check balance change on destchain for bridge step {:?}",
@@ -471,8 +664,11 @@ impl Runner for MultiStep {
                 let dest_chain = as_single_step
                     .dest_chain(context)
                     .ok_or("MissingSourceChain")?;
-                let latest_balance =
-                    dest_chain.get_balance(as_single_step.receive_asset.clone(), recipient)?;
+                let latest_balance = dest_chain.get_balance(
+                    as_single_step.receive_asset.clone(),
+                    recipient,
+                    context.rpc_health,
+                )?;
                 let origin_balance = as_single_step
                     .origin_balance
                     .ok_or("MissingOriginReserve")?;
@@ -488,6 +684,24 @@ impl Runner for MultiStep {
         }
         Ok(false)
     }
+
+    fn has_finalized(
+        &self,
+        nonce: u64,
+        sent_txs: &[ExecuteTx],
+        context: &Context,
+    ) -> Result<bool, &'static str> {
+        let source_chain = self
+            .as_single_step()
+            .source_chain(context)
+            .ok_or("MissingSourceChain")?;
+        match self.tx_status(nonce, sent_txs, context)? {
+            Some(status) => {
+                tx::is_finalized(&source_chain, status.block_number, context.rpc_health)
+            }
+            None => Ok(false),
+        }
+    }
 }
 
 #[derive(Clone, Debug, Decode, Encode, Eq, PartialEq, Ord, PartialOrd)]
@@ -496,7 +710,7 @@ pub struct StepSimulateResult {
     pub action_extra_info: ActionExtraInfo,
     // Estimate gas cost for the step on EVM chain
     pub gas_limit: Option<U256>,
-    // Current suggest gas price on EVM chains
+    // Gas price expected to pay on EVM chains according to the fee strategy of the chain
     pub gas_price: Option<U256>,
     // Native asset price in USD
     // the  USD amount is the value / 10000
@@ -514,7 +728,7 @@ pub trait Simulate {
 impl Simulate for MultiStep {
     #[allow(unused_variables)]
     fn simulate(&self, context: &Context) -> Result<StepSimulateResult, &'static str> {
-        let action_extra_info = match self {
+        let mut action_extra_info = match self {
             MultiStep::Single(step) => context
                 .get_action_extra_info(&step.source_chain, &step.exe)
                 .ok_or("NoActionFound")?,
@@ -534,6 +748,14 @@ impl Simulate for MultiStep {
                 extra_info
             }
         };
+        // Fees quoted from the actions, paid by the handler or deducted from the output
+        let steps = match self {
+            MultiStep::Single(step) => vec![step.clone()],
+            MultiStep::Batch(batch_steps) => batch_steps.clone(),
+        };
+        for step in steps.iter() {
+            action_extra_info.extra_proto_fee_in_usd += step.quote_fee_in_usd(context)?;
+        }
 
         let calls = self.derive_calls(context)?;
 
@@ -546,34 +768,40 @@ impl Simulate for MultiStep {
         pink_extension::debug!("Start to simulate step with calls: {:?}", &calls);
         let (gas_limit, gas_price, native_price_in_usd, tx_fee_in_usd) = match chain.chain_type {
             ChainType::Evm => {
-                let eth = Eth::new(PinkHttp::new(chain.endpoint));
-                let handler = Contract::from_json(
-                    eth.clone(),
-                    chain.handler_contract.to_array().into(),
-                    include_bytes!("./abi/handler.json"),
-                )
-                .expect("Bad abi data");
-                let options = if as_single_step.spend_asset == chain.native_asset {
-                    Options::with(|opt| {
-                        opt.value = Some(U256::from(as_single_step.spend_amount.unwrap()))
-                    })
-                } else {
-                    Options::default()
-                };
+                let (gas, gas_price) = chain.with_rpc(context.rpc_health, |endpoint| {
+                    let eth = Eth::new(PinkHttp::new(endpoint));
+                    let handler = Contract::from_json(
+                        eth.clone(),
+                        chain.handler_contract.to_array().into(),
+                        include_bytes!("./abi/handler.json"),
+                    )
+                    .expect("Bad abi data");
+                    let options = if as_single_step.spend_asset == chain.native_asset {
+                        Options::with(|opt| {
+                            opt.value = Some(U256::from(as_single_step.spend_amount.unwrap()))
+                        })
+                    } else {
+                        Options::default()
+                    };
+
+                    // Estiamte gas before submission
+                    let gas = resolve_ready(handler.estimate_gas(
+                        "batchCall",
+                        calls.clone(),
+                        worker_account.account20.into(),
+                        options,
+                    ))
+                    .map_err(|e| {
+                        pink_extension::error!(
+                            "Failed to estimated step gas cost with error: {:?}",
+                            e
+                        );
+                        "FailedToEstimateGas"
+                    })?;
 
-                // Estiamte gas before submission
-                let gas = resolve_ready(handler.estimate_gas(
-                    "batchCall",
-                    calls,
-                    worker_account.account20.into(),
-                    options,
-                ))
-                .map_err(|e| {
-                    pink_extension::error!("Failed to estimated step gas cost with error: {:?}", e);
-                    "FailedToEstimateGas"
+                    let gas_price = chain.fee_strategy.suggest_fee(&eth)?.expected_gas_price();
+                    Ok((gas, gas_price))
                 })?;
-
-                let gas_price = resolve_ready(eth.gas_price()).or(Err("FailedToGetGasPrice"))?;
                 let native_asset_price = crate::price::get_price(&chain.name, &chain.native_asset)
                     .ok_or("MissingPriceData")?;
                 (
@@ -621,6 +849,47 @@ mod tests {
     use crate::registry::Registry;
     use crate::step::StepInput;
 
+    #[test]
+    fn test_batch_with_updated_amount() {
+        let registry = Registry::new();
+        let context = Context {
+            signer: [0; 32],
+            registry: &registry,
+            worker_accounts: vec![],
+            rpc_health: &Default::default(),
+            action_cache: Default::default(),
+            xcm_delivery_cache: Default::default(),
+            tx_status_cache: Default::default(),
+        };
+        let step = |exe: &str, spend_asset: &str, receive_asset: &str| Step {
+            exe: String::from(exe),
+            source_chain: String::from("Ethereum"),
+            dest_chain: String::from("Ethereum"),
+            spend_asset: hex::decode(spend_asset).unwrap(),
+            receive_asset: hex::decode(receive_asset).unwrap(),
+            sender: None,
+            recipient: hex::decode("d693bDC5cb0cF2a31F08744A0Ec135a68C26FE1c").unwrap(),
+            spend_amount: None,
+            origin_balance: None,
+            nonce: None,
+        };
+        let pha = "6c5bA91642F10282b576d91922Ae6448C9d52f4E";
+        let weth = "C02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2";
+        let usdc = "A0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48";
+        let swap = step("ethereum_uniswapv2", pha, weth);
+        let aggregator = step("ethereum_1inch", weth, usdc);
+        assert!(!swap.get_action(&context).unwrap().needs_final_amount());
+        assert!(aggregator
+            .get_action(&context)
+            .unwrap()
+            .needs_final_amount());
+
+        // Amount of the aggregator swap would be updated with the output of the swap before it
+        let mut batch = MultiStep::Batch(vec![swap, aggregator]);
+        batch.set_spend(1_000_000_000_000_000_000);
+        assert_eq!(batch.derive_calls(&context), Err("ActionNeedsFinalAmount"));
+    }
+
     #[test]
     #[ignore]
     fn test_batch_call_spend_erc20() {
@@ -635,6 +904,10 @@ mod tests {
             signer: worker_key,
             registry: &Registry::default(),
             worker_accounts: vec![],
+            rpc_health: &Default::default(),
+            action_cache: Default::default(),
+            xcm_delivery_cache: Default::default(),
+            tx_status_cache: Default::default(),
         };
 
         let mut step: MultiStep = MultiStepInput::Batch(vec![StepInput {
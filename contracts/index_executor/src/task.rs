@@ -2,6 +2,7 @@ use super::account::AccountInfo;
 use super::context::Context;
 use super::traits::Runner;
 use crate::chain::{Chain, ChainType, NonceFetcher};
+use crate::gas;
 use crate::price;
 use crate::step::{MultiStep, Simulate as StepSimulate};
 use crate::storage::StorageClient;
@@ -15,7 +16,7 @@ use pink_subrpc::{create_transaction, send_transaction, ExtraParam};
 
 use pink_web3::{
     api::{Eth, Namespace},
-    contract::{Contract, Options},
+    contract::{tokens::Tokenize, Contract, Options},
     keys::pink::KeyPair,
     signing::Key,
     transports::{resolve_ready, PinkHttp},
@@ -37,10 +38,44 @@ pub enum TaskStatus {
     Executing(u8, Option<u64>),
     /// Last step of task has been executed successfully on dest chain.
     Completed,
+    /// Step with the index failed in a way retrying can't recover from, e.g. the XCM message
+    /// failed to execute on dest chain. Task is kept for the DAO to handle
+    Failed(u8),
 }
 
 pub type TaskId = [u8; 32];
 
+#[derive(Clone, Decode, Encode, Eq, PartialEq, Ord, PartialOrd, Debug)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+pub enum TxKind {
+    /// First transaction sent to execute a step (or claim the task)
+    Execute,
+    /// Replacement of a pending transaction with the same nonce and a bumped fee
+    SpeedUp,
+    /// Zero-value self-transfer sent by admin to cancel a pending transaction
+    Cancel,
+}
+
+#[derive(Clone, Decode, Encode, Eq, PartialEq, Ord, PartialOrd, Debug)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+pub struct ExecuteTx {
+    /// Index of the step the transaction sent for
+    pub step_index: u8,
+    /// Worker account nonce the transaction applied
+    pub nonce: u64,
+    /// Transaction hash
+    pub hash: Vec<u8>,
+    pub kind: TxKind,
+    /// Timestamp in milliseconds when the transaction was sent
+    pub sent_at: u64,
+    /// Best block number of Substrate chains when the transaction was sent, where we start
+    /// looking for it
+    pub sent_block: Option<u64>,
+    /// Best block number of the destination chain of a bridge step when the transaction was
+    /// sent, where we start looking for the XCM message it sent
+    pub dest_block: Option<u64>,
+}
+
 #[derive(Clone, Decode, Encode, Eq, PartialEq, Ord, PartialOrd)]
 #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
 pub struct Task {
@@ -62,8 +97,8 @@ pub struct Task {
     pub claim_tx: Option<Vec<u8>>,
     /// Steps  after merged, those actually will be executed
     pub merged_steps: Vec<MultiStep>,
-    /// Transaction hash of each step operation
-    pub execute_txs: Vec<Vec<u8>>,
+    /// Transactions sent to execute steps, including replacements of the pending ones
+    pub execute_txs: Vec<ExecuteTx>,
     /// Current step index that is executing
     pub execute_index: u8,
     /// Sender address on source chain
@@ -74,6 +109,69 @@ pub struct Task {
     pub retry_counter: u8,
 }
 
+/// Layout of `Task` stored by earlier versions, which kept one transaction hash per step in
+/// `execute_txs`. Such tasks are migrated to the current layout when read by `Task::read`
+#[derive(Decode, Encode)]
+struct TaskV0 {
+    id: TaskId,
+    worker: [u8; 32],
+    status: TaskStatus,
+    source: String,
+    amount: u128,
+    fee: Option<u128>,
+    claim_nonce: Option<u64>,
+    claim_tx: Option<Vec<u8>>,
+    merged_steps: Vec<MultiStep>,
+    execute_txs: Vec<Vec<u8>>,
+    execute_index: u8,
+    sender: Vec<u8>,
+    recipient: Vec<u8>,
+    retry_counter: u8,
+}
+
+impl TaskV0 {
+    /// Task in the current layout, migrated at `migrated_at` in milliseconds
+    fn migrate(self, migrated_at: u64) -> Task {
+        // Hash at index `i` is the last transaction sent for step `i`, sending time and block
+        // were not recorded. Transactions still pending are taken as sent at migration, so they
+        // wait the full `replace_after_sec` of the chain before being replaced
+        let execute_txs = self
+            .execute_txs
+            .into_iter()
+            .enumerate()
+            .map(|(step_index, hash)| ExecuteTx {
+                step_index: step_index as u8,
+                nonce: self
+                    .merged_steps
+                    .get(step_index)
+                    .and_then(|step| step.get_nonce())
+                    .unwrap_or_default(),
+                hash,
+                kind: TxKind::Execute,
+                sent_at: migrated_at,
+                sent_block: None,
+                dest_block: None,
+            })
+            .collect();
+        Task {
+            id: self.id,
+            worker: self.worker,
+            status: self.status,
+            source: self.source,
+            amount: self.amount,
+            fee: self.fee,
+            claim_nonce: self.claim_nonce,
+            claim_tx: self.claim_tx,
+            merged_steps: self.merged_steps,
+            execute_txs,
+            execute_index: self.execute_index,
+            sender: self.sender,
+            recipient: self.recipient,
+            retry_counter: self.retry_counter,
+        }
+    }
+}
+
 impl Default for Task {
     fn default() -> Self {
         Self {
@@ -114,12 +212,21 @@ impl sp_std::fmt::Debug for Task {
 }
 
 impl Task {
+    /// Read task from storage, tasks stored with the layout of earlier versions are migrated
+    pub fn read(client: &StorageClient, id: &[u8]) -> Result<Option<Task>, &'static str> {
+        match client.read::<Task>(id) {
+            Err("DecodeDataFailed") => {
+                pink_extension::info!("Migrate task {:?} from earlier layout", hex::encode(id));
+                let now = pink_extension::ext().untrusted_millis_since_unix_epoch();
+                Ok(client.read::<TaskV0>(id)?.map(|task| task.migrate(now)))
+            }
+            result => result,
+        }
+    }
+
     // Initialize task
     pub fn init(&mut self, context: &Context, client: &StorageClient) -> Result<(), &'static str> {
-        if let Some(task) = client
-            .read::<Task>(&self.id)
-            .map_err(|_| "FailedToReadStorage")?
-        {
+        if let Some(task) = Task::read(client, &self.id).map_err(|_| "FailedToReadStorage")? {
             pink_extension::debug!(
                 "Task {:?} already initialized, will check if it is missed to be added to execute",
                 hex::encode(self.id)
@@ -134,6 +241,9 @@ impl Task {
                 client.insert(&self.worker, &self.id.encode())?;
             }
         } else {
+            for step in self.merged_steps.iter() {
+                step.check_assets(context)?;
+            }
             // Apply worker nonce for each step in task
             self.apply_nonce(0, context, client)?;
 
@@ -153,6 +263,9 @@ impl Task {
         context: &Context,
         client: &StorageClient,
     ) -> Result<TaskStatus, &'static str> {
+        if let TaskStatus::Failed(_) = self.status {
+            return Ok(self.status.clone());
+        }
         // Check claim before executing
         if !(self.has_claimed(context))? {
             pink_extension::debug!(
@@ -166,15 +279,27 @@ impl Task {
         }
 
         let step_count = self.merged_steps.len();
-        match self.merged_steps[self.execute_index as usize].has_finished(
-            // An executing task must have nonce applied
-            self.merged_steps[self.execute_index as usize]
-                .get_nonce()
-                .unwrap(),
-            context,
-        ) {
+        // An executing task must have nonce applied
+        let nonce = self.merged_steps[self.execute_index as usize]
+            .get_nonce()
+            .unwrap();
+        let sent_txs = self.sent_txs(self.execute_index, nonce);
+        match self.merged_steps[self.execute_index as usize].has_finished(nonce, &sent_txs, context)
+        {
             // If step already executed successfully, execute next step
             Ok(true) => {
+                // Settle only when the step can not be reverted by a reorg, waiting for finality
+                // doesn't count as a retry
+                if !self.merged_steps[self.execute_index as usize]
+                    .has_finalized(nonce, &sent_txs, context)?
+                {
+                    pink_extension::debug!(
+                        "Step[{:?}] executed, waiting for finality",
+                        self.execute_index
+                    );
+                    return Ok(self.status.clone());
+                }
+
                 // If all step executed successfully, set task as `Completed`
                 if self.execute_index as usize == (step_count - 1) {
                     self.status = TaskStatus::Completed;
@@ -185,8 +310,8 @@ impl Task {
                 self.retry_counter = 0;
 
                 // Settle last step before execute next step
-                let settle_balance =
-                    self.merged_steps[(self.execute_index - 1) as usize].settle(context)?;
+                let settle_balance = self.merged_steps[(self.execute_index - 1) as usize]
+                    .settle(&sent_txs, context)?;
                 pink_extension::debug!(
                     "Finished previous step execution, settle balance of last step[{:?}], settle amount: {:?}",
                     (self.execute_index - 1),
@@ -218,6 +343,13 @@ impl Task {
                     return Err("TooManyRetry");
                 }
             }
+            Err("XcmExecutionFailed") => {
+                pink_extension::error!(
+                    "Step[{:?}] failed to execute on dest chain, mark task as failed",
+                    self.execute_index
+                );
+                self.status = TaskStatus::Failed(self.execute_index);
+            }
             Err(e) => return Err(e),
         }
 
@@ -246,12 +378,61 @@ impl Task {
                 nonce
             );
             self.status = TaskStatus::Executing(self.execute_index, Some(nonce));
-            let execute_tx = self.merged_steps[self.execute_index as usize].run(nonce, context)?;
-            if self.execute_txs.len() == self.execute_index as usize + 1 {
-                // Not the first time to execute the step, just replace it with new tx hash
-                self.execute_txs[self.execute_index as usize] = execute_tx;
-            } else {
-                self.execute_txs.push(execute_tx);
+            let chain = self.merged_steps[self.execute_index as usize]
+                .as_single_step()
+                .source_chain(context)
+                .ok_or("MissingSourceChain")?;
+            // Transaction with the same nonce still pending, EVM transaction can be replaced
+            // if it stuck because of low gas fee
+            match self.pending_execute_tx(self.execute_index, nonce, &chain) {
+                Some(pending_tx) if pending_tx.kind == TxKind::Cancel => {
+                    pink_extension::debug!(
+                        "Step[{:?}] has been cancelled, wait for retry",
+                        self.execute_index
+                    );
+                }
+                Some(pending_tx) if !is_stuck(&pending_tx, &chain) => {
+                    pink_extension::debug!(
+                        "Step[{:?}] tx {:?} is pending, wait for confirmation",
+                        self.execute_index,
+                        hex::encode(&pending_tx.hash)
+                    );
+                }
+                Some(pending_tx) => {
+                    pink_extension::info!(
+                        "Step[{:?}] tx {:?} stuck, speed up it",
+                        self.execute_index,
+                        hex::encode(&pending_tx.hash)
+                    );
+                    let execute_tx = self.merged_steps[self.execute_index as usize].speed_up(
+                        nonce,
+                        &pending_tx.hash,
+                        context,
+                    )?;
+                    self.record_execute_tx(
+                        self.execute_index,
+                        nonce,
+                        execute_tx,
+                        TxKind::SpeedUp,
+                        None,
+                        None,
+                    );
+                }
+                None => {
+                    let sent_block = tx::sent_block_number(&chain, context.rpc_health);
+                    let dest_block =
+                        self.merged_steps[self.execute_index as usize].dest_block_number(context);
+                    let execute_tx =
+                        self.merged_steps[self.execute_index as usize].run(nonce, context)?;
+                    self.record_execute_tx(
+                        self.execute_index,
+                        nonce,
+                        execute_tx,
+                        TxKind::Execute,
+                        sent_block,
+                        dest_block,
+                    );
+                }
             }
         } else {
             pink_extension::debug!("Step[{:?}] not runnable, return", self.execute_index);
@@ -259,6 +440,59 @@ impl Task {
         Ok(self.status.clone())
     }
 
+    /// Record a transaction sent to execute the step with `step_index`
+    pub fn record_execute_tx(
+        &mut self,
+        step_index: u8,
+        nonce: u64,
+        hash: Vec<u8>,
+        kind: TxKind,
+        sent_block: Option<u64>,
+        dest_block: Option<u64>,
+    ) {
+        self.execute_txs.push(ExecuteTx {
+            step_index,
+            nonce,
+            hash,
+            kind,
+            sent_at: pink_extension::ext().untrusted_millis_since_unix_epoch(),
+            sent_block,
+            dest_block,
+        });
+    }
+
+    /// Return index of the step that applied `nonce` on `chain`. Claim shares index 0 with the
+    /// first step since they are executed in the same transaction
+    pub fn step_index_by_nonce(&self, chain: &String, nonce: u64) -> Option<u8> {
+        if &self.source == chain && self.claim_nonce == Some(nonce) {
+            return Some(0);
+        }
+        self.merged_steps
+            .iter()
+            .position(|step| {
+                &step.as_single_step().source_chain == chain && step.get_nonce() == Some(nonce)
+            })
+            .map(|index| index as u8)
+    }
+
+    // Latest transaction sent for the step with the same nonce. Only EVM transactions are
+    // tracked since substrate transactions can not be replaced by us
+    fn pending_execute_tx(&self, step_index: u8, nonce: u64, chain: &Chain) -> Option<ExecuteTx> {
+        if chain.chain_type != ChainType::Evm {
+            return None;
+        }
+        self.sent_txs(step_index, nonce).pop()
+    }
+
+    // Transactions sent for the step with the same nonce, in the order they were sent
+    fn sent_txs(&self, step_index: u8, nonce: u64) -> Vec<ExecuteTx> {
+        self.execute_txs
+            .iter()
+            .filter(|tx| tx.step_index == step_index && tx.nonce == nonce)
+            .cloned()
+            .collect()
+    }
+
     /// Delete task record from on-chain storage
     pub fn destroy(&mut self, client: &StorageClient) -> Result<(), &'static str> {
         let _ = client
@@ -325,7 +559,9 @@ impl Task {
             ChainType::Sub => account_info.account32.to_vec(),
             // ChainType::Unknown => panic!("chain not supported!"),
         };
-        let nonce = chain.get_nonce(account).map_err(|_| "FetchNonceFailed")?;
+        let nonce = chain
+            .get_nonce(account, context.rpc_health)
+            .map_err(|_| "FetchNonceFailed")?;
         Ok(nonce)
     }
 
@@ -344,11 +580,39 @@ impl Task {
         self.fee = Some(fee);
 
         match chain.chain_type {
-            ChainType::Evm => {
-                Ok(self.claim_evm_actived_tasks(chain, self.id, fee, context, claim_nonce)?)
-            }
+            ChainType::Evm => match self.pending_execute_tx(0, claim_nonce, &chain) {
+                Some(pending_tx) if pending_tx.kind == TxKind::Cancel => Err("ClaimCancelled"),
+                Some(pending_tx) if !is_stuck(&pending_tx, &chain) => {
+                    pink_extension::debug!(
+                        "Claim tx {:?} is pending, wait for confirmation",
+                        hex::encode(&pending_tx.hash)
+                    );
+                    Ok(pending_tx.hash)
+                }
+                pending_tx => Ok(self.claim_evm_actived_tasks(
+                    chain,
+                    self.id,
+                    fee,
+                    context,
+                    claim_nonce,
+                    pending_tx.as_ref().map(|tx| tx.hash.as_slice()),
+                )?),
+            },
             ChainType::Sub => {
-                Ok(self.claim_sub_actived_tasks(chain, self.id, fee, context, claim_nonce)?)
+                // First step is executed along with the claim
+                let sent_block = tx::sent_block_number(&chain, context.rpc_health);
+                let dest_block = self.merged_steps[0].dest_block_number(context);
+                let tx_id =
+                    self.claim_sub_actived_tasks(chain, self.id, fee, context, claim_nonce)?;
+                self.record_execute_tx(
+                    0,
+                    claim_nonce,
+                    tx_id.clone(),
+                    TxKind::Execute,
+                    sent_block,
+                    dest_block,
+                );
+                Ok(tx_id)
             }
         }
     }
@@ -369,7 +633,11 @@ impl Task {
         // Check if already claimed success
         let onchain_nonce = worker_account.get_nonce(&self.source, context)?;
         if onchain_nonce > claim_nonce {
-            if tx::has_confirmed(&chain.tx_indexer_url, &account, claim_nonce)? {
+            let sent_txs = self.sent_txs(0, claim_nonce);
+            let status = context.get_tx_status(&chain.name, claim_nonce, &sent_txs, || {
+                tx::get_tx_status(&chain, &account, claim_nonce, &sent_txs, context.rpc_health)
+            })?;
+            if status.map_or(false, |status| status.result) {
                 Ok(true)
             } else {
                 Err("ClaimFailed")
@@ -386,12 +654,10 @@ impl Task {
         fee: u128,
         context: &Context,
         nonce: u64,
+        // Pending claim transaction to be replaced
+        replaced_tx: Option<&[u8]>,
     ) -> Result<Vec<u8>, &'static str> {
         let handler: H160 = H160::from_slice(&chain.handler_contract);
-        let transport = Eth::new(PinkHttp::new(chain.endpoint));
-        let handler = Contract::from_json(transport, handler, include_bytes!("./abi/handler.json"))
-            .map_err(|_| "ConstructContractFailed")?;
-        let worker = KeyPair::from(context.signer);
 
         // We call claimAndBatchCall so that first step will be executed along with the claim operation
         let first_step = &mut self.merged_steps[0];
@@ -402,47 +668,57 @@ impl Task {
         pink_extension::debug!("Calls will be executed along with claim: {:?}", &calls);
 
         let params = (task_id, U256::from(fee), calls);
-        // Estiamte gas before submission
-        let gas = resolve_ready(handler.estimate_gas(
-            "claimAndBatchCall",
-            params.clone(),
-            worker.address(),
-            Options::default(),
-        ))
-        .map_err(|e| {
-            pink_extension::error!(
-                "claimAndBatchCall: failed to estimate gas cost with error {:?}",
-                &e
-            );
-            "GasEstimateFailed"
-        })?;
+        // Sign once, only broadcasting is retried on other endpoints
+        let signed_tx = chain.with_rpc(context.rpc_health, |endpoint| {
+            let eth = Eth::new(PinkHttp::new(endpoint));
+            let handler =
+                Contract::from_json(eth.clone(), handler, include_bytes!("./abi/handler.json"))
+                    .map_err(|_| "ConstructContractFailed")?;
+            let worker = KeyPair::from(context.signer);
+
+            // Estiamte gas before submission
+            let gas = resolve_ready(handler.estimate_gas(
+                "claimAndBatchCall",
+                params.clone(),
+                worker.address(),
+                Options::default(),
+            ))
+            .map_err(|e| {
+                pink_extension::error!(
+                    "claimAndBatchCall: failed to estimate gas cost with error {:?}",
+                    &e
+                );
+                "GasEstimateFailed"
+            })?;
 
+            let options = match replaced_tx {
+                Some(_) => {
+                    gas::replacement_tx_options(&chain, &eth, gas, nonce, None, replaced_tx)?
+                }
+                None => gas::tx_options(&chain, &eth, gas, Some(nonce), None)?,
+            };
+            let data = handler
+                .abi()
+                .function("claimAndBatchCall")
+                .and_then(|function| function.encode_input(&params.clone().into_tokens()))
+                .or(Err("EncodeParamError"))?;
+            tx::sign_tx(&eth, handler.address(), data, options, worker)
+        })?;
         // Submit the claim transaction
-        let tx_id = resolve_ready(handler.signed_call(
-            "claimAndBatchCall",
-            params,
-            Options::with(|opt| {
-                // Give 50% gas for potentially gas exceeding
-                opt.gas = Some(gas * U256::from(15) / U256::from(10));
-                opt.nonce = Some(nonce.into());
-            }),
-            worker,
-        ))
-        .map_err(|e| {
-            pink_extension::error!("claimAndBatchCall: failed to submit tx with error {:?}", &e);
+        let tx_id = tx::send_raw_tx(&chain, &signed_tx, context.rpc_health).map_err(|e| {
+            pink_extension::error!("claimAndBatchCall: failed to submit tx with error {:?}", e);
             "ClaimSubmitFailed"
-        })?
-        .as_bytes()
-        .to_vec();
+        })?;
 
+        let first_step = &mut self.merged_steps[0];
         // Merge nonce to let check for first step work properly
         first_step.set_nonce(self.claim_nonce.unwrap());
         // Set first step execution transaction hash
-        if self.execute_txs.is_empty() {
-            self.execute_txs.push(tx_id.clone());
-        } else {
-            self.execute_txs[0] = tx_id.clone();
-        }
+        let kind = match replaced_tx {
+            Some(_) => TxKind::SpeedUp,
+            None => TxKind::Execute,
+        };
+        self.record_execute_tx(0, nonce, tx_id.clone(), kind, None, None);
 
         pink_extension::info!(
             "Submit transaction to claim task {:?} on {:?}, tx id: {:?}",
@@ -461,27 +737,32 @@ impl Task {
         context: &Context,
         nonce: u64,
     ) -> Result<Vec<u8>, &'static str> {
-        let signed_tx = create_transaction(
-            &context.signer,
-            "phala",
-            &chain.endpoint,
-            // Pallet id of `pallet-index`
-            *chain
-                .handler_contract
-                .first()
-                .ok_or("ClaimMissingPalletId")?,
-            // Call index of `claim_task`
-            0x03u8,
-            (task_id, fee),
-            ExtraParam {
-                tip: 0,
-                nonce: Some(nonce),
-                era: None,
-            },
-        )
-        .map_err(|_| "ClaimInvalidSignature")?;
-        let tx_id =
-            send_transaction(&chain.endpoint, &signed_tx).map_err(|_| "ClaimSubmitFailed")?;
+        let pallet_id = *chain
+            .handler_contract
+            .first()
+            .ok_or("ClaimMissingPalletId")?;
+        let signed_tx = chain.with_rpc(context.rpc_health, |endpoint| {
+            create_transaction(
+                &context.signer,
+                "phala",
+                endpoint,
+                // Pallet id of `pallet-index`
+                pallet_id,
+                // Call index of `claim_task`
+                0x03u8,
+                (task_id, fee),
+                ExtraParam {
+                    tip: 0,
+                    nonce: Some(nonce),
+                    era: None,
+                },
+            )
+            .map_err(|_| "ClaimInvalidSignature")
+        })?;
+        // The same extrinsic is sent to other endpoints on failure
+        let tx_id = chain.with_rpc(context.rpc_health, |endpoint| {
+            send_transaction(endpoint, &signed_tx).map_err(|_| "ClaimSubmitFailed")
+        })?;
         pink_extension::info!(
             "Submit transaction to claim task {:?} on ${:?}, tx id: {:?}",
             hex::encode(task_id),
@@ -523,18 +804,26 @@ impl Task {
             .registry
             .get_asset(&self.source, &asset_location)
             .ok_or("MissingAssetInfo")?;
-        let asset_price =
-            price::get_price(&self.source, &asset_location).ok_or("MissingPriceData")?;
+        let asset_price = price::get_asset_price(context.registry, &self.source, &asset_location)
+            .ok_or("MissingPriceData")?;
         Ok(10u128.pow(asset_info.decimals as u32) * fee_in_usd as u128 / asset_price as u128)
     }
 }
 
+// Whether a pending transaction has waited longer than the chain allows
+fn is_stuck(tx: &ExecuteTx, chain: &Chain) -> bool {
+    let now = pink_extension::ext().untrusted_millis_since_unix_epoch();
+    now.saturating_sub(tx.sent_at) >= chain.fee_strategy.replace_after_sec * 1000
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::account::AccountInfo;
     use crate::chain::{BalanceFetcher, Chain, ChainType};
+    use crate::gas::FeeStrategy;
     use crate::registry::Registry;
+    use crate::rpc::{RpcEndpoint, RpcHealth};
     use crate::step::StepInput;
     use crate::task_fetcher::ActivedTaskFetcher;
     use crate::utils::ToArray;
@@ -542,6 +831,73 @@ mod tests {
     use hex_literal::hex;
     use primitive_types::H160;
 
+    #[test]
+    fn test_migrate_task_v0() {
+        let step = |nonce| {
+            MultiStep::Single(crate::step::Step {
+                exe: String::from("phala_bridge_to_astar"),
+                source_chain: String::from("Phala"),
+                dest_chain: String::from("Astar"),
+                spend_asset: hex::decode("0000").unwrap(),
+                receive_asset: hex::decode("010100cd1f").unwrap(),
+                sender: None,
+                recipient: vec![1; 32],
+                spend_amount: None,
+                origin_balance: None,
+                nonce: Some(nonce),
+            })
+        };
+        let task = TaskV0 {
+            id: [1; 32],
+            worker: [2; 32],
+            status: TaskStatus::Executing(1, Some(8)),
+            source: String::from("Phala"),
+            amount: 100,
+            fee: None,
+            claim_nonce: Some(6),
+            claim_tx: Some(vec![3; 32]),
+            merged_steps: vec![step(7), step(8)],
+            execute_txs: vec![vec![4; 32], vec![5; 32]],
+            execute_index: 1,
+            sender: vec![6; 32],
+            recipient: vec![1; 32],
+            retry_counter: 2,
+        };
+        let encoded = task.encode();
+        assert!(Task::decode(&mut encoded.as_slice()).is_err());
+
+        let migrated_at = 1_700_000_000_000;
+        let migrated = TaskV0::decode(&mut encoded.as_slice())
+            .unwrap()
+            .migrate(migrated_at);
+        assert_eq!(migrated.status, TaskStatus::Executing(1, Some(8)));
+        assert_eq!(migrated.merged_steps, task.merged_steps);
+        assert_eq!(migrated.retry_counter, 2);
+        assert_eq!(
+            migrated.execute_txs,
+            vec![
+                ExecuteTx {
+                    step_index: 0,
+                    nonce: 7,
+                    hash: vec![4; 32],
+                    kind: TxKind::Execute,
+                    sent_at: migrated_at,
+                    sent_block: None,
+                    dest_block: None,
+                },
+                ExecuteTx {
+                    step_index: 1,
+                    nonce: 8,
+                    hash: vec![5; 32],
+                    kind: TxKind::Execute,
+                    sent_at: migrated_at,
+                    sent_block: None,
+                    dest_block: None,
+                },
+            ]
+        );
+    }
+
     #[test]
     // Remove when `handler address is not hardcoded
     #[ignore]
@@ -557,20 +913,25 @@ mod tests {
                 id: 0,
                 name: String::from("Ethereum"),
                 chain_type: ChainType::Evm,
-                endpoint: String::from(
+                endpoints: vec![RpcEndpoint::new(
                     "https://eth-goerli.g.alchemy.com/v2/lLqSMX_1unN9Xrdy_BB9LLZRgbrXwZv2",
-                ),
+                    0,
+                )],
                 native_asset: vec![0],
                 foreign_asset: None,
                 handler_contract: hex!("056C0E37d026f9639313C281250cA932C9dbe921").into(),
                 tx_indexer_url: Default::default(),
+                fee_strategy: Default::default(),
+                finality: Default::default(),
+                xcm_events: None,
+                xcm_dest_weight: None,
             },
             worker: AccountInfo {
                 account20: worker_address.into(),
                 account32: [0; 32],
             },
         }
-        .fetch_task(&client)
+        .fetch_task(&client, &RpcHealth::default())
         .unwrap()
         .unwrap();
     }
@@ -592,13 +953,18 @@ mod tests {
             id: 0,
             name: String::from("Goerli"),
             chain_type: ChainType::Evm,
-            endpoint: String::from(
+            endpoints: vec![RpcEndpoint::new(
                 "https://eth-goerli.g.alchemy.com/v2/lLqSMX_1unN9Xrdy_BB9LLZRgbrXwZv2",
-            ),
+                0,
+            )],
             native_asset: vec![0],
             foreign_asset: None,
             handler_contract: hex!("056C0E37d026f9639313C281250cA932C9dbe921").into(),
             tx_indexer_url: Default::default(),
+            fee_strategy: Default::default(),
+            finality: Default::default(),
+            xcm_events: None,
+            xcm_dest_weight: None,
         };
 
         let context = Context {
@@ -606,8 +972,13 @@ mod tests {
             registry: &Registry {
                 chains: vec![goerli],
                 assets: vec![],
+                actions: vec![],
             },
             worker_accounts: vec![],
+            rpc_health: &Default::default(),
+            action_cache: Default::default(),
+            xcm_delivery_cache: Default::default(),
+            tx_status_cache: Default::default(),
         };
         let mut task = Task::default();
         task.id = hex::decode("0000000000000000000000000000000000000000000000000000000000000001")
@@ -645,18 +1016,22 @@ mod tests {
                 id: 0,
                 name: String::from("Khala"),
                 chain_type: ChainType::Sub,
-                endpoint: String::from("http://127.0.0.1:30444"),
+                endpoints: vec![RpcEndpoint::new("http://127.0.0.1:30444", 0)],
                 native_asset: vec![0],
                 foreign_asset: None,
                 handler_contract: hex!("00").into(),
                 tx_indexer_url: Default::default(),
+                fee_strategy: Default::default(),
+                finality: Default::default(),
+                xcm_events: None,
+                xcm_dest_weight: None,
             },
             worker: AccountInfo {
                 account20: [0; 20],
                 account32: worker_key,
             },
         }
-        .fetch_task(&client)
+        .fetch_task(&client, &RpcHealth::default())
         .unwrap()
         .unwrap();
     }
@@ -680,11 +1055,15 @@ mod tests {
             id: 0,
             name: String::from("Khala"),
             chain_type: ChainType::Sub,
-            endpoint: String::from("http://127.0.0.1:30444"),
+            endpoints: vec![RpcEndpoint::new("http://127.0.0.1:30444", 0)],
             native_asset: pha.clone(),
             foreign_asset: None,
             handler_contract: hex!("79").into(),
             tx_indexer_url: Default::default(),
+            fee_strategy: Default::default(),
+            finality: Default::default(),
+            xcm_events: None,
+            xcm_dest_weight: None,
         };
 
         let context = Context {
@@ -692,8 +1071,13 @@ mod tests {
             registry: &Registry {
                 chains: vec![khala.clone()],
                 assets: vec![],
+                actions: vec![],
             },
             worker_accounts: vec![],
+            rpc_health: &Default::default(),
+            action_cache: Default::default(),
+            xcm_delivery_cache: Default::default(),
+            tx_status_cache: Default::default(),
         };
         let mut task = Task::default();
         task.id = hex::decode("0000000000000000000000000000000000000000000000000000000000000001")
@@ -715,7 +1099,10 @@ mod tests {
 
         // After claim, asset sent from pallet-index account to worker account
         assert_eq!(
-            khala.get_balance(pha, mock_worker_pub_key.into()).unwrap() - 301_000_000_000_000u128
+            khala
+                .get_balance(pha, mock_worker_pub_key.into(), &RpcHealth::default())
+                .unwrap()
+                - 301_000_000_000_000u128
                 > 0,
             true
         );
@@ -730,18 +1117,26 @@ mod tests {
         let goerli = Chain {
             id: 1,
             name: String::from("Goerli"),
-            endpoint: String::from(
+            endpoints: vec![RpcEndpoint::new(
                 "https://eth-goerli.g.alchemy.com/v2/lLqSMX_1unN9Xrdy_BB9LLZRgbrXwZv2",
-            ),
+                0,
+            )],
             chain_type: ChainType::Evm,
             native_asset: vec![0],
             foreign_asset: None,
             handler_contract: "0x056C0E37d026f9639313C281250cA932C9dbe921".into(),
             tx_indexer_url: Default::default(),
+            fee_strategy: Default::default(),
+            finality: Default::default(),
+            xcm_events: None,
+            xcm_dest_weight: None,
         };
         assert_eq!(
             goerli
-                .get_nonce(hex!("0E275F8839b788B2674935AD97C01cF73A9E8c41").into())
+                .get_nonce(
+                    hex!("0E275F8839b788B2674935AD97C01cF73A9E8c41").into(),
+                    &RpcHealth::default()
+                )
                 .unwrap(),
             2
         );
@@ -776,20 +1171,25 @@ mod tests {
                 id: 0,
                 name: String::from("Ethereum"),
                 chain_type: ChainType::Evm,
-                endpoint: String::from(
+                endpoints: vec![RpcEndpoint::new(
                     "https://eth-goerli.g.alchemy.com/v2/lLqSMX_1unN9Xrdy_BB9LLZRgbrXwZv2",
-                ),
+                    0,
+                )],
                 native_asset: vec![0],
                 foreign_asset: None,
                 handler_contract: "0x056C0E37d026f9639313C281250cA932C9dbe921".into(),
                 tx_indexer_url: Default::default(),
+                fee_strategy: Default::default(),
+                finality: Default::default(),
+                xcm_events: None,
+                xcm_dest_weight: None,
             },
             worker: AccountInfo {
                 account20: pre_mock_executor_address.into(),
                 account32: [0; 32],
             },
         }
-        .fetch_task(&client)
+        .fetch_task(&client, &RpcHealth::default())
         .unwrap()
         .unwrap();
 
@@ -802,27 +1202,40 @@ mod tests {
                         Chain {
                             id: 1,
                             name: String::from("Khala"),
-                            endpoint: String::from("http://127.0.0.1:39933"),
+                            endpoints: vec![RpcEndpoint::new("http://127.0.0.1:39933", 0)],
                             chain_type: ChainType::Sub,
                             native_asset: vec![0],
                             foreign_asset: None,
                             handler_contract: "0x056C0E37d026f9639313C281250cA932C9dbe921".into(),
                             tx_indexer_url: Default::default(),
+                            fee_strategy: Default::default(),
+                            finality: Default::default(),
+                            xcm_events: None,
+                            xcm_dest_weight: None,
                         },
                         Chain {
                             id: 2,
                             name: String::from("Ethereum"),
-                            endpoint: String::from("https://eth-goerli.g.alchemy.com/v2/lLqSMX_1unN9Xrdy_BB9LLZRgbrXwZv2"),
+                            endpoints: vec![RpcEndpoint::new("https://eth-goerli.g.alchemy.com/v2/lLqSMX_1unN9Xrdy_BB9LLZRgbrXwZv2", 0)],
                             chain_type: ChainType::Evm,
                             native_asset: vec![0],
                             foreign_asset: None,
                             handler_contract: "0x056C0E37d026f9639313C281250cA932C9dbe921".into(),
                             tx_indexer_url: Default::default(),
+                            fee_strategy: Default::default(),
+                            finality: Default::default(),
+                            xcm_events: None,
+                            xcm_dest_weight: None,
                         }
                     ],
                     assets: vec![],
+                    actions: vec![],
                 },
                 worker_accounts: worker_accounts.clone(),
+                rpc_health: &Default::default(),
+                action_cache: Default::default(),
+                xcm_delivery_cache: Default::default(),
+            tx_status_cache: Default::default(),
             },
             &client,
         ), Ok(()));
@@ -963,6 +1376,10 @@ mod tests {
             signer: worker_key,
             worker_accounts: vec![AccountInfo::from(worker_key)],
             registry: &Registry::new(),
+            rpc_health: &Default::default(),
+            action_cache: Default::default(),
+            xcm_delivery_cache: Default::default(),
+            tx_status_cache: Default::default(),
         };
 
         let mut calls = vec![];
@@ -1001,6 +1418,50 @@ mod tests {
         assert_eq!(calls[6].input_call, Some(0));
     }
 
+    #[test]
+    fn test_pending_execute_tx() {
+        pink_extension_runtime::mock_ext::mock_all_ext();
+
+        let mut task = Task {
+            source: "Moonbeam".to_string(),
+            claim_nonce: Some(3),
+            merged_steps: build_multi_steps(),
+            ..Default::default()
+        };
+        task.merged_steps[1].set_nonce(4);
+        assert_eq!(
+            task.step_index_by_nonce(&"Moonbeam".to_string(), 3),
+            Some(0)
+        );
+        assert_eq!(task.step_index_by_nonce(&"Phala".to_string(), 4), Some(1));
+        assert_eq!(task.step_index_by_nonce(&"Moonbeam".to_string(), 4), None);
+
+        let moonbeam = Chain {
+            name: String::from("Moonbeam"),
+            chain_type: ChainType::Evm,
+            ..Default::default()
+        };
+        task.record_execute_tx(0, 3, vec![1], TxKind::Execute, None, None);
+        task.record_execute_tx(0, 3, vec![2], TxKind::SpeedUp, None, None);
+        let pending_tx = task.pending_execute_tx(0, 3, &moonbeam).unwrap();
+        assert_eq!(pending_tx.hash, vec![2]);
+        assert_eq!(pending_tx.kind, TxKind::SpeedUp);
+        // Nonce re-applied, nothing pending with the new nonce
+        assert_eq!(task.pending_execute_tx(0, 4, &moonbeam), None);
+        // Just sent, not stuck yet
+        assert!(!is_stuck(&pending_tx, &moonbeam));
+        assert!(is_stuck(
+            &pending_tx,
+            &Chain {
+                fee_strategy: FeeStrategy {
+                    replace_after_sec: 0,
+                    ..Default::default()
+                },
+                ..moonbeam
+            }
+        ));
+    }
+
     #[test]
     #[ignore]
     fn test_fee_calculation() {
@@ -1016,6 +1477,10 @@ mod tests {
             signer: worker_key,
             registry: &Registry::default(),
             worker_accounts: vec![],
+            rpc_health: &Default::default(),
+            action_cache: Default::default(),
+            xcm_delivery_cache: Default::default(),
+            tx_status_cache: Default::default(),
         };
         let mut task = Task::default();
         task.id = hex::decode("0000000000000000000000000000000000000000000000000000000000000001")
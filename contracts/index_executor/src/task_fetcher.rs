@@ -1,5 +1,6 @@
 use crate::account::AccountInfo;
 use crate::chain::{Chain, ChainType};
+use crate::rpc::RpcHealth;
 use crate::storage::StorageClient;
 use crate::task::Task;
 use crate::task_deposit::{DepositData, EvmDepositData, Solution, SubDepositData};
@@ -33,10 +34,16 @@ impl ActivedTaskFetcher {
         ActivedTaskFetcher { chain, worker }
     }
 
-    pub fn fetch_task(&self, client: &StorageClient) -> Result<Option<Task>, &'static str> {
+    pub fn fetch_task(
+        &self,
+        client: &StorageClient,
+        health: &RpcHealth,
+    ) -> Result<Option<Task>, &'static str> {
         match self.chain.chain_type {
-            ChainType::Evm => Ok(self.query_evm_actived_task(&self.chain, &self.worker, client)?),
-            ChainType::Sub => Ok(self.query_sub_actived_task(&self.chain, &self.worker)?),
+            ChainType::Evm => {
+                Ok(self.query_evm_actived_task(&self.chain, &self.worker, client, health)?)
+            }
+            ChainType::Sub => Ok(self.query_sub_actived_task(&self.chain, &self.worker, health)?),
         }
     }
 
@@ -45,16 +52,9 @@ impl ActivedTaskFetcher {
         chain: &Chain,
         worker: &AccountInfo,
         client: &StorageClient,
+        health: &RpcHealth,
     ) -> Result<Option<Task>, &'static str> {
         let handler_on_goerli: H160 = H160::from_slice(&chain.handler_contract);
-        let transport = Eth::new(PinkHttp::new(&chain.endpoint));
-        let handler = Contract::from_json(
-            transport,
-            handler_on_goerli,
-            include_bytes!("./abi/handler.json"),
-        )
-        .map_err(|_| "ConstructContractFailed")?;
-
         let worker_address: Address = worker.account20.into();
         pink_extension::debug!(
             "Lookup actived task for worker {:?} on {:?}",
@@ -62,24 +62,43 @@ impl ActivedTaskFetcher {
             &chain.name
         );
 
-        let task_id: [u8; 32] = resolve_ready(handler.query(
-            "getNextActivedTask",
-            worker_address,
-            None,
-            Options::default(),
-            None,
-        ))
-        .map_err(|_| "FailedGetNextActivedTask")?;
-        if task_id == [0; 32] {
+        let Some((task_id, mut evm_deposit_data)) = chain.with_rpc(health, |endpoint| {
+            let transport = Eth::new(PinkHttp::new(endpoint));
+            let handler = Contract::from_json(
+                transport,
+                handler_on_goerli,
+                include_bytes!("./abi/handler.json"),
+            )
+            .map_err(|_| "ConstructContractFailed")?;
+
+            let task_id: [u8; 32] = resolve_ready(handler.query(
+                "getNextActivedTask",
+                worker_address,
+                None,
+                Options::default(),
+                None,
+            ))
+            .map_err(|_| "FailedGetNextActivedTask")?;
+            if task_id == [0; 32] {
+                return Ok(None);
+            }
+            pink_extension::debug!(
+                "getNextActivedTask, return task_id: {:?}",
+                hex::encode(task_id)
+            );
+            let evm_deposit_data: EvmDepositData = resolve_ready(handler.query(
+                "getTaskData",
+                task_id,
+                None,
+                Options::default(),
+                None,
+            ))
+            .map_err(|_| "FailedGetTaskData")?;
+            Ok(Some((task_id, evm_deposit_data)))
+        })?
+        else {
             return Ok(None);
-        }
-        pink_extension::debug!(
-            "getNextActivedTask, return task_id: {:?}",
-            hex::encode(task_id)
-        );
-        let mut evm_deposit_data: EvmDepositData =
-            resolve_ready(handler.query("getTaskData", task_id, None, Options::default(), None))
-                .map_err(|_| "FailedGetTaskData")?;
+        };
         pink_extension::debug!(
             "Fetch deposit data successfully for task {:?} on {:?}, deposit data: {:?}",
             &hex::encode(task_id),
@@ -109,34 +128,37 @@ impl ActivedTaskFetcher {
         &self,
         chain: &Chain,
         worker: &AccountInfo,
+        health: &RpcHealth,
     ) -> Result<Option<Task>, &'static str> {
-        if let Some(raw_storage) = get_storage(
-            &chain.endpoint,
-            &storage_map_prefix::<Twox64Concat>(
-                &storage_prefix("PalletIndex", "ActivedTasks")[..],
-                &worker.account32,
-            ),
-            None,
-        )
-        .log_err("Read storage [actived task] failed")
-        .map_err(|_| "FailedGetTaskData")?
-        {
+        if let Some(raw_storage) = chain.with_rpc(health, |endpoint| {
+            get_storage(
+                endpoint,
+                &storage_map_prefix::<Twox64Concat>(
+                    &storage_prefix("PalletIndex", "ActivedTasks")[..],
+                    &worker.account32,
+                ),
+                None,
+            )
+            .log_err("Read storage [actived task] failed")
+            .map_err(|_| "FailedGetTaskData")
+        })? {
             let actived_tasks: Vec<[u8; 32]> = scale::Decode::decode(&mut raw_storage.as_slice())
                 .log_err("Decode storage [actived task] failed")
                 .map_err(|_| "DecodeStorageFailed")?;
             if !actived_tasks.is_empty() {
                 let oldest_task = actived_tasks[0];
-                if let Some(raw_storage) = get_storage(
-                    &chain.endpoint,
-                    &storage_map_prefix::<Twox64Concat>(
-                        &storage_prefix("PalletIndex", "DepositRecords")[..],
-                        &oldest_task,
-                    ),
-                    None,
-                )
-                .log_err("Read storage [actived task] failed")
-                .map_err(|_| "FailedGetDepositData")?
-                {
+                if let Some(raw_storage) = chain.with_rpc(health, |endpoint| {
+                    get_storage(
+                        endpoint,
+                        &storage_map_prefix::<Twox64Concat>(
+                            &storage_prefix("PalletIndex", "DepositRecords")[..],
+                            &oldest_task,
+                        ),
+                        None,
+                    )
+                    .log_err("Read storage [actived task] failed")
+                    .map_err(|_| "FailedGetDepositData")
+                })? {
                     let sub_deposit_data: SubDepositData =
                         scale::Decode::decode(&mut raw_storage.as_slice())
                             .log_err("Decode storage [deposit data] failed")
@@ -166,6 +188,7 @@ impl ActivedTaskFetcher {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::rpc::RpcEndpoint;
     use hex_literal::hex;
     use primitive_types::H160;
 
@@ -180,18 +203,25 @@ mod tests {
                 id: 0,
                 name: String::from("Moonbeam"),
                 chain_type: ChainType::Evm,
-                endpoint: String::from("https://moonbeam.api.onfinality.io/public"),
+                endpoints: vec![RpcEndpoint::new(
+                    "https://moonbeam.api.onfinality.io/public",
+                    0,
+                )],
                 native_asset: vec![0],
                 foreign_asset: None,
                 handler_contract: hex!("f778f213B618bBAfCF827b2a5faE93966697E4B5").into(),
                 tx_indexer_url: Default::default(),
+                fee_strategy: Default::default(),
+                finality: Default::default(),
+                xcm_events: None,
+                xcm_dest_weight: None,
             },
             worker: AccountInfo {
                 account20: worker_address.into(),
                 account32: [0; 32],
             },
         }
-        .fetch_task(&client)
+        .fetch_task(&client, &RpcHealth::default())
         .unwrap()
         .unwrap();
     }
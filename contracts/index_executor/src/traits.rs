@@ -1,5 +1,6 @@
 use super::context::Context;
 use super::storage::StorageClient;
+use super::task::ExecuteTx;
 use alloc::vec::Vec;
 use xcm::v3::MultiLocation;
 
@@ -21,8 +22,23 @@ pub trait Runner {
 
     /// Check if a job is already executed successfully when executing the job.
     ///
-    /// Only when the transaction was successfully executed, it can return `true`
-    fn has_finished(&self, nonce: u64, context: &Context) -> Result<bool, &'static str>;
+    /// Only when the transaction was successfully executed, it can return `true`.
+    /// `sent_txs` are transactions sent with the nonce, used to look up the execution result.
+    fn has_finished(
+        &self,
+        nonce: u64,
+        sent_txs: &[ExecuteTx],
+        context: &Context,
+    ) -> Result<bool, &'static str>;
+
+    /// Check if the transaction executed the job has been included in a block that reached
+    /// the finality required by the chain, job can only be settled after that.
+    fn has_finalized(
+        &self,
+        nonce: u64,
+        sent_txs: &[ExecuteTx],
+        context: &Context,
+    ) -> Result<bool, &'static str>;
 }
 
 pub trait AssetRegistry<T> {
@@ -1,7 +1,40 @@
-use alloc::{format, string::String, vec, vec::Vec};
-use pink_extension::http_req;
-use scale::Decode;
-use serde::Deserialize;
+use crate::chain::{Chain, ChainType, Finality};
+use crate::rpc::RpcHealth;
+use crate::task::{ExecuteTx, TxKind};
+use alloc::{boxed::Box, format, string::String, vec, vec::Vec};
+use core::ops::Range;
+use pink_extension::{http_req, ResultExt};
+use pink_subrpc::{
+    hasher::{Blake2_256, Hasher},
+    storage::storage_prefix,
+};
+use pink_web3::{
+    api::{Accounts, Eth, Namespace},
+    contract::Options,
+    ethabi::Address,
+    keys::pink::KeyPair,
+    transports::{resolve_ready, PinkHttp},
+    types::{
+        BlockId, BlockNumber, Bytes, SignedTransaction, TransactionId, TransactionParameters, H256,
+        U64,
+    },
+};
+use scale::{Compact, Decode, Encode};
+use serde::{Deserialize, Serialize};
+
+/// Max number of blocks scanned to find a substrate extrinsic or an XCM message
+const MAX_SCAN_BLOCKS: u32 = 50;
+/// Tolerance between worker clock and block timestamp in milliseconds
+const CLOCK_DRIFT_MS: u64 = 60_000;
+/// Pallet index of `frame_system`, which is 0 on all supported chains
+const SYSTEM_PALLET_INDEX: u8 = 0;
+/// Variant index of `Success` and `Fail` events of pallet xcmpQueue
+const XCMP_SUCCESS_EVENT: u8 = 0;
+const XCMP_FAIL_EVENT: u8 = 1;
+/// Variant index of `ExecutedDownward` event of pallet dmpQueue
+const DMP_EXECUTED_DOWNWARD_EVENT: u8 = 2;
+/// Variant index of `Processed` event of pallet messageQueue
+const MESSAGE_QUEUE_PROCESSED_EVENT: u8 = 1;
 
 #[derive(Deserialize, Clone, Debug, PartialEq, Eq, Decode)]
 #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
@@ -91,27 +124,1216 @@ fn get_tx(
     }))
 }
 
-/// Return true if transaction is confirmed on chain
-pub fn has_confirmed(indexer_url: &str, account: &[u8], nonce: u64) -> Result<bool, &'static str> {
+/// Execution result of a transaction included on chain
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TxStatus {
+    pub result: bool,
+    /// Number of the block including the transaction
+    pub block_number: u64,
+}
+
+/// Source of transaction execution results
+trait ConfirmationProvider {
+    fn name(&self) -> &'static str;
+
+    /// Return execution result of the transaction sent by `account` with `nonce`,
+    /// `None` if the provider can not find the transaction.
+    ///
+    /// `sent_txs` are all transactions we sent with the nonce, only one of them can be
+    /// included on chain.
+    fn get_result(
+        &self,
+        account: &[u8],
+        nonce: u64,
+        sent_txs: &[ExecuteTx],
+    ) -> Result<Option<TxStatus>, &'static str>;
+}
+
+/// Off-chain indexer that indexes transactions by account and nonce
+struct IndexerProvider<'a> {
+    url: &'a str,
+}
+
+impl<'a> ConfirmationProvider for IndexerProvider<'a> {
+    fn name(&self) -> &'static str {
+        "indexer"
+    }
+
+    fn get_result(
+        &self,
+        account: &[u8],
+        nonce: u64,
+        sent_txs: &[ExecuteTx],
+    ) -> Result<Option<TxStatus>, &'static str> {
+        pink_extension::debug!(
+            "Trying to fetch tx data for account {:?} from indexer {:?} with nonce {:?}",
+            hex::encode(account),
+            self.url,
+            nonce
+        );
+        let tx = get_tx(self.url, account, nonce)?;
+        pink_extension::debug!("Tx record returned from off-chain indexer: {:?}", tx);
+        Ok(tx.and_then(|tx| indexed_tx_status(&tx, sent_txs)))
+    }
+}
+
+/// Execution result of the transaction found by the indexer.
+///
+/// Like other providers, a cancellation occupying the nonce means the step never got executed.
+/// If the indexed transaction can not be matched with what we sent while a cancellation has
+/// been sent, the result is unknown.
+fn indexed_tx_status(tx: &Transaction, sent_txs: &[ExecuteTx]) -> Option<TxStatus> {
+    let kind = match decode_hex(&tx.id) {
+        Ok(hash) => sent_txs
+            .iter()
+            .find(|sent| sent.hash == hash)
+            .map(|sent| sent.kind.clone()),
+        Err(_) => None,
+    };
+    let cancelled = match kind {
+        Some(kind) => kind == TxKind::Cancel,
+        None if sent_txs.iter().any(|sent| sent.kind == TxKind::Cancel) => return None,
+        None => false,
+    };
+    Some(TxStatus {
+        result: !cancelled && tx.result,
+        block_number: tx.block_number,
+    })
+}
+
+/// Read `eth_getTransactionReceipt` of the sent transactions from EVM RPC node
+struct EvmReceiptProvider<'a> {
+    chain: &'a Chain,
+    health: &'a RpcHealth,
+}
+
+impl<'a> ConfirmationProvider for EvmReceiptProvider<'a> {
+    fn name(&self) -> &'static str {
+        "evm receipt"
+    }
+
+    fn get_result(
+        &self,
+        _account: &[u8],
+        _nonce: u64,
+        sent_txs: &[ExecuteTx],
+    ) -> Result<Option<TxStatus>, &'static str> {
+        for tx in sent_txs.iter().filter(|tx| tx.hash.len() == 32) {
+            let receipt = self.chain.with_rpc(self.health, |endpoint| {
+                let eth = Eth::new(PinkHttp::new(endpoint));
+                resolve_ready(eth.transaction_receipt(H256::from_slice(&tx.hash))).map_err(|e| {
+                    pink_extension::error!("Failed to get transaction receipt with error: {:?}", e);
+                    "FailedToGetReceipt"
+                })
+            })?;
+            if let Some(receipt) = receipt {
+                pink_extension::debug!(
+                    "Found receipt of tx {:?}, status: {:?}",
+                    hex::encode(&tx.hash),
+                    receipt.status
+                );
+                // Cancellation occupied the nonce, the step itself never got executed
+                return Ok(Some(TxStatus {
+                    result: tx.kind != TxKind::Cancel && receipt.status == Some(U64::from(1)),
+                    block_number: receipt
+                        .block_number
+                        .ok_or("ReceiptMissingBlockNumber")?
+                        .as_u64(),
+                }));
+            }
+        }
+        Ok(None)
+    }
+}
+
+/// Find the sent extrinsics in recent blocks and read `System.Events` at the inclusion block
+struct SubEventsProvider<'a> {
+    chain: &'a Chain,
+    health: &'a RpcHealth,
+}
+
+#[derive(Deserialize)]
+struct RpcResponse<T> {
+    result: T,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BlockHeader {
+    parent_hash: String,
+    number: String,
+}
+
+impl BlockHeader {
+    fn number(&self) -> Result<u64, &'static str> {
+        u64::from_str_radix(self.number.trim_start_matches("0x"), 16).or(Err("InvalidBlockNumber"))
+    }
+}
+
+#[derive(Deserialize)]
+struct Block {
+    header: BlockHeader,
+    extrinsics: Vec<String>,
+}
+
+#[derive(Deserialize)]
+struct SignedBlock {
+    block: Block,
+}
+
+fn sub_rpc_call<T: serde::de::DeserializeOwned>(
+    endpoint: &str,
+    method: &str,
+    params: &str,
+) -> Result<T, &'static str> {
+    let body = format!(r#"{{"id":1,"jsonrpc":"2.0","method":"{method}","params":[{params}]}}"#);
+    let response = send_request(endpoint, &body).or(Err("CallRpcFailed"))?;
+    let response: RpcResponse<T> =
+        pink_json::from_slice(&response).or(Err("InvalidRpcResponse"))?;
+    Ok(response.result)
+}
+
+impl<'a> SubEventsProvider<'a> {
+    fn get_storage_at(
+        endpoint: &str,
+        key: &[u8],
+        block_hash: &str,
+    ) -> Result<Vec<u8>, &'static str> {
+        let raw_storage: Option<String> = sub_rpc_call(
+            endpoint,
+            "state_getStorage",
+            &format!(r#""0x{}","{}""#, hex::encode(key), block_hash),
+        )?;
+        let raw_storage = raw_storage.ok_or("StorageNotFound")?;
+        decode_hex(&raw_storage)
+    }
+
+    /// Find the sent extrinsic on chain, return its execution result along with the index and
+    /// events of it.
+    ///
+    /// Blocks are looked up forward from where the transactions were sent, transactions
+    /// recorded without it are looked for in recent blocks
+    fn find_extrinsic(
+        endpoint: &str,
+        sent_txs: &[ExecuteTx],
+    ) -> Result<Option<IncludedExtrinsic>, &'static str> {
+        let mut find =
+            |block: &Block, block_hash: &str| -> Result<Option<IncludedExtrinsic>, &'static str> {
+                for (index, extrinsic) in block.extrinsics.iter().enumerate() {
+                    let extrinsic_hash = Blake2_256::hash(&decode_hex(extrinsic)?);
+                    if let Some(tx) = sent_txs.iter().find(|tx| tx.hash[..] == extrinsic_hash[..]) {
+                        pink_extension::debug!(
+                            "Found extrinsic {:?} in block {:?}",
+                            hex::encode(&tx.hash),
+                            block_hash
+                        );
+                        let events = Self::get_storage_at(
+                            endpoint,
+                            &storage_prefix("System", "Events"),
+                            block_hash,
+                        )?;
+                        let index = index as u32;
+                        let result =
+                            extrinsic_result(&events, index).ok_or("ExtrinsicResultNotFound")?;
+                        return Ok(Some(IncludedExtrinsic {
+                            status: TxStatus {
+                                result: tx.kind != TxKind::Cancel && result,
+                                block_number: block.header.number()?,
+                            },
+                            index,
+                            events,
+                        }));
+                    }
+                }
+                Ok(None)
+            };
+
+        if let Some(from) = sent_txs.iter().filter_map(|tx| tx.sent_block).min() {
+            return scan_blocks_from(endpoint, from, &mut find);
+        }
+        match sent_txs.iter().map(|tx| tx.sent_at).min() {
+            Some(since) => scan_blocks(endpoint, since, &mut find),
+            None => Ok(None),
+        }
+    }
+}
+
+/// Extrinsic found on chain with the events emitted in its block
+struct IncludedExtrinsic {
+    status: TxStatus,
+    /// Index of the extrinsic in the block
+    index: u32,
+    /// Encoded `System.Events` of the block
+    events: Vec<u8>,
+}
+
+/// Walk forward from block `from` until `f` returns a result, stop at the best block or after
+/// `MAX_SCAN_BLOCKS` blocks, within which a transaction is included or has expired
+fn scan_blocks_from<T>(
+    endpoint: &str,
+    from: u64,
+    mut f: impl FnMut(&Block, &str) -> Result<Option<T>, &'static str>,
+) -> Result<Option<T>, &'static str> {
+    let best: BlockHeader = sub_rpc_call(endpoint, "chain_getHeader", "")?;
+    let to = best.number()?.min(from + MAX_SCAN_BLOCKS as u64);
+    for number in from..=to {
+        let block_hash: Option<String> =
+            sub_rpc_call(endpoint, "chain_getBlockHash", &number.to_string())?;
+        let block_hash = block_hash.ok_or("BlockNotFound")?;
+        let block: SignedBlock =
+            sub_rpc_call(endpoint, "chain_getBlock", &format!(r#""{}""#, block_hash))?;
+        if let Some(result) = f(&block.block, &block_hash)? {
+            return Ok(Some(result));
+        }
+    }
+
+    Ok(None)
+}
+
+/// Walk back from the best block until `f` returns a result, stop when reaching blocks
+/// produced before `since`
+fn scan_blocks<T>(
+    endpoint: &str,
+    since: u64,
+    mut f: impl FnMut(&Block, &str) -> Result<Option<T>, &'static str>,
+) -> Result<Option<T>, &'static str> {
+    let mut block_hash: String = sub_rpc_call(endpoint, "chain_getBlockHash", "")?;
+    for _ in 0..MAX_SCAN_BLOCKS {
+        let block: SignedBlock =
+            sub_rpc_call(endpoint, "chain_getBlock", &format!(r#""{}""#, block_hash))?;
+        if let Some(result) = f(&block.block, &block_hash)? {
+            return Ok(Some(result));
+        }
+
+        // Stop when reaching blocks produced before the transactions sent
+        let timestamp = SubEventsProvider::get_storage_at(
+            endpoint,
+            &storage_prefix("Timestamp", "Now"),
+            &block_hash,
+        )?;
+        let timestamp = u64::decode(&mut timestamp.as_slice()).or(Err("InvalidTimestamp"))?;
+        if timestamp + CLOCK_DRIFT_MS < since {
+            break;
+        }
+        block_hash = block.block.header.parent_hash;
+    }
+
+    Ok(None)
+}
+
+impl<'a> ConfirmationProvider for SubEventsProvider<'a> {
+    fn name(&self) -> &'static str {
+        "substrate events"
+    }
+
+    fn get_result(
+        &self,
+        _account: &[u8],
+        _nonce: u64,
+        sent_txs: &[ExecuteTx],
+    ) -> Result<Option<TxStatus>, &'static str> {
+        // Blocks are scanned on a single endpoint, retry from start on the next one if it fails
+        self.chain.with_rpc(self.health, |endpoint| {
+            Ok(Self::find_extrinsic(endpoint, sent_txs)?.map(|extrinsic| extrinsic.status))
+        })
+    }
+}
+
+fn decode_hex(s: &str) -> Result<Vec<u8>, &'static str> {
+    hex::decode(s.trim_start_matches("0x")).or(Err("InvalidHex"))
+}
+
+/// Find the `ExtrinsicSuccess` or `ExtrinsicFailed` event emitted by extrinsic with `index` from
+/// encoded `System.Events`.
+///
+/// Events of different runtimes can not be decoded without metadata, but both events are emitted
+/// in phase `ApplyExtrinsic(index)` as variant 0 and 1 of `frame_system`, so we look for the encoded
+/// record prefix directly.
+fn extrinsic_result(events: &[u8], index: u32) -> Option<bool> {
+    let phase = [
+        &[0u8][..],
+        &index.to_le_bytes()[..],
+        &[SYSTEM_PALLET_INDEX][..],
+    ]
+    .concat();
+    events
+        .windows(phase.len() + 1)
+        .find(|record| record.starts_with(&phase) && record[phase.len()] <= 1)
+        .map(|record| record[phase.len()] == 0)
+}
+
+/// Find hash of the message sent by extrinsic with `index` from the `XcmpMessageSent` event of
+/// pallet xcmpQueue, as reported by `sender`.
+fn sent_message_hash(events: &[u8], index: u32, sender: &XcmSender) -> Option<[u8; 32]> {
+    let XcmSender::XcmpQueue {
+        pallet_id,
+        event_id,
+        optional_hash,
+    } = *sender
+    else {
+        return None;
+    };
+    let prefix = [
+        &[0u8][..],
+        &index.to_le_bytes()[..],
+        &[pallet_id, event_id][..],
+        // `Some` of `Option<XcmHash>`
+        if optional_hash { &[1u8][..] } else { &[][..] },
+    ]
+    .concat();
+    events
+        .windows(prefix.len() + 32)
+        .find(|record| record.starts_with(&prefix))
+        .map(|record| record[prefix.len()..].try_into().expect("Length checked"))
+}
+
+/// Whether an event record may start at `position` of encoded `System.Events`, that is the bytes
+/// before it can be the end of an encoded `Phase`: `ApplyExtrinsic(u32)` of the first 65536
+/// extrinsics, `Finalization` or `Initialization`.
+///
+/// Events can not be decoded without metadata, this rules out most of the false matches of
+/// event prefixes found in the fields of other events.
+fn is_event_start(events: &[u8], position: usize) -> bool {
+    matches!(events[..position], [.., 1 | 2] | [.., 0, _, _, 0, 0])
+}
+
+/// XCM message reported processed by a queue in encoded `System.Events`
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct ProcessedMessage {
+    /// Position of the event in the events
+    position: usize,
+    /// Hash of the message, or its id reported by `MessageQueue.Processed`
+    id: [u8; 32],
+    /// Whether the message is sent by the relay chain
+    downward: bool,
+    success: bool,
+}
+
+/// Decode the event at the start of `record` if it reports a message processed by `queue`.
+///
+/// Events of the queue that fail to decode are reported as errors, the pallet or the event may
+/// have been changed by a runtime upgrade and the queue of the chain needs to be updated
+fn processed_message(
+    record: &[u8],
+    queue: &XcmQueue,
+) -> Result<Option<([u8; 32], bool, bool)>, &'static str> {
+    let mut input = record;
+    let Ok((pallet, event)) = <(u8, u8)>::decode(&mut input) else {
+        return Ok(None);
+    };
+    let invalid = |_| "InvalidXcmQueueEvent";
+    match *queue {
+        // `Success { message_hash: Option<XcmHash>, weight }` and
+        // `Fail { message_hash: Option<XcmHash>, error, weight }`
+        XcmQueue::XcmpQueue(pallet_id)
+            if pallet == pallet_id && (event == XCMP_SUCCESS_EVENT || event == XCMP_FAIL_EVENT) =>
+        {
+            let hash = Option::<[u8; 32]>::decode(&mut input).map_err(invalid)?;
+            Ok(hash.map(|hash| (hash, false, event == XCMP_SUCCESS_EVENT)))
+        }
+        // `ExecutedDownward { message_id, outcome }`, outcome is `Complete(Weight)` on success
+        XcmQueue::DmpQueue(pallet_id)
+            if pallet == pallet_id && event == DMP_EXECUTED_DOWNWARD_EVENT =>
+        {
+            let (hash, outcome) = <([u8; 32], u8)>::decode(&mut input).map_err(invalid)?;
+            Ok(Some((hash, true, outcome == 0)))
+        }
+        // `Processed { id, origin, weight_used, success }`, origin is `Here`, `Parent` or
+        // `Sibling(ParaId)` on parachains
+        XcmQueue::MessageQueue(pallet_id)
+            if pallet == pallet_id && event == MESSAGE_QUEUE_PROCESSED_EVENT =>
+        {
+            let id = <[u8; 32]>::decode(&mut input).map_err(invalid)?;
+            let origin = u8::decode(&mut input).map_err(invalid)?;
+            match origin {
+                0 | 1 => {}
+                2 => {
+                    u32::decode(&mut input).map_err(invalid)?;
+                }
+                _ => return Err("InvalidXcmQueueEvent"),
+            }
+            let _weight_used =
+                <(Compact<u64>, Compact<u64>)>::decode(&mut input).map_err(invalid)?;
+            let success = bool::decode(&mut input).map_err(invalid)?;
+            Ok(Some((id, origin == 1, success)))
+        }
+        _ => Ok(None),
+    }
+}
+
+/// All messages reported processed by `queues` in encoded `System.Events`, in the order of events
+fn processed_messages(
+    events: &[u8],
+    queues: &[XcmQueue],
+) -> Result<Vec<ProcessedMessage>, &'static str> {
+    let mut messages = vec![];
+    for position in (0..events.len()).filter(|position| is_event_start(events, *position)) {
+        for queue in queues.iter() {
+            if let Some((id, downward, success)) = processed_message(&events[position..], queue)? {
+                messages.push(ProcessedMessage {
+                    position,
+                    id,
+                    downward,
+                    success,
+                });
+            }
+        }
+    }
+    Ok(messages)
+}
+
+/// Amount of the last deposit to `recipient` by one of `deposits` in `range` of encoded
+/// `System.Events`.
+///
+/// Deposit events, e.g. `Balances.Deposit`, `Tokens.Deposited` and `Assets.Issued`, all end with
+/// the account followed by the amount, the asset id before the account is skipped by its length.
+fn last_deposit(
+    events: &[u8],
+    range: Range<usize>,
+    recipient: &[u8],
+    deposits: &[DepositEvent],
+) -> Option<u128> {
+    range
+        .rev()
+        .filter(|position| is_event_start(events, *position))
+        .find_map(|position| {
+            let record = &events[position..];
+            deposits.iter().find_map(|deposit| {
+                let account = 2 + deposit.asset_id_len as usize;
+                let amount = account + recipient.len();
+                match record {
+                    [pallet, event, ..]
+                        if *pallet == deposit.pallet_id
+                            && *event == deposit.event_id
+                            && record.len() >= amount + 16
+                            && &record[account..amount] == recipient =>
+                    {
+                        Some(u128::from_le_bytes(
+                            record[amount..amount + 16]
+                                .try_into()
+                                .expect("Length checked"),
+                        ))
+                    }
+                    _ => None,
+                }
+            })
+        })
+}
+
+// Merge results returned by providers, providers failed to respond are skipped,
+// results from different providers must be the same. The highest block number
+// is taken in case a provider lags behind a reorg
+fn reconcile(
+    results: &[(&'static str, Result<Option<TxStatus>, &'static str>)],
+) -> Result<Option<TxStatus>, &'static str> {
+    let mut confirmed: Option<TxStatus> = None;
+    for (name, result) in results.iter() {
+        match result {
+            Ok(Some(status)) => match &confirmed {
+                Some(confirmed) if confirmed.result != status.result => {
+                    pink_extension::error!(
+                        "Tx result returned by {:?} is {:?}, mismatch with other providers",
+                        name,
+                        status.result
+                    );
+                    return Err("ConfirmationMismatch");
+                }
+                Some(confirmed) if confirmed.block_number >= status.block_number => {}
+                _ => confirmed = Some(status.clone()),
+            },
+            Ok(None) => {}
+            Err(e) => pink_extension::error!("Confirmation provider {:?} failed: {:?}", name, e),
+        }
+    }
+    if confirmed.is_none() && results.iter().all(|(_, result)| result.is_err()) {
+        return Err("ConfirmationUnavailable");
+    }
+    Ok(confirmed)
+}
+
+/// Sign a transaction calling `to` with `data` on an EVM chain, `options` are expected to be
+/// built by `gas::tx_options`. Fields missing from `options`, e.g. nonce, are read from `eth`.
+///
+/// The signed transaction should be sent with `send_raw_tx`, so that retrying on other endpoints
+/// won't sign another transaction with a different hash.
+pub fn sign_tx(
+    eth: &Eth<PinkHttp>,
+    to: Address,
+    data: Vec<u8>,
+    options: Options,
+    key: KeyPair,
+) -> Result<SignedTransaction, &'static str> {
+    let tx = TransactionParameters {
+        nonce: options.nonce,
+        to: Some(to),
+        gas: options.gas.ok_or("MissingGasLimit")?,
+        gas_price: options.gas_price,
+        value: options.value.unwrap_or_default(),
+        data: Bytes(data),
+        transaction_type: options.transaction_type,
+        max_fee_per_gas: options.max_fee_per_gas,
+        max_priority_fee_per_gas: options.max_priority_fee_per_gas,
+        ..Default::default()
+    };
+    resolve_ready(Accounts::new(eth.transport().clone()).sign_transaction(tx, key))
+        .log_err("Failed to sign transaction")
+        .or(Err("SignTransactionFailed"))
+}
+
+/// Broadcast the signed transaction to endpoints of `chain` until one accepts it, return hash
+/// of the transaction.
+///
+/// An endpoint may have accepted the transaction before failing to respond, which makes the
+/// next endpoint reject it as a duplicate, so the transaction is looked up before giving up.
+pub fn send_raw_tx(
+    chain: &Chain,
+    signed_tx: &SignedTransaction,
+    health: &RpcHealth,
+) -> Result<Vec<u8>, &'static str> {
+    let tx_hash = signed_tx.transaction_hash;
+    chain.with_rpc(health, |endpoint| {
+        let eth = Eth::new(PinkHttp::new(endpoint));
+        match resolve_ready(eth.send_raw_transaction(signed_tx.raw_transaction.clone())) {
+            Ok(hash) => Ok(hash.as_bytes().to_vec()),
+            Err(e) => match resolve_ready(eth.transaction(TransactionId::Hash(tx_hash))) {
+                Ok(Some(_)) => Ok(tx_hash.as_bytes().to_vec()),
+                _ => {
+                    pink_extension::error!(
+                        "Failed to send transaction {:?} with error: {:?}",
+                        hex::encode(tx_hash.as_bytes()),
+                        e
+                    );
+                    Err("FailedToSubmitTransaction")
+                }
+            },
+        }
+    })
+}
+
+/// Execution result of an XCM message on the destination chain
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum XcmDelivery {
+    /// Message hasn't been processed by the destination chain yet
+    Pending,
+    /// Message executed, with the amount deposited to the recipient if found
+    Succeeded {
+        received: Option<u128>,
+    },
+    Failed,
+}
+
+/// How a chain sends XCM messages
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Encode, Decode, Serialize, Deserialize)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+pub enum XcmSender {
+    /// Parachain sending messages to siblings through pallet xcmpQueue at `pallet_id`, which
+    /// reports hash of the sent message with event `XcmpMessageSent` at `event_id`. The hash
+    /// is encoded as `Option<XcmHash>` if `optional_hash`, as in runtimes before polkadot v1.0
+    XcmpQueue {
+        pallet_id: u8,
+        event_id: u8,
+        optional_hash: bool,
+    },
+    /// Relay chain sending downward messages to parachains, which reports nothing about the
+    /// sent message
+    Dmp,
+}
+
+/// Queue through which a parachain receives XCM messages, with index of the pallet
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Encode, Decode, Serialize, Deserialize)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+pub enum XcmQueue {
+    /// Pallet xcmpQueue, messages sent by sibling parachains
+    XcmpQueue(u8),
+    /// Pallet dmpQueue, messages sent by the relay chain
+    DmpQueue(u8),
+    /// Pallet messageQueue, which replaces both queues above in later runtimes
+    MessageQueue(u8),
+}
+
+/// Event depositing assets to an account, e.g. `Balances.Deposit`
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Encode, Decode, Serialize, Deserialize)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+pub struct DepositEvent {
+    pub pallet_id: u8,
+    pub event_id: u8,
+    /// Length of the encoded asset id preceding the account
+    pub asset_id_len: u8,
+}
+
+/// Pallets and events through which a Substrate chain reports the XCM messages it sent and
+/// received, by their indices in the runtime of the chain. Runtime upgrades may reorder them,
+/// they are updated along with the chain in the registry
+#[derive(Clone, Debug, Default, PartialEq, Eq, Encode, Decode, Serialize, Deserialize)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+pub struct XcmEvents {
+    /// How the chain sends XCM messages, delivery of messages sent by chains without it is
+    /// checked by balance change of the recipient
+    pub sender: Option<XcmSender>,
+    /// Queues through which the chain receives XCM messages, delivery of messages sent to
+    /// chains without them is checked by balance change of the recipient
+    pub queues: Vec<XcmQueue>,
+    /// Events depositing assets received by XCM messages
+    pub deposits: Vec<DepositEvent>,
+}
+
+/// XCM message sent by a bridge step, as identified on the destination chain
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum XcmMessage {
+    /// Message with the hash reported by the source chain
+    Hash([u8; 32]),
+    /// Downward message from the relay chain, identified by the deposit to the recipient
+    Downward,
+}
+
+fn xcm_sender(chain: &Chain) -> Option<XcmSender> {
+    chain.xcm_events.as_ref().and_then(|events| events.sender)
+}
+
+/// Queues and deposit events of `chain`, `None` if XCM delivery to `chain` is not tracked
+pub fn xcm_receiver(chain: &Chain) -> Option<(&[XcmQueue], &[DepositEvent])> {
+    chain
+        .xcm_events
+        .as_ref()
+        .filter(|events| !events.queues.is_empty())
+        .map(|events| (events.queues.as_slice(), events.deposits.as_slice()))
+}
+
+/// Return the XCM message sent by the extrinsic in `sent_txs`, `None` if the extrinsic has
+/// not been found or the message can not be tracked.
+pub fn xcm_message(
+    chain: &Chain,
+    sent_txs: &[ExecuteTx],
+    health: &RpcHealth,
+) -> Result<Option<XcmMessage>, &'static str> {
+    if chain.chain_type != ChainType::Sub {
+        return Ok(None);
+    }
+    let sender = match xcm_sender(chain) {
+        Some(XcmSender::Dmp) => return Ok(Some(XcmMessage::Downward)),
+        Some(sender) => sender,
+        None => return Ok(None),
+    };
+    let extrinsic = chain.with_rpc(health, |endpoint| {
+        SubEventsProvider::find_extrinsic(endpoint, sent_txs)
+    })?;
+    let Some(extrinsic) = extrinsic else {
+        return Ok(None);
+    };
+    // A successful transfer always sends the message, the event of the chain may have been
+    // changed by a runtime upgrade
+    let hash = sent_message_hash(&extrinsic.events, extrinsic.index, &sender).ok_or_else(|| {
+        pink_extension::error!("XCM message sent by {:?} not found in events", &sender);
+        "XcmMessageNotFound"
+    })?;
+    Ok(Some(XcmMessage::Hash(hash)))
+}
+
+/// Find the result of `message` in processed messages of a block. Assets are deposited while
+/// executing the message, so the received amount is read from the last deposit to `recipient`
+/// after the previous message processed.
+fn find_delivery(
+    events: &[u8],
+    message: &XcmMessage,
+    recipient: &[u8],
+    queues: &[XcmQueue],
+    deposits: &[DepositEvent],
+) -> Result<Option<XcmDelivery>, &'static str> {
+    let processed = processed_messages(events, queues)?;
+    Ok(processed
+        .iter()
+        .enumerate()
+        .find_map(|(i, processed_message)| {
+            let start = match i {
+                0 => 0,
+                _ => processed[i - 1].position + 2,
+            };
+            let received = || {
+                last_deposit(
+                    events,
+                    start..processed_message.position,
+                    recipient,
+                    deposits,
+                )
+            };
+            match message {
+                XcmMessage::Hash(hash) if &processed_message.id == hash => {
+                    Some(match processed_message.success {
+                        true => XcmDelivery::Succeeded {
+                            received: received(),
+                        },
+                        false => XcmDelivery::Failed,
+                    })
+                }
+                // Failed downward messages can not be told apart, they are left pending
+                XcmMessage::Downward if processed_message.downward && processed_message.success => {
+                    received().map(|received| XcmDelivery::Succeeded {
+                        received: Some(received),
+                    })
+                }
+                _ => None,
+            }
+        }))
+}
+
+/// Look for the execution result of XCM `message` in blocks of `chain` from block `from`.
+///
+/// Messages are processed within `MAX_SCAN_BLOCKS` blocks after sent, `None` is returned if
+/// the message is not found once all of them are produced, delivery is then checked by
+/// balance change of the recipient
+pub fn xcm_delivery(
+    chain: &Chain,
+    message: &XcmMessage,
+    recipient: &[u8],
+    from: u64,
+    health: &RpcHealth,
+) -> Result<Option<XcmDelivery>, &'static str> {
+    let (queues, deposits) = xcm_receiver(chain).ok_or("XcmDeliveryNotTracked")?;
+    chain.with_rpc(health, |endpoint| {
+        let best = best_block_number(chain, endpoint)?;
+        let delivery = scan_blocks_from(endpoint, from, |_block, block_hash| {
+            let events = SubEventsProvider::get_storage_at(
+                endpoint,
+                &storage_prefix("System", "Events"),
+                block_hash,
+            )?;
+            let delivery = find_delivery(&events, message, recipient, queues, deposits)?;
+            if let Some(delivery) = &delivery {
+                pink_extension::debug!(
+                    "Found XCM message {:?} processed in block {:?}: {:?}",
+                    message,
+                    block_hash,
+                    delivery
+                );
+            }
+            Ok(delivery)
+        })?;
+        Ok(scanned_delivery(delivery, from, best))
+    })
+}
+
+/// Delivery found by scanning blocks from `from` up to the best block `best`, pending if the
+/// message may still be processed in blocks to come
+fn scanned_delivery(found: Option<XcmDelivery>, from: u64, best: u64) -> Option<XcmDelivery> {
+    match found {
+        Some(delivery) => Some(delivery),
+        None if best > from + MAX_SCAN_BLOCKS as u64 => None,
+        None => Some(XcmDelivery::Pending),
+    }
+}
+
+/// Number of the best block of `chain`, recorded along with a transaction about to send to find
+/// it later. `None` for EVM chains whose transactions are found by receipts
+pub fn sent_block_number(chain: &Chain, health: &RpcHealth) -> Option<u64> {
+    if chain.chain_type != ChainType::Sub {
+        return None;
+    }
+    chain
+        .with_rpc(health, |endpoint| best_block_number(chain, endpoint))
+        .log_err("Failed to get best block number")
+        .ok()
+}
+
+/// Return true if the block with `block_number` has reached the finality required by `chain`
+pub fn is_finalized(
+    chain: &Chain,
+    block_number: u64,
+    health: &RpcHealth,
+) -> Result<bool, &'static str> {
+    let (latest, depth) = match chain.finality {
+        Finality::Depth(depth) => (
+            chain.with_rpc(health, |endpoint| best_block_number(chain, endpoint))?,
+            depth as u64,
+        ),
+        Finality::Finalized => (
+            chain.with_rpc(health, |endpoint| finalized_block_number(chain, endpoint))?,
+            0,
+        ),
+    };
     pink_extension::debug!(
-        "Trying to fetch tx data for account {:?} from indexer {:?} with nonce {:?}",
-        hex::encode(account),
-        indexer_url,
-        nonce
+        "Block {:?} on {:?}, latest block: {:?}, required depth: {:?}",
+        block_number,
+        &chain.name,
+        latest,
+        depth
     );
-    // nonce from storage is one larger than the last tx's nonce
-    let tx = get_tx(indexer_url, account, nonce)?;
-    pink_extension::debug!("Tx record returned from off-chain indexer: {:?}", tx);
-    if let Some(tx) = tx {
-        return Ok(tx.result);
+    Ok(latest >= block_number + depth)
+}
+
+fn best_block_number(chain: &Chain, endpoint: &str) -> Result<u64, &'static str> {
+    match chain.chain_type {
+        ChainType::Evm => {
+            let eth = Eth::new(PinkHttp::new(endpoint));
+            Ok(resolve_ready(eth.block_number())
+                .or(Err("FailedToGetBlockNumber"))?
+                .as_u64())
+        }
+        ChainType::Sub => {
+            let header: BlockHeader = sub_rpc_call(endpoint, "chain_getHeader", "")?;
+            header.number()
+        }
+    }
+}
+
+fn finalized_block_number(chain: &Chain, endpoint: &str) -> Result<u64, &'static str> {
+    match chain.chain_type {
+        ChainType::Evm => {
+            let eth = Eth::new(PinkHttp::new(endpoint));
+            Ok(
+                resolve_ready(eth.block(BlockId::Number(BlockNumber::Finalized)))
+                    .or(Err("FailedToGetFinalizedBlock"))?
+                    .and_then(|block| block.number)
+                    .ok_or("FailedToGetFinalizedBlock")?
+                    .as_u64(),
+            )
+        }
+        ChainType::Sub => {
+            let finalized_head: String = sub_rpc_call(endpoint, "chain_getFinalizedHead", "")?;
+            let header: BlockHeader = sub_rpc_call(
+                endpoint,
+                "chain_getHeader",
+                &format!(r#""{}""#, finalized_head),
+            )?;
+            header.number()
+        }
+    }
+}
+
+/// Return execution result and inclusion block of transaction sent by `account` with `nonce`,
+/// `None` if the transaction has not been found.
+///
+/// Result from RPC node of the chain is cross-checked with the off-chain indexer, any of
+/// them still works when the other one is unavailable.
+pub fn get_tx_status(
+    chain: &Chain,
+    account: &[u8],
+    nonce: u64,
+    sent_txs: &[ExecuteTx],
+    health: &RpcHealth,
+) -> Result<Option<TxStatus>, &'static str> {
+    let mut providers: Vec<Box<dyn ConfirmationProvider + '_>> = vec![];
+    match chain.chain_type {
+        ChainType::Evm => providers.push(Box::new(EvmReceiptProvider { chain, health })),
+        ChainType::Sub => providers.push(Box::new(SubEventsProvider { chain, health })),
+    }
+    if !chain.tx_indexer_url.is_empty() {
+        providers.push(Box::new(IndexerProvider {
+            url: &chain.tx_indexer_url,
+        }));
     }
 
-    Ok(false)
+    let results: Vec<_> = providers
+        .iter()
+        .map(|provider| {
+            (
+                provider.name(),
+                provider.get_result(account, nonce, sent_txs),
+            )
+        })
+        .collect();
+    reconcile(&results)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::registry::Registry;
+
+    #[test]
+    fn test_extrinsic_result() {
+        // [ApplyExtrinsic(1), Balances.Withdraw, topics], [ApplyExtrinsic(1), System.ExtrinsicSuccess, topics]
+        let events = hex_literal::hex!("08 0001000000 0a08 00 00 0001000000 0000 00");
+        assert_eq!(extrinsic_result(&events, 1), Some(true));
+        assert_eq!(extrinsic_result(&events, 2), None);
+
+        let events = hex_literal::hex!("04 0002000000 0001 00");
+        assert_eq!(extrinsic_result(&events, 2), Some(false));
+    }
+
+    #[test]
+    fn test_xcm_delivery_events() {
+        let hash = [7u8; 32];
+        // [ApplyExtrinsic(2), XcmpQueue.XcmpMessageSent { message_hash: Some(hash) }]
+        let sent = [
+            &hex_literal::hex!("04 0002000000 1e04 01")[..],
+            &hash[..],
+            &[0],
+        ]
+        .concat();
+        let registry = Registry::new();
+        let chain = |name: &str| registry.get_chain(&name.to_string()).unwrap();
+        let phala = chain("Phala");
+        let sender = xcm_sender(&phala).unwrap();
+        assert_eq!(sent_message_hash(&sent, 2, &sender), Some(hash));
+        assert_eq!(sent_message_hash(&sent, 1, &sender), None);
+        assert_eq!(
+            sent_message_hash(&sent, 2, &xcm_sender(&chain("Astar")).unwrap()),
+            None
+        );
+        // [ApplyExtrinsic(2), XcmpQueue.XcmpMessageSent { message_hash: hash }] of later runtimes
+        let sent_v1 = [
+            &hex_literal::hex!("04 0002000000 1e04")[..],
+            &hash[..],
+            &[0],
+        ]
+        .concat();
+        assert_eq!(sent_message_hash(&sent_v1, 2, &sender), None);
+        let sender_v1 = XcmSender::XcmpQueue {
+            pallet_id: 30,
+            event_id: 4,
+            optional_hash: false,
+        };
+        assert_eq!(sent_message_hash(&sent_v1, 2, &sender_v1), Some(hash));
+
+        let (queues, deposits) = xcm_receiver(&phala).unwrap();
+        let recipient = [1u8; 32];
+        let deposit = |event: &[u8], amount: u128| {
+            // [Initialization, event { .., who, amount }]
+            [&[2], event, &recipient[..], &amount.to_le_bytes()[..], &[0]].concat()
+        };
+        // Balances.Deposit, Assets.Issued of asset 1 and an unknown event
+        let balances = |amount| deposit(&hex_literal::hex!("2807"), amount);
+        let assets = |amount| deposit(&hex_literal::hex!("5a01 01000000"), amount);
+        let unknown = |amount| deposit(&hex_literal::hex!("2808"), amount);
+        // [Initialization, XcmpQueue.Success { message_hash: Some(hash), weight }]
+        let success =
+            |hash: &[u8]| [&hex_literal::hex!("02 1e00 01")[..], hash, &[0, 0, 0]].concat();
+        let events = [
+            &[0x1c][..],
+            &balances(100),
+            &success(&[8u8; 32]),
+            &assets(200),
+            &unknown(300),
+            &success(&hash),
+            &balances(400),
+        ]
+        .concat();
+        let message = XcmMessage::Hash(hash);
+        assert_eq!(
+            find_delivery(&events, &message, &recipient, queues, deposits),
+            Ok(Some(XcmDelivery::Succeeded {
+                received: Some(200)
+            }))
+        );
+        assert_eq!(
+            find_delivery(&events, &message, &[2u8; 32], queues, deposits),
+            Ok(Some(XcmDelivery::Succeeded { received: None }))
+        );
+        // Deposit before the previous message doesn't count
+        let events = [
+            &[0x0c][..],
+            &balances(100),
+            &success(&[8u8; 32]),
+            &success(&hash),
+        ]
+        .concat();
+        assert_eq!(
+            find_delivery(&events, &message, &recipient, queues, deposits),
+            Ok(Some(XcmDelivery::Succeeded { received: None }))
+        );
+        assert_eq!(
+            find_delivery(
+                &events,
+                &XcmMessage::Hash([9u8; 32]),
+                &recipient,
+                queues,
+                deposits
+            ),
+            Ok(None)
+        );
+
+        // [Initialization, XcmpQueue.Fail { message_hash: Some(hash), error, weight }]
+        let fail = [
+            &hex_literal::hex!("04 02 1e01 01")[..],
+            &hash[..],
+            &[0x18, 0, 0],
+        ]
+        .concat();
+        assert_eq!(
+            find_delivery(&fail, &message, &recipient, queues, deposits),
+            Ok(Some(XcmDelivery::Failed))
+        );
+
+        // [Initialization, DmpQueue.ExecutedDownward { message_id, outcome: Complete(weight) }]
+        let downward = [&hex_literal::hex!("02 2002")[..], &hash[..], &[0, 0, 0]].concat();
+        let events = [&[0x08][..], &balances(100), &downward].concat();
+        assert_eq!(
+            find_delivery(&events, &message, &recipient, queues, deposits),
+            Ok(Some(XcmDelivery::Succeeded {
+                received: Some(100)
+            }))
+        );
+        assert_eq!(
+            find_delivery(&events, &XcmMessage::Downward, &recipient, queues, deposits),
+            Ok(Some(XcmDelivery::Succeeded {
+                received: Some(100)
+            }))
+        );
+        assert_eq!(
+            find_delivery(&events, &XcmMessage::Downward, &[2u8; 32], queues, deposits),
+            Ok(None)
+        );
+
+        // [Finalization, MessageQueue.Processed { id, origin: Sibling(2004), weight_used, success }]
+        let moonbeam = chain("Moonbeam");
+        let (queues, deposits) = xcm_receiver(&moonbeam).unwrap();
+        let recipient = [1u8; 20];
+        let processed = |success: u8| {
+            [
+                &hex_literal::hex!("01 6f01")[..],
+                &hash[..],
+                &hex_literal::hex!("02 d4070000 0b00a0724e1809 0700e8764817")[..],
+                &[success, 0],
+            ]
+            .concat()
+        };
+        let issued = [
+            &hex_literal::hex!("01 6801")[..],
+            &[3u8; 16],
+            &recipient[..],
+            &500u128.to_le_bytes()[..],
+            &[0],
+        ]
+        .concat();
+        let events = [&[0x08][..], &issued, &processed(1)].concat();
+        assert_eq!(
+            find_delivery(&events, &message, &recipient, queues, deposits),
+            Ok(Some(XcmDelivery::Succeeded {
+                received: Some(500)
+            }))
+        );
+        let events = [&[0x04][..], &processed(0)].concat();
+        assert_eq!(
+            find_delivery(&events, &message, &recipient, queues, deposits),
+            Ok(Some(XcmDelivery::Failed))
+        );
+
+        // Queue events failed to decode are reported
+        let truncated = [&hex_literal::hex!("04 01 6f01")[..], &hash[..], &[2]].concat();
+        assert_eq!(
+            find_delivery(&truncated, &message, &recipient, queues, deposits),
+            Err("InvalidXcmQueueEvent")
+        );
+
+        assert_eq!(xcm_receiver(&chain("Ethereum")), None);
+        assert_eq!(xcm_receiver(&chain("Kusama")), None);
+        assert_eq!(xcm_sender(&chain("Kusama")), Some(XcmSender::Dmp));
+        assert_eq!(xcm_sender(&moonbeam), None);
+    }
+
+    #[test]
+    fn test_scanned_delivery() {
+        let succeeded = XcmDelivery::Succeeded { received: Some(1) };
+        assert_eq!(
+            scanned_delivery(Some(succeeded.clone()), 100, 200),
+            Some(succeeded)
+        );
+        assert_eq!(scanned_delivery(None, 100, 120), Some(XcmDelivery::Pending));
+        assert_eq!(
+            scanned_delivery(None, 100, 100 + MAX_SCAN_BLOCKS as u64),
+            Some(XcmDelivery::Pending)
+        );
+        // Window passed without the message found, checked by balance change instead
+        assert_eq!(
+            scanned_delivery(None, 100, 101 + MAX_SCAN_BLOCKS as u64),
+            None
+        );
+    }
+
+    #[test]
+    fn test_reconcile_cancelled_tx() {
+        let sent_tx = |hash: u8, kind| ExecuteTx {
+            step_index: 0,
+            nonce: 1,
+            hash: vec![hash; 32],
+            kind,
+            sent_at: 0,
+            sent_block: None,
+            dest_block: None,
+        };
+        let indexed_tx = |hash: u8| Transaction {
+            block_number: 10,
+            id: format!("0x{}", hex::encode([hash; 32])),
+            nonce: 1,
+            result: true,
+            timestamp: String::from("0"),
+            account: vec![],
+        };
+        let sent_txs = [sent_tx(1, TxKind::Execute), sent_tx(2, TxKind::Cancel)];
+        let cancelled = Ok(Some(TxStatus {
+            result: false,
+            block_number: 10,
+        }));
+
+        // Cancellation included, which succeeded as a transfer but the step didn't execute
+        assert_eq!(
+            reconcile(&[
+                ("rpc", cancelled.clone()),
+                ("indexer", Ok(indexed_tx_status(&indexed_tx(2), &sent_txs)))
+            ]),
+            cancelled
+        );
+        // Indexer alone doesn't settle a cancelled step
+        assert_eq!(
+            reconcile(&[
+                ("rpc", Err("CallRpcFailed")),
+                ("indexer", Ok(indexed_tx_status(&indexed_tx(2), &sent_txs)))
+            ]),
+            cancelled
+        );
+        // Unknown transaction while a cancellation was sent
+        assert_eq!(indexed_tx_status(&indexed_tx(3), &sent_txs), None);
+        assert_eq!(
+            indexed_tx_status(&indexed_tx(1), &sent_txs),
+            Some(TxStatus {
+                result: true,
+                block_number: 10
+            })
+        );
+        assert_eq!(
+            indexed_tx_status(&indexed_tx(3), &sent_txs[..1]).map(|status| status.result),
+            Some(true)
+        );
+    }
+
+    #[test]
+    fn test_reconcile_confirmations() {
+        let status = |result, block_number| -> Result<Option<TxStatus>, &'static str> {
+            Ok(Some(TxStatus {
+                result,
+                block_number,
+            }))
+        };
+
+        assert_eq!(
+            reconcile(&[("rpc", status(true, 10)), ("indexer", status(true, 9))]),
+            status(true, 10)
+        );
+        // Fallback to the other provider
+        assert_eq!(
+            reconcile(&[("rpc", Err("CallRpcFailed")), ("indexer", status(true, 9))]),
+            status(true, 9)
+        );
+        assert_eq!(
+            reconcile(&[("rpc", status(false, 10)), ("indexer", Ok(None))]),
+            status(false, 10)
+        );
+        assert_eq!(
+            reconcile(&[("rpc", Ok(None)), ("indexer", Err("CallIndexerFailed"))]),
+            Ok(None)
+        );
+        assert_eq!(
+            reconcile(&[
+                ("rpc", Err("CallRpcFailed")),
+                ("indexer", Err("CallIndexerFailed"))
+            ]),
+            Err("ConfirmationUnavailable")
+        );
+        assert_eq!(
+            reconcile(&[("rpc", status(false, 10)), ("indexer", status(true, 10))]),
+            Err("ConfirmationMismatch")
+        );
+    }
     #[test]
     #[ignore]
     fn should_work() {
@@ -47,3 +47,92 @@ pub fn slice_to_generalkey(key: &[u8]) -> Junction {
 pub fn h160_to_sr25519_pub(addr: &[u8]) -> [u8; 32] {
     Blake2_256::hash(&[b"evm:", addr].concat())
 }
+
+/// (De)serialize byte arrays as hex strings with `0x` prefix, used by JSON representations
+pub mod serde_hex {
+    use alloc::{format, string::String, vec::Vec};
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer, const N: usize>(
+        data: &[u8; N],
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&format!("0x{}", hex::encode(data)))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>, const N: usize>(
+        deserializer: D,
+    ) -> Result<[u8; N], D::Error> {
+        let s = String::deserialize(deserializer)?;
+        let data: Vec<u8> =
+            hex::decode(s.trim_start_matches("0x")).map_err(|_| D::Error::custom("InvalidHex"))?;
+        data.try_into()
+            .map_err(|_| D::Error::custom("InvalidLength"))
+    }
+}
+
+/// (De)serialize lists of byte arrays as lists of hex strings with `0x` prefix
+pub mod serde_hex_list {
+    use alloc::{format, string::String, vec::Vec};
+    use serde::{de::Error, ser::SerializeSeq, Deserialize, Deserializer, Serializer};
+
+    // Takes `&Vec` since the field is passed as is by `#[serde(with)]`
+    #[allow(clippy::ptr_arg)]
+    pub fn serialize<S: Serializer, const N: usize>(
+        data: &Vec<[u8; N]>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        let mut seq = serializer.serialize_seq(Some(data.len()))?;
+        for item in data {
+            seq.serialize_element(&format!("0x{}", hex::encode(item)))?;
+        }
+        seq.end()
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>, const N: usize>(
+        deserializer: D,
+    ) -> Result<Vec<[u8; N]>, D::Error> {
+        Vec::<String>::deserialize(deserializer)?
+            .iter()
+            .map(|s| {
+                let data: Vec<u8> = hex::decode(s.trim_start_matches("0x"))
+                    .map_err(|_| D::Error::custom("InvalidHex"))?;
+                data.try_into()
+                    .map_err(|_| D::Error::custom("InvalidLength"))
+            })
+            .collect()
+    }
+}
+
+/// (De)serialize byte vectors as hex strings with `0x` prefix
+pub mod serde_hex_bytes {
+    use alloc::{format, string::String, vec::Vec};
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+
+    // Takes `&Vec` since the field is passed as is by `#[serde(with)]`
+    #[allow(clippy::ptr_arg)]
+    pub fn serialize<S: Serializer>(data: &Vec<u8>, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&format!("0x{}", hex::encode(data)))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        hex::decode(s.trim_start_matches("0x")).map_err(|_| D::Error::custom("InvalidHex"))
+    }
+}
+
+/// (De)serialize amounts as decimal strings, JSON numbers can't hold all `u128` values
+pub mod serde_amount {
+    use alloc::string::{String, ToString};
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(amount: &u128, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&amount.to_string())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u128, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(|_| D::Error::custom("InvalidAmount"))
+    }
+}